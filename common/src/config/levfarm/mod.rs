@@ -141,3 +141,47 @@ pub struct LevFarmConfig {
     pub base_token_mint: Pubkey,
     pub quote_token_mint: Pubkey,
 }
+
+impl LevFarmConfig {
+    /// returns the configured addresses in a deterministic order suitable for storing in an
+    /// address lookup table, omitting duplicates
+    pub fn lookup_table_addresses(&self) -> Vec<Pubkey> {
+        crate::packer::lookup_table_addresses([
+            self.lending_program,
+            self.solfarm_vault_program,
+            self.account,
+            self.global,
+            self.vault_account,
+            self.base_token_account,
+            self.quote_token_account,
+            self.serum_market,
+            self.serum_request_queue,
+            self.serum_event_queue,
+            self.serum_bids,
+            self.serum_asks,
+            self.serum_coin_vault,
+            self.serum_pc_vault,
+            self.serum_fee_receiver,
+            self.serum_open_orders,
+            self.lp_mint,
+            self.amm_id,
+            self.amm_open_orders,
+            self.amm_quantities_or_target_orders,
+            self.amm_coin_account,
+            self.amm_pc_account,
+            self.amm_temp_account,
+            self.amm_withdraw_queue,
+            self.lending_market,
+            self.lending_market_authority,
+            self.lp_price_account,
+            self.coin_price_account,
+            self.pc_price_account,
+            self.coin_reserve_fee_receiver,
+            self.pc_reserve_fee_receiver,
+            self.base_reserve,
+            self.quote_reserve,
+            self.base_token_mint,
+            self.quote_token_mint,
+        ])
+    }
+}
@@ -53,7 +53,7 @@ pub mod market_config {
     pub const SERUM_COIN_VAULT: Pubkey =
         static_pubkey!("GGcdamvNDYFhAXr93DWyJ8QmwawUHLCyRqWL3KngtLRa");
     pub const SERUM_PC_VAULT: Pubkey =
-        static_pubkey!("GGcdamvNDYFhAXr93DWyJ8QmwawUHLCyRqWL3KngtLRa");
+        static_pubkey!("22jHt5WmosAykp3LPGSAKgY45p7VGh4DFWSwp21SWBVe");
     pub const SERUM_FEE_RECEIVER: Pubkey =
         static_pubkey!("PgjTxGVTmFGQjGX8DrETmT68hRt6Jyps4SFTnxJcf3S");
     pub const SERUM_OPEN_ORDERS: Pubkey =
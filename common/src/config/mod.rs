@@ -31,5 +31,7 @@ pub const RAYDIUM_LIQUIDITY_V4: Pubkey =
     static_pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
 /// address of first raydium staking program
 pub const RAYDIUM_STAKE: Pubkey = static_pubkey!("EhhTKczWMGQt46ynNeRX1WfeagwwJd7ufHvCDjRxjo5Q");
+/// address of the dual reward raydium staking program
+pub const RAYDIUM_STAKE_V4: Pubkey = static_pubkey!("CBuCnLe26faBpcBP2fktp4rp8abpcAnTWft6ZrP5Q4T");
 /// address of the latest raydium staking program
 pub const RAYDIUM_STAKE_V5: Pubkey = static_pubkey!("9KEPoZmtHUrBbhWN1v1KWLMkkvwY6WLtAVUCPRtRjP4z");
@@ -1,6 +1,7 @@
 //! tulip strategy vault configurations
 
 use self::traits::{MultiVaultProgramConfig, StandaloneVaultProgramConfig};
+use anchor_lang::solana_program::{pubkey::Pubkey, system_program, sysvar};
 
 pub mod traits;
 pub mod withdraw;
@@ -24,6 +25,10 @@ pub enum Platform {
     Solend,
 }
 
+impl Platform {
+    pub const ALL: [Platform; 3] = [Platform::MangoV3, Platform::Tulip, Platform::Solend];
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum StrategyVaults {
     USDCv1,
@@ -33,6 +38,12 @@ pub enum StrategyVaults {
 }
 
 impl StrategyVaults {
+    pub const ALL: [StrategyVaults; 4] = [
+        StrategyVaults::USDCv1,
+        StrategyVaults::SOLv1,
+        StrategyVaults::RAYv1,
+        StrategyVaults::USDTv1,
+    ];
     /// returns the multi deposit vault program configuration trait for strategy vault `self`
     pub fn multi_deposit_config(&self) -> Box<dyn MultiVaultProgramConfig> {
         match self {
@@ -60,6 +71,46 @@ impl StrategyVaults {
             Self::USDTv1 => self.multi_deposit_config().standalone_config(platform),
         }
     }
+    /// returns the addresses used when depositing into, withdrawing from, and rebalancing
+    /// strategy vault `self` in a deterministic order suitable for storing in an address
+    /// lookup table. this includes the multi deposit vault, followed by the standalone vault
+    /// and platform accounts of each platform
+    pub fn lookup_table_addresses(&self) -> Vec<Pubkey> {
+        let config = self.multi_deposit_config();
+        let mut addresses = vec![
+            crate::config::ID,
+            spl_token::id(),
+            system_program::id(),
+            sysvar::clock::id(),
+            sysvar::rent::id(),
+            config.account(),
+            config.pda(),
+            config.shares_mint(),
+            config.underlying_mint(),
+            config.underlying_compound_queue(),
+            config.underlying_deposit_queue(),
+            config.underlying_withdraw_queue(),
+            config.rebalance_state_transition(),
+            config.rebalance_state_transition_underlying(),
+        ];
+        for platform in Platform::ALL {
+            let standalone = config.standalone_config(platform);
+            addresses.extend_from_slice(&[
+                config.optimizer_shares_account(platform),
+                standalone.account(),
+                standalone.pda(),
+                standalone.shares_mint(),
+                standalone.underlying_compound_queue(),
+                standalone.underlying_deposit_queue(),
+                standalone.underlying_withdraw_queue(),
+                standalone.config_data_account(),
+                standalone.information_account(),
+                standalone.program_id(),
+            ]);
+            addresses.extend(config.remaining_accounts(platform));
+        }
+        crate::packer::lookup_table_addresses(addresses)
+    }
 }
 
 /// given address `vault`, return the corresponding multi deposit vault configuration trait.
@@ -769,4 +820,24 @@ mod test {
             }
         );
     }
+    #[test]
+    fn test_strategy_vault_lookup_table_addresses() {
+        for vault in StrategyVaults::ALL {
+            let addresses = vault.lookup_table_addresses();
+            assert_eq!(addresses, vault.lookup_table_addresses());
+            assert!(addresses.len() <= crate::packer::MAX_LOOKUP_TABLE_ADDRESSES);
+            let unique: std::collections::BTreeSet<_> = addresses.iter().collect();
+            assert_eq!(unique.len(), addresses.len());
+            let config = vault.multi_deposit_config();
+            assert!(addresses.contains(&config.account()));
+            assert!(addresses.contains(&config.shares_mint()));
+            for platform in Platform::ALL {
+                assert!(addresses.contains(&config.optimizer_shares_account(platform)));
+                assert!(addresses.contains(&config.standalone_config(platform).account()));
+                for account in config.remaining_accounts(platform) {
+                    assert!(addresses.contains(&account));
+                }
+            }
+        }
+    }
 }
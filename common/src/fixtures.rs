@@ -0,0 +1,270 @@
+//! provides an `AccountProvider` abstraction used to run tests against recorded accounts
+//! instead of a live rpc node. accounts are recorded in the json format output by
+//! `solana account <address> --output json`, and the recorded fixtures used by the sdk's
+//! own tests are stored in `fixtures/accounts` at the root of the repository.
+//!
+//! fixtures can be re-recorded, or new fixtures added using `scripts/record_fixtures.sh`
+
+use anchor_lang::solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// directory containing the recorded fixtures used by the sdk's tests
+pub const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../fixtures/accounts");
+
+/// an owned copy of an account's state
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FixtureAccount {
+    pub lamports: u64,
+    data: AccountData,
+    pub owner: Pubkey,
+    pub executable: bool,
+    pub rent_epoch: u64,
+}
+
+impl FixtureAccount {
+    pub fn new(lamports: u64, data: &[u8], owner: Pubkey) -> Self {
+        Self {
+            lamports,
+            data: AccountData::new(data),
+            owner,
+            ..Default::default()
+        }
+    }
+    pub fn data(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        self.data.as_mut_slice()
+    }
+    /// returns an `AccountInfo` borrowing the account, which is neither a signer nor writable
+    pub fn account_info<'a>(&'a mut self, key: &'a Pubkey) -> AccountInfo<'a> {
+        AccountInfo::new(
+            key,
+            false,
+            false,
+            &mut self.lamports,
+            self.data.as_mut_slice(),
+            &self.owner,
+            self.executable,
+            self.rent_epoch,
+        )
+    }
+    /// parses an account from the json output of `solana account <address> --output json`,
+    /// returning the account's address along with the account
+    pub fn from_json(json: &str) -> Result<(Pubkey, Self)> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let key = parse_pubkey(&value["pubkey"])?;
+        let account = &value["account"];
+        let data: Vec<u8> = match account["data"].as_array().map(|data| &data[..]) {
+            Some([data, encoding]) if encoding == "base64" => base64::decode(
+                data.as_str()
+                    .ok_or_else(|| invalid_data("account data is not a string"))?,
+            )
+            .map_err(invalid_data)?,
+            _ => return Err(invalid_data("account data is not base64 encoded")),
+        };
+        Ok((
+            key,
+            Self {
+                lamports: parse_u64(&account["lamports"])?,
+                data: AccountData::new(&data),
+                owner: parse_pubkey(&account["owner"])?,
+                executable: account["executable"].as_bool().unwrap_or_default(),
+                rent_epoch: parse_u64(&account["rentEpoch"])?,
+            },
+        ))
+    }
+    /// reads an account from a json file, see [FixtureAccount::from_json]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<(Pubkey, Self)> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// account data, stored such that the data following the 8 byte anchor discriminator is
+/// 16 byte aligned. this allows zero copy accounts containing u128's to be loaded on
+/// targets where u128's have an alignment of 16, unlike the 8 byte alignment used on-chain
+#[derive(Clone, Default)]
+struct AccountData {
+    words: Vec<u128>,
+    len: usize,
+}
+
+impl AccountData {
+    const OFFSET: usize = 8;
+    fn new(data: &[u8]) -> Self {
+        let mut account_data = Self {
+            words: vec![0_u128; (Self::OFFSET + data.len()) / 16 + 1],
+            len: data.len(),
+        };
+        account_data.as_mut_slice().copy_from_slice(data);
+        account_data
+    }
+    fn as_slice(&self) -> &[u8] {
+        &bytemuck::cast_slice(&self.words)[Self::OFFSET..Self::OFFSET + self.len]
+    }
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut bytemuck::cast_slice_mut(&mut self.words)[Self::OFFSET..Self::OFFSET + self.len]
+    }
+}
+
+impl std::fmt::Debug for AccountData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl PartialEq for AccountData {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for AccountData {}
+
+/// provides access to accounts by their address
+pub trait AccountProvider {
+    /// returns the account stored at `key`, or None if the account does not exist
+    fn get_account(&self, key: &Pubkey) -> Option<FixtureAccount>;
+}
+
+/// an account provider storing all accounts in memory
+#[derive(Clone, Debug, Default)]
+pub struct MemoryAccountProvider {
+    accounts: HashMap<Pubkey, FixtureAccount>,
+}
+
+impl MemoryAccountProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// loads all json files within `dir`
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut provider = Self::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension() == Some("json".as_ref()) {
+                let (key, account) = FixtureAccount::from_file(&path)?;
+                provider.insert(key, account);
+            }
+        }
+        Ok(provider)
+    }
+    /// stores `account` at `key`, returning the previously stored account if any
+    pub fn insert(&mut self, key: Pubkey, account: FixtureAccount) -> Option<FixtureAccount> {
+        self.accounts.insert(key, account)
+    }
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+impl AccountProvider for MemoryAccountProvider {
+    fn get_account(&self, key: &Pubkey) -> Option<FixtureAccount> {
+        self.accounts.get(key).cloned()
+    }
+}
+
+/// an account provider reading accounts from json files named `<address>.json`, which
+/// are only read when the account is requested
+#[derive(Clone, Debug)]
+pub struct JsonFileAccountProvider {
+    dir: PathBuf,
+}
+
+impl JsonFileAccountProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+    /// returns a provider reading the sdk's recorded fixtures
+    pub fn fixtures() -> Self {
+        Self::new(FIXTURES_DIR)
+    }
+}
+
+impl AccountProvider for JsonFileAccountProvider {
+    /// returns None if the file does not exist, and panics if the file is malformed
+    /// or recorded for a different address
+    fn get_account(&self, key: &Pubkey) -> Option<FixtureAccount> {
+        let path = self.dir.join(format!("{}.json", key));
+        if !path.exists() {
+            return None;
+        }
+        let (got_key, account) = FixtureAccount::from_file(&path)
+            .unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+        assert_eq!(
+            got_key,
+            *key,
+            "{} is recorded for another address",
+            path.display()
+        );
+        Some(account)
+    }
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Error {
+    Error::new(ErrorKind::InvalidData, err)
+}
+
+fn parse_pubkey(value: &serde_json::Value) -> Result<Pubkey> {
+    let value = value
+        .as_str()
+        .ok_or_else(|| invalid_data("address is not a string"))?;
+    Pubkey::from_str(value).map_err(|err| invalid_data(err.to_string()))
+}
+
+fn parse_u64(value: &serde_json::Value) -> Result<u64> {
+    value
+        .as_u64()
+        .ok_or_else(|| invalid_data("value is not a u64"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use static_pubkey::static_pubkey;
+    #[test]
+    fn test_account_providers() {
+        let usdcv1_vault = static_pubkey!("3wPiV9inTGexMZjp6x5Amqwp2sRNtpSheG8Hbv2rgq8W");
+        let memory = MemoryAccountProvider::from_dir(FIXTURES_DIR).unwrap();
+        assert!(!memory.is_empty());
+        let files = JsonFileAccountProvider::fixtures();
+        let mut account = files.get_account(&usdcv1_vault).unwrap();
+        assert_eq!(memory.get_account(&usdcv1_vault).unwrap(), account);
+        assert_eq!(account.owner, crate::config::ID);
+        let info = account.account_info(&usdcv1_vault);
+        assert_eq!(info.data_len(), 2072);
+        assert!(!info.is_writable);
+        // zero copy account data following the discriminator is 16 byte aligned
+        assert_eq!(info.data.borrow()[8..].as_ptr() as usize % 16, 0);
+        assert!(files.get_account(&Pubkey::new_unique()).is_none());
+        assert!(memory.get_account(&Pubkey::new_unique()).is_none());
+
+        assert!(FixtureAccount::from_json("{}").is_err());
+        let json = r#"{
+            "pubkey": "3wPiV9inTGexMZjp6x5Amqwp2sRNtpSheG8Hbv2rgq8W",
+            "account": {
+                "lamports": 1,
+                "data": ["AQID", "base64"],
+                "owner": "11111111111111111111111111111111",
+                "executable": false,
+                "rentEpoch": 2
+            }
+        }"#;
+        let (key, account) = FixtureAccount::from_json(json).unwrap();
+        assert_eq!(key, usdcv1_vault);
+        assert_eq!(
+            account,
+            FixtureAccount {
+                rent_epoch: 2,
+                ..FixtureAccount::new(1, &[1, 2, 3], Pubkey::default())
+            }
+        );
+        assert!(FixtureAccount::from_json(&json.replace("base64", "base58")).is_err());
+    }
+}
@@ -30,38 +30,42 @@ pub fn get_leverage_farm_lp_mint(
 mod test {
     use std::str::FromStr;
 
-    use anchor_client::solana_client::rpc_client::RpcClient;
-    use solana_program::account_info::IntoAccountInfo;
+    use crate::fixtures::{AccountProvider, JsonFileAccountProvider};
+    use solana_program::program_pack::Pack;
 
     use super::*;
-    #[test]
-    fn test_get_leverage_farm_lp_mint_orca() {
-        let basis_usdc_orca_lev_farm_account =
-            Pubkey::from_str("7dKmQgDoXJ5gBeugwzwXHHE15ypVMBfqffmbJXqnvmcH").unwrap();
-        let want_basis_usdc_orca_lp_mint =
-            Pubkey::from_str("GoaAiajubRgeCFEz9L6mLnSmT2QFegoJDH5tpLfivpj").unwrap();
-        let rpc_client = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
-        let lev_farm_account = rpc_client
-            .get_account(&basis_usdc_orca_lev_farm_account)
-            .unwrap();
-        let mut lev_farm_tuple = (basis_usdc_orca_lev_farm_account, lev_farm_account);
-        let lev_farm_account_info = lev_farm_tuple.into_account_info();
+    fn test_get_leverage_farm_lp_mint(lev_farm_key: &str, lp_mint_key: &str) {
+        let lev_farm_key = Pubkey::from_str(lev_farm_key).unwrap();
+        let want_lp_mint = Pubkey::from_str(lp_mint_key).unwrap();
+        let fixtures = JsonFileAccountProvider::fixtures();
+        let mut lev_farm_account = fixtures.get_account(&lev_farm_key).unwrap();
+        let lev_farm_account_info = lev_farm_account.account_info(&lev_farm_key);
         let got_lp_mint = get_leverage_farm_lp_mint(&lev_farm_account_info).unwrap();
-        assert_eq!(got_lp_mint, want_basis_usdc_orca_lp_mint);
+        assert_eq!(got_lp_mint, want_lp_mint);
+        // the lp mint must be an initialized token mint
+        let lp_mint_account = fixtures.get_account(&got_lp_mint).unwrap();
+        assert_eq!(lp_mint_account.owner, spl_token::id());
+        assert!(spl_token::state::Mint::unpack(lp_mint_account.data()).is_ok());
     }
     #[test]
-    fn test_get_leverage_farm_lp_mint_raydium() {
-        let stsol_usdc_ray_lev_farm_account =
-            Pubkey::from_str("2RwEGydvxM7ZuHLgRmKyfve1qYc8ZNsd39BBZtK38CeX").unwrap();
-        let want_stsol_usdc_ray_lp_mint =
-            Pubkey::from_str("HDUJMwYZkjUZre63xUeDhdCi8c6LgUDiBqxmP3QC3VPX").unwrap();
-        let rpc_client = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
-        let lev_farm_account = rpc_client
-            .get_account(&stsol_usdc_ray_lev_farm_account)
-            .unwrap();
-        let mut lev_farm_tuple = (want_stsol_usdc_ray_lp_mint, lev_farm_account);
-        let lev_farm_account_info = lev_farm_tuple.into_account_info();
-        let got_lp_mint = get_leverage_farm_lp_mint(&lev_farm_account_info).unwrap();
-        assert_eq!(got_lp_mint, want_stsol_usdc_ray_lp_mint);
+    fn test_get_leverage_farm_lp_mint_fixtures() {
+        // ORCA-USDC orca farm, and RAY-USDC raydium farm
+        test_get_leverage_farm_lp_mint(
+            "5o3EsLS1NTciKHXVsGNYqQQ8iBBK3dBfSPwCH7wsdtRT",
+            "n8Mpu28RjeYD7oUX3LG1tPxzhRZh3YYLRSHcHRdS3Zx",
+        );
+        test_get_leverage_farm_lp_mint(
+            "84ayseJgpJavzfeESgRdyfMoDo2bs4J2YUBjMT4iTs66",
+            "FbC6K13MzHvN42bXrtGaWsvZY9fxrackRSZcBGfjPc7m",
+        );
+    }
+    #[test]
+    fn test_get_leverage_farm_lp_mint_too_small() {
+        let key = Pubkey::new_unique();
+        let mut account = crate::fixtures::FixtureAccount::new(0, &[0_u8; 100], Pubkey::default());
+        assert_eq!(
+            get_leverage_farm_lp_mint(&account.account_info(&key)),
+            Err(ProgramError::AccountDataTooSmall)
+        );
     }
 }
@@ -1079,3 +1079,178 @@ impl Pack for Reserve {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    prop_compose! {
+        fn reserve_config()(
+            optimal_utilization_rate in 1..=99_u8,
+            borrow_rates in prop::array::uniform4(0..=250_u8),
+        )(
+            degen_utilization_rate in optimal_utilization_rate..=99,
+            optimal_utilization_rate in Just(optimal_utilization_rate),
+            mut borrow_rates in Just(borrow_rates),
+        ) -> ReserveConfig {
+            borrow_rates.sort_unstable();
+            ReserveConfig {
+                optimal_utilization_rate,
+                degen_utilization_rate,
+                min_borrow_rate: borrow_rates[0],
+                optimal_borrow_rate: borrow_rates[1],
+                degen_borrow_rate: borrow_rates[2],
+                max_borrow_rate: borrow_rates[3],
+                ..Default::default()
+            }
+        }
+    }
+
+    fn reserve_with_fees(borrow_fee_wad: u64, host_fee_percentage: u8) -> Reserve {
+        let mut reserve = Reserve::default();
+        reserve.liquidity.mint_decimals = 6;
+        reserve.liquidity.market_price = Decimal::one();
+        reserve.config.fees = ReserveFees {
+            borrow_fee_wad,
+            flash_loan_fee_wad: 0,
+            host_fee_percentage,
+        };
+        reserve
+    }
+
+    proptest! {
+        #[test]
+        fn collateral_round_trip_never_creates_value(
+            rate in 1..=100 * WAD as u128,
+            amount in any::<u64>(),
+        ) {
+            let exchange_rate = CollateralExchangeRate(Rate::from_scaled_val_big(rate));
+            if let Ok(collateral) = exchange_rate.liquidity_to_collateral(amount) {
+                let liquidity = exchange_rate.collateral_to_liquidity(collateral).unwrap();
+                prop_assert!(liquidity <= amount);
+            }
+            if let Ok(liquidity) = exchange_rate.collateral_to_liquidity(amount) {
+                if let Ok(collateral) = exchange_rate.liquidity_to_collateral(liquidity) {
+                    prop_assert!(collateral <= amount);
+                }
+            }
+        }
+
+        #[test]
+        fn borrow_fees_never_exceed_amount(
+            amount in 1..u64::MAX,
+            borrow_fee_wad in 0..WAD / 10,
+            host_fee_percentage in 0..=100_u8,
+        ) {
+            let reserve = reserve_with_fees(borrow_fee_wad, host_fee_percentage);
+            let max_borrow_value = Decimal::from(u64::MAX).try_mul(u64::MAX).unwrap();
+            match reserve.calculate_borrow(amount, max_borrow_value) {
+                Ok(result) => {
+                    prop_assert!(Decimal::from(result.borrow_fee) < result.borrow_amount);
+                    prop_assert!(result.host_fee <= result.borrow_fee);
+                    prop_assert_eq!(result.receive_amount, amount);
+                    prop_assert_eq!(
+                        result.borrow_amount,
+                        Decimal::from(amount).try_add(result.borrow_fee.into()).unwrap()
+                    );
+                }
+                Err(err) => prop_assert_eq!(err, LendingError::BorrowTooSmall.into()),
+            }
+        }
+
+        #[test]
+        fn inclusive_borrow_fees_never_exceed_amount(
+            available_amount in 1..u64::MAX,
+            max_borrow_value in 1..1_000_000_000_000_000_u64,
+            borrow_fee_wad in 0..WAD / 10,
+            host_fee_percentage in 0..=100_u8,
+        ) {
+            let mut reserve = reserve_with_fees(borrow_fee_wad, host_fee_percentage);
+            reserve.liquidity.available_amount = available_amount;
+            match reserve.calculate_borrow(u64::MAX, Decimal::from(max_borrow_value)) {
+                Ok(result) => {
+                    prop_assert!(result.borrow_amount <= Decimal::from(available_amount));
+                    prop_assert!(result.host_fee <= result.borrow_fee);
+                    prop_assert_eq!(
+                        result.receive_amount + result.borrow_fee,
+                        result.borrow_amount.try_floor_u64().unwrap()
+                    );
+                }
+                Err(err) => prop_assert_eq!(err, LendingError::BorrowTooSmall.into()),
+            }
+        }
+
+        #[test]
+        fn repay_never_exceeds_borrowed(
+            amount_to_repay in any::<u64>(),
+            borrowed_amount in 0..u64::MAX as u128 * WAD as u128,
+        ) {
+            let reserve = Reserve::default();
+            let borrowed_amount = Decimal::from_scaled_val(borrowed_amount);
+            let result = reserve.calculate_repay(amount_to_repay, borrowed_amount).unwrap();
+            prop_assert!(result.settle_amount <= borrowed_amount);
+            prop_assert!(Decimal::from(result.repay_amount) >= result.settle_amount);
+            prop_assert!(result.repay_amount <= borrowed_amount.try_ceil_u64().unwrap());
+            if amount_to_repay != u64::MAX {
+                prop_assert!(result.settle_amount <= Decimal::from(amount_to_repay));
+            }
+        }
+
+        #[test]
+        fn accrue_interest_is_monotonic_in_slot(
+            config in reserve_config(),
+            available_amount in 0..u64::MAX / 2,
+            borrowed_amount in 0..u64::MAX / 2,
+            slot in 0..10_000_000_u64,
+            later_slots in 0..10_000_000_u64,
+        ) {
+            let mut reserve = Reserve {
+                config,
+                ..Default::default()
+            };
+            reserve.liquidity.available_amount = available_amount;
+            reserve.liquidity.borrowed_amount_wads = Decimal::from(borrowed_amount);
+            reserve.liquidity.cumulative_borrow_rate_wads = Decimal::one();
+            let mut earlier = reserve.clone();
+            earlier.accrue_interest(slot).unwrap();
+            let mut later = reserve;
+            later.accrue_interest(slot + later_slots).unwrap();
+            prop_assert!(Decimal::one() <= earlier.liquidity.cumulative_borrow_rate_wads);
+            prop_assert!(
+                earlier.liquidity.cumulative_borrow_rate_wads
+                    <= later.liquidity.cumulative_borrow_rate_wads
+            );
+            prop_assert!(
+                earlier.liquidity.borrowed_amount_wads <= later.liquidity.borrowed_amount_wads
+            );
+        }
+    }
+
+    #[test]
+    fn test_recorded_reserves() {
+        use crate::fixtures::{AccountProvider, JsonFileAccountProvider};
+        use std::str::FromStr;
+        let fixtures = JsonFileAccountProvider::fixtures();
+        let lending_market =
+            Pubkey::from_str("D1cqtVThyebK9KXKGXrCEuiqaNf5L4UfM1vHgCqiJxym").unwrap();
+        for reserve_key in [
+            "6sJg8f3zcAjrd38QhSA3C34n8MzLq1XVTiQr4msozAuv",
+            "9Bm8d2izGsf9eT6Wr79DTnXBkW2LHYVQa57QzeoTbsAF",
+            "FTkSmGsJ3ZqDSHdcnY7ejN1pWV3Ej7i88MYpZyyaqgGt",
+        ] {
+            let account = fixtures
+                .get_account(&Pubkey::from_str(reserve_key).unwrap())
+                .unwrap();
+            let mut reserve = Reserve::unpack(account.data()).unwrap();
+            assert_eq!(reserve.lending_market, lending_market);
+            assert!(reserve.current_borrow_rate().is_ok());
+            assert!(reserve.collateral_exchange_rate().is_ok());
+            let borrowed = reserve.liquidity.borrowed_amount_wads;
+            reserve
+                .accrue_interest(reserve.last_update.slot + 1_000)
+                .unwrap();
+            assert!(reserve.liquidity.borrowed_amount_wads >= borrowed);
+        }
+    }
+}
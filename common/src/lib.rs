@@ -5,8 +5,12 @@
 #![allow(clippy::too_many_arguments)]
 
 pub mod config;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
 pub mod lending;
 pub mod math;
+pub mod packer;
+pub mod pricing;
 pub mod pyth;
 pub mod tag;
 pub mod traits;
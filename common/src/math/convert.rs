@@ -0,0 +1,309 @@
+//! lossless conversions of `Decimal` and `Rate` to and from strings and f64's, intended
+//! for off-chain usage. all conversions report errors as a `MathError`, and fail with
+//! `MathError::PrecisionLoss` instead of silently rounding, unless explicitly stated otherwise.
+//!
+//! enabling the `serde` feature serializes both types as decimal strings
+
+use super::common::SCALE;
+use super::decimal::Decimal;
+use super::error::MathError;
+use super::rate::{Rate, U128};
+use super::uint::U192;
+use std::str::FromStr;
+
+/// parses a non-negative decimal string such as "1.2345" into a value scaled by a WAD
+pub(crate) fn parse_scaled(val: &str) -> Result<U192, MathError> {
+    let (integer, fraction) = match val.split_once('.') {
+        Some((integer, fraction)) => {
+            if fraction.is_empty() {
+                return Err(MathError::InvalidNumber);
+            }
+            (integer, fraction)
+        }
+        None => (val, ""),
+    };
+    if integer.is_empty()
+        || !integer.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(MathError::InvalidNumber);
+    }
+    // trailing zeros past the supported precision don't lose any information
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > SCALE {
+        return Err(MathError::PrecisionLoss);
+    }
+    let integer = U192::from_dec_str(integer).map_err(|_| MathError::MathOverflow)?;
+    let fraction = if fraction.is_empty() {
+        U192::zero()
+    } else {
+        // fraction is at most 18 digits, which always fits
+        U192::from_dec_str(fraction).map_err(|_| MathError::InvalidNumber)?
+            * U192::exp10(SCALE - fraction.len())
+    };
+    integer
+        .checked_mul(U192::exp10(SCALE))
+        .and_then(|integer| integer.checked_add(fraction))
+        .ok_or(MathError::MathOverflow)
+}
+
+/// formats a value scaled by a WAD with `precision` decimal places,
+/// rounding half up when `precision` is less than 18
+pub(crate) fn format_scaled(scaled: U192, precision: usize) -> String {
+    let (value, digits) = if precision < SCALE {
+        let divisor = U192::exp10(SCALE - precision);
+        let (mut quotient, remainder) = scaled.div_mod(divisor);
+        if remainder >= divisor / 2 {
+            // quotient is at most U192::MAX / 10, so this can't overflow
+            quotient += U192::from(1_u64);
+        }
+        (quotient, precision)
+    } else {
+        (scaled, SCALE)
+    };
+    let mut formatted = value.to_string();
+    if digits > 0 {
+        if formatted.len() <= digits {
+            formatted.insert_str(0, &"0".repeat(digits + 1 - formatted.len()));
+        }
+        formatted.insert(formatted.len() - digits, '.');
+        formatted.push_str(&"0".repeat(precision - digits));
+    }
+    formatted
+}
+
+/// parses the shortest decimal representation of `val`, which round trips back into the same f64
+fn parse_f64(val: f64) -> Result<U192, MathError> {
+    if !val.is_finite() || val.is_sign_negative() && val != 0.0 {
+        return Err(MathError::InvalidNumber);
+    }
+    parse_scaled(&val.abs().to_string())
+}
+
+/// parses `val` rounded to 18 decimal places
+fn parse_f64_lossy(val: f64) -> Result<U192, MathError> {
+    if !val.is_finite() || val.is_sign_negative() && val != 0.0 {
+        return Err(MathError::InvalidNumber);
+    }
+    parse_scaled(&format!("{:.*}", SCALE, val.abs()))
+}
+
+impl FromStr for Decimal {
+    type Err = MathError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(parse_scaled(s)?))
+    }
+}
+
+impl Decimal {
+    /// formats the decimal with `precision` decimal places, rounding half up.
+    /// equivalent to `format!("{:.precision$}", decimal)`
+    pub fn to_string_with_precision(&self, precision: usize) -> String {
+        format_scaled(self.0, precision)
+    }
+    /// converts an f64 into a decimal, failing if the shortest representation
+    /// of `val` has more than 18 decimal places
+    pub fn try_from_f64(val: f64) -> Result<Self, MathError> {
+        Ok(Self(parse_f64(val)?))
+    }
+    /// converts an f64 into a decimal, rounding to 18 decimal places
+    pub fn from_f64_lossy(val: f64) -> Result<Self, MathError> {
+        Ok(Self(parse_f64_lossy(val)?))
+    }
+    /// converts the decimal into the nearest f64
+    pub fn to_f64_lossy(&self) -> f64 {
+        // the string is always a valid float, and parsing rounds to the nearest f64
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+    /// converts the decimal into an f64, failing unless the f64 converts back into the same decimal
+    pub fn try_to_f64(&self) -> Result<f64, MathError> {
+        let val = self.to_f64_lossy();
+        if Self::try_from_f64(val).as_ref() != Ok(self) {
+            return Err(MathError::PrecisionLoss);
+        }
+        Ok(val)
+    }
+}
+
+impl FromStr for Rate {
+    type Err = MathError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        rate_from_scaled(parse_scaled(s)?)
+    }
+}
+
+fn rate_from_scaled(scaled: U192) -> Result<Rate, MathError> {
+    let scaled = u128::try_from(scaled).map_err(|_| MathError::MathOverflow)?;
+    Ok(Rate(U128::from(scaled)))
+}
+
+impl Rate {
+    /// formats the rate with `precision` decimal places, rounding half up.
+    /// equivalent to `format!("{:.precision$}", rate)`
+    pub fn to_string_with_precision(&self, precision: usize) -> String {
+        format_scaled(U192::from(self.to_scaled_val()), precision)
+    }
+    /// converts an f64 into a rate, failing if the shortest representation
+    /// of `val` has more than 18 decimal places
+    pub fn try_from_f64(val: f64) -> Result<Self, MathError> {
+        rate_from_scaled(parse_f64(val)?)
+    }
+    /// converts an f64 into a rate, rounding to 18 decimal places
+    pub fn from_f64_lossy(val: f64) -> Result<Self, MathError> {
+        rate_from_scaled(parse_f64_lossy(val)?)
+    }
+    /// converts the rate into the nearest f64
+    pub fn to_f64_lossy(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+    /// converts the rate into an f64, failing unless the f64 converts back into the same rate
+    pub fn try_to_f64(&self) -> Result<f64, MathError> {
+        let val = self.to_f64_lossy();
+        if Self::try_from_f64(val).as_ref() != Ok(self) {
+            return Err(MathError::PrecisionLoss);
+        }
+        Ok(val)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    impl Serialize for Decimal {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Decimal {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            String::deserialize(deserializer)?
+                .parse()
+                .map_err(D::Error::custom)
+        }
+    }
+
+    impl Serialize for Rate {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Rate {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            String::deserialize(deserializer)?
+                .parse()
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Decimal::from_str("1").unwrap(), Decimal::one());
+        assert_eq!(
+            Decimal::from_str("0.69").unwrap(),
+            Decimal::from_percent(69)
+        );
+        assert_eq!(
+            Decimal::from_str("1.234500000000000000000").unwrap(),
+            Decimal::from_scaled_val(1_234_500_000_000_000_000)
+        );
+        assert_eq!(
+            Decimal::from_str("0.000000000000000001").unwrap(),
+            Decimal::from_scaled_val(1)
+        );
+        assert_eq!(
+            Decimal::from_str("0.0000000000000000001"),
+            Err(MathError::PrecisionLoss)
+        );
+        for invalid in ["", ".", "1.", ".5", "-1", "1e5", "1.2.3", " 1"] {
+            assert_eq!(Decimal::from_str(invalid), Err(MathError::InvalidNumber));
+        }
+        let max = Decimal(U192::MAX);
+        assert_eq!(Decimal::from_str(&max.to_string()).unwrap(), max);
+        assert_eq!(
+            Decimal::from_str(&format!("1{}", max)),
+            Err(MathError::MathOverflow)
+        );
+        assert_eq!(Rate::from_str("0.5").unwrap(), Rate::from_percent(50));
+        assert_eq!(
+            Rate::from_str("1000000000000000000000"),
+            Err(MathError::MathOverflow)
+        );
+        let rate = Rate::from_scaled_val(123_456_789);
+        assert_eq!(Rate::from_str(&rate.to_string()).unwrap(), rate);
+    }
+    #[test]
+    fn test_precision_formatting() {
+        let val = Decimal::from_str("1234.56789").unwrap();
+        assert_eq!(val.to_string_with_precision(0), "1235");
+        assert_eq!(val.to_string_with_precision(2), "1234.57");
+        assert_eq!(val.to_string_with_precision(4), "1234.5679");
+        assert_eq!(format!("{:.3}", val), "1234.568");
+        assert_eq!(
+            val.to_string_with_precision(20),
+            "1234.56789000000000000000"
+        );
+        assert_eq!(format!("{}", val), "1234.567890000000000000");
+        assert_eq!(
+            Decimal::from_scaled_val(4_999).to_string_with_precision(15),
+            "0.000000000000005"
+        );
+        assert_eq!(Decimal::zero().to_string_with_precision(1), "0.0");
+        assert_eq!(format!("{:.1}", Rate::from_percent(25)), "0.3");
+    }
+    #[test]
+    fn test_f64_conversions() {
+        assert_eq!(
+            Decimal::try_from_f64(0.1).unwrap(),
+            Decimal::from_percent(10)
+        );
+        assert_eq!(
+            Decimal::try_from_f64(1e20).unwrap(),
+            Decimal::from(100_000_000_000_000_000_000_u128)
+        );
+        assert_eq!(Decimal::try_from_f64(-0.0).unwrap(), Decimal::zero());
+        assert_eq!(Decimal::try_from_f64(1e-19), Err(MathError::PrecisionLoss));
+        assert_eq!(
+            Decimal::from_f64_lossy(1e-19).unwrap(),
+            Decimal::from_scaled_val(0)
+        );
+        assert_eq!(Decimal::try_from_f64(-1.0), Err(MathError::InvalidNumber));
+        assert_eq!(
+            Decimal::try_from_f64(f64::NAN),
+            Err(MathError::InvalidNumber)
+        );
+        assert_eq!(
+            Decimal::try_from_f64(f64::INFINITY),
+            Err(MathError::InvalidNumber)
+        );
+        assert_eq!(Decimal::from_percent(10).try_to_f64().unwrap(), 0.1);
+        let lossy = Decimal::from_str("0.100000000000000001").unwrap();
+        assert_eq!(lossy.try_to_f64(), Err(MathError::PrecisionLoss));
+        assert_eq!(lossy.to_f64_lossy(), 0.1);
+        assert_eq!(
+            Rate::try_from_f64(2.56).unwrap().try_to_f64().unwrap(),
+            2.56
+        );
+        assert_eq!(Rate::try_from_f64(1e30), Err(MathError::MathOverflow));
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde::de::{value::StrDeserializer, IntoDeserializer};
+        use serde::Deserialize;
+        let deserializer: StrDeserializer<serde::de::value::Error> = "1.5".into_deserializer();
+        assert_eq!(
+            Decimal::deserialize(deserializer).unwrap(),
+            Decimal::from_percent(150)
+        );
+        let deserializer: StrDeserializer<serde::de::value::Error> = "1.5x".into_deserializer();
+        assert!(Rate::deserialize(deserializer).is_err());
+    }
+}
@@ -25,6 +25,7 @@
 #![allow(clippy::manual_range_contains)]
 
 use super::common::*;
+use super::convert::format_scaled;
 use super::rate::Rate;
 
 use super::error::MathError;
@@ -103,10 +104,33 @@ impl Decimal {
             .ok_or(MathError::MathOverflow)?;
         Ok(u64::try_from(ceil_val).map_err(|_| MathError::MathOverflow)?)
     }
+
+    /// Square root of the scaled decimal, rounded down
+    pub fn try_sqrt(&self) -> Result<Self, ProgramError> {
+        // sqrt(x * wad) keeps the result scaled by wad
+        let value = self
+            .0
+            .checked_mul(Self::wad())
+            .ok_or(MathError::MathOverflow)?;
+        if value.is_zero() {
+            return Ok(Self::zero());
+        }
+        // newton's method, starting from a value guaranteed to be >= the root
+        let mut root = value;
+        let mut next = (value >> 1) + U192::from(1u64);
+        while next < root {
+            root = next;
+            next = (root + value / root) >> 1;
+        }
+        Ok(Self(root))
+    }
 }
 
 impl fmt::Display for Decimal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(precision) = f.precision() {
+            return f.write_str(&format_scaled(self.0, precision));
+        }
         let mut scaled_val = self.0.to_string();
         if scaled_val.len() <= SCALE {
             scaled_val.insert_str(0, &vec!["0"; SCALE - scaled_val.len()].join(""));
@@ -351,6 +375,24 @@ mod test {
         assert_eq!(a.try_div(Rate::from_percent(1)).is_err(), true);
         assert_eq!(a.try_mul(u64::MAX).is_err(), true);
         assert_eq!(a.try_div(0_u64).is_err(), true);
+        assert_eq!(a.try_sqrt().is_err(), true);
+    }
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(Decimal::zero().try_sqrt().unwrap(), Decimal::zero());
+        assert_eq!(Decimal::one().try_sqrt().unwrap(), Decimal::one());
+        assert_eq!(
+            Decimal::from(144_u64).try_sqrt().unwrap(),
+            Decimal::from(12_u64)
+        );
+        assert_eq!(
+            Decimal::from_percent(25).try_sqrt().unwrap(),
+            Decimal::from_percent(50)
+        );
+        assert_eq!(
+            Decimal::from(2_u64).try_sqrt().unwrap().to_string(),
+            "1.414213562373095048".to_string()
+        );
     }
     #[test]
     fn test_constant_funcs() {
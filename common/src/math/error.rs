@@ -9,6 +9,10 @@ use thiserror::Error;
 pub enum MathError {
     #[error("Math operation overflow")]
     MathOverflow,
+    #[error("Invalid decimal number")]
+    InvalidNumber,
+    #[error("Conversion would lose precision")]
+    PrecisionLoss,
 }
 
 impl From<MathError> for ProgramError {
@@ -0,0 +1,160 @@
+//! property tests checking `Decimal` and `Rate` arithmetic against an exact rational
+//! reference implementation. the reference keeps numerators and denominators as 512 bit
+//! integers, which is wide enough to never overflow for any pair of u192 operands, and
+//! only rounds (down) once when converting back into a WAD scaled value
+
+#![allow(clippy::assign_op_pattern)]
+#![allow(clippy::ptr_offset_with_cast)]
+#![allow(clippy::manual_range_contains)]
+
+use super::common::*;
+use super::decimal::Decimal;
+use super::rate::{Rate, U128};
+use super::uint::U192;
+use proptest::prelude::*;
+use uint::construct_uint;
+
+construct_uint! {
+    struct U512(8);
+}
+
+/// exact non-negative rational number
+#[derive(Clone, Copy, Debug)]
+struct Ratio {
+    num: U512,
+    den: U512,
+}
+
+impl Ratio {
+    fn from_scaled(scaled: u128) -> Self {
+        Self {
+            num: U512::from(scaled),
+            den: U512::from(WAD),
+        }
+    }
+    fn from_decimal(val: Decimal) -> Self {
+        Self {
+            num: from_u192(val.0),
+            den: U512::from(WAD),
+        }
+    }
+    fn from_rate(val: Rate) -> Self {
+        Self::from_scaled(val.to_scaled_val())
+    }
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            num: self.num * rhs.den + rhs.num * self.den,
+            den: self.den * rhs.den,
+        }
+    }
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            num: self.num * rhs.num,
+            den: self.den * rhs.den,
+        }
+    }
+    fn div(self, rhs: Self) -> Self {
+        Self {
+            num: self.num * rhs.den,
+            den: self.den * rhs.num,
+        }
+    }
+    /// the WAD scaled value of the ratio, rounded down
+    fn floor_scaled(self) -> U512 {
+        self.num * U512::from(WAD) / self.den
+    }
+}
+
+fn from_u192(val: U192) -> U512 {
+    let mut words = [0_u64; 8];
+    words[..3].copy_from_slice(&val.0);
+    U512(words)
+}
+
+fn rate_scaled(val: Rate) -> U512 {
+    U512::from(val.to_scaled_val())
+}
+
+proptest! {
+    #[test]
+    fn decimal_add_sub(a in any::<u128>(), b in any::<u128>()) {
+        let (a, b) = (Decimal::from_scaled_val(a), Decimal::from_scaled_val(b));
+        let sum = a.try_add(b).unwrap();
+        prop_assert_eq!(
+            from_u192(sum.0),
+            Ratio::from_decimal(a).add(Ratio::from_decimal(b)).floor_scaled()
+        );
+        prop_assert_eq!(sum.try_sub(b).unwrap(), a);
+        prop_assert_eq!(a.try_sub(b).is_ok(), a >= b);
+    }
+
+    #[test]
+    fn decimal_mul(a in any::<u128>(), b in any::<u64>()) {
+        let (a, b) = (Decimal::from_scaled_val(a), Decimal::from_scaled_val(b as u128));
+        let product = a.try_mul(b).unwrap();
+        prop_assert_eq!(
+            from_u192(product.0),
+            Ratio::from_decimal(a).mul(Ratio::from_decimal(b)).floor_scaled()
+        );
+    }
+
+    #[test]
+    fn decimal_div(a in any::<u128>(), b in 1..u128::MAX) {
+        let (a, b) = (Decimal::from_scaled_val(a), Decimal::from_scaled_val(b));
+        let quotient = a.try_div(b).unwrap();
+        prop_assert_eq!(
+            from_u192(quotient.0),
+            Ratio::from_decimal(a).div(Ratio::from_decimal(b)).floor_scaled()
+        );
+    }
+
+    #[test]
+    fn decimal_rounding(a in any::<u128>()) {
+        let scaled = U192::from(a);
+        let val = Decimal(scaled);
+        if let (Ok(floor), Ok(ceil)) = (val.try_floor_u64(), val.try_ceil_u64()) {
+            prop_assert!(Decimal::from(floor) <= val);
+            prop_assert!(Decimal::from(ceil) >= val);
+            prop_assert!(ceil - floor <= 1);
+            let round = val.try_round_u64().unwrap();
+            prop_assert!(round == floor || round == ceil);
+        }
+    }
+
+    #[test]
+    fn rate_mul_div(a in any::<u64>(), b in 1..u64::MAX) {
+        let (a, b) = (Rate::from_scaled_val(a), Rate::from_scaled_val(b));
+        let product = a.try_mul(b).unwrap();
+        prop_assert_eq!(
+            rate_scaled(product),
+            Ratio::from_rate(a).mul(Ratio::from_rate(b)).floor_scaled()
+        );
+        let quotient = a.try_div(b).unwrap();
+        prop_assert_eq!(
+            rate_scaled(quotient),
+            Ratio::from_rate(a).div(Ratio::from_rate(b)).floor_scaled()
+        );
+        prop_assert_eq!(
+            Decimal::from(a).try_mul(b).unwrap(),
+            Decimal::from(product)
+        );
+    }
+
+    // per slot interest rates, as used when accruing interest. intermediate products of
+    // `Rate` overflow once values exceed ~18, which rules out large rates or exponents
+    #[test]
+    fn rate_pow_is_monotonic(rate in 0..WAD / 1_000_000, exp in 0..1_000_000_u64) {
+        let rate = Rate::one().try_add(Rate::from_scaled_val(rate)).unwrap();
+        let pow = rate.try_pow(exp).unwrap();
+        prop_assert!(pow >= Rate::one());
+        if exp > 0 {
+            prop_assert!(pow >= rate.try_pow(exp - 1).unwrap());
+        }
+    }
+
+    #[test]
+    fn rate_decimal_conversion(a in any::<u128>()) {
+        let rate = Rate(U128::from(a));
+        prop_assert_eq!(Rate::try_from(Decimal::from(rate)).unwrap(), rate);
+    }
+}
@@ -1,9 +1,13 @@
 //! variety of math helper functions sourced spl lending program library
 
 pub mod common;
+pub mod convert;
 pub mod decimal;
 pub mod error;
+#[cfg(test)]
+mod invariants;
 pub mod rate;
+pub mod signed;
 pub mod uint;
 
 pub fn calculate_underlying_to_withdraw(
@@ -31,8 +31,10 @@
 #![allow(clippy::manual_range_contains)]
 
 use super::common::*;
+use super::convert::format_scaled;
 use super::decimal::Decimal;
 use super::error::*;
+use super::uint::U192;
 
 use anchor_lang::solana_program::program_error::ProgramError;
 use std::{convert::TryFrom, fmt};
@@ -130,6 +132,9 @@ impl Rate {
 
 impl fmt::Display for Rate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(precision) = f.precision() {
+            return f.write_str(&format_scaled(U192::from(self.to_scaled_val()), precision));
+        }
         let mut scaled_val = self.0.to_string();
         if scaled_val.len() <= SCALE {
             scaled_val.insert_str(0, &vec!["0"; SCALE - scaled_val.len()].join(""));
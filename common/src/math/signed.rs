@@ -0,0 +1,345 @@
+//! Signed decimal values, used for profit and loss, funding and slippage math
+//! which may go negative.
+//!
+//! Signed decimals are represented as a sign and a `Decimal` magnitude, giving them
+//! the same precision and range as `Decimal`. Zero is always positive.
+
+use super::common::*;
+use super::convert::format_scaled;
+use super::decimal::Decimal;
+use super::error::MathError;
+use super::rate::Rate;
+
+use anchor_lang::solana_program::program_error::ProgramError;
+use std::{cmp::Ordering, convert::TryFrom, fmt, ops::Neg, str::FromStr};
+
+/// Signed decimal values, precise to 18 digits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignedDecimal {
+    negative: bool,
+    magnitude: Decimal,
+}
+
+impl SignedDecimal {
+    /// Create a signed decimal from a magnitude and sign
+    pub fn new(magnitude: Decimal, negative: bool) -> Self {
+        Self {
+            negative: negative && magnitude != Decimal::zero(),
+            magnitude,
+        }
+    }
+
+    /// One
+    pub fn one() -> Self {
+        Self::from(Decimal::one())
+    }
+
+    /// Zero
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    /// Returns `a - b`, which is negative if `b` is greater than `a`
+    pub fn difference(a: Decimal, b: Decimal) -> Self {
+        if a >= b {
+            Self::new(Decimal(a.0 - b.0), false)
+        } else {
+            Self::new(Decimal(b.0 - a.0), true)
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude == Decimal::zero()
+    }
+
+    /// Absolute value
+    pub fn abs(&self) -> Decimal {
+        self.magnitude
+    }
+
+    /// Round scaled decimal to i64, rounding halves away from zero
+    pub fn try_round_i64(&self) -> Result<i64, ProgramError> {
+        self.signed_i64(self.magnitude.try_round_u64()?)
+    }
+
+    /// Floor scaled decimal to i64, rounding towards negative infinity
+    pub fn try_floor_i64(&self) -> Result<i64, ProgramError> {
+        if self.negative {
+            self.signed_i64(self.magnitude.try_ceil_u64()?)
+        } else {
+            self.signed_i64(self.magnitude.try_floor_u64()?)
+        }
+    }
+
+    /// Ceiling scaled decimal to i64, rounding towards positive infinity
+    pub fn try_ceil_i64(&self) -> Result<i64, ProgramError> {
+        if self.negative {
+            self.signed_i64(self.magnitude.try_floor_u64()?)
+        } else {
+            self.signed_i64(self.magnitude.try_ceil_u64()?)
+        }
+    }
+
+    fn signed_i64(&self, magnitude: u64) -> Result<i64, ProgramError> {
+        let val = if self.negative {
+            0_i128 - magnitude as i128
+        } else {
+            magnitude as i128
+        };
+        Ok(i64::try_from(val).map_err(|_| MathError::MathOverflow)?)
+    }
+
+    fn with_sign(self, negative: bool, magnitude: Decimal) -> Self {
+        Self::new(magnitude, self.negative != negative)
+    }
+}
+
+impl fmt::Display for SignedDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        match f.precision() {
+            Some(precision) => f.write_str(&format_scaled(self.magnitude.0, precision)),
+            None => fmt::Display::fmt(&self.magnitude, f),
+        }
+    }
+}
+
+impl FromStr for SignedDecimal {
+    type Err = MathError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('-') {
+            Some(magnitude) => Ok(Self::new(Decimal::from_str(magnitude)?, true)),
+            None => Ok(Self::from(Decimal::from_str(s)?)),
+        }
+    }
+}
+
+impl Neg for SignedDecimal {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(self.magnitude, !self.negative)
+    }
+}
+
+impl PartialOrd for SignedDecimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SignedDecimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl From<Decimal> for SignedDecimal {
+    fn from(val: Decimal) -> Self {
+        Self::new(val, false)
+    }
+}
+
+impl From<Rate> for SignedDecimal {
+    fn from(val: Rate) -> Self {
+        Self::from(Decimal::from(val))
+    }
+}
+
+impl From<u64> for SignedDecimal {
+    fn from(val: u64) -> Self {
+        Self::from(Decimal::from(val))
+    }
+}
+
+impl From<i64> for SignedDecimal {
+    fn from(val: i64) -> Self {
+        Self::new(Decimal::from(val.unsigned_abs()), val < 0)
+    }
+}
+
+impl TryFrom<SignedDecimal> for Decimal {
+    type Error = ProgramError;
+    fn try_from(val: SignedDecimal) -> Result<Self, Self::Error> {
+        if val.negative {
+            return Err(MathError::MathOverflow.into());
+        }
+        Ok(val.magnitude)
+    }
+}
+
+impl TryAdd for SignedDecimal {
+    fn try_add(self, rhs: Self) -> Result<Self, ProgramError> {
+        if self.negative == rhs.negative {
+            return Ok(Self::new(
+                self.magnitude.try_add(rhs.magnitude)?,
+                self.negative,
+            ));
+        }
+        // opposite signs, so the result takes the sign of the larger magnitude
+        let difference = Self::difference(self.magnitude, rhs.magnitude);
+        Ok(self.with_sign(difference.negative, difference.magnitude))
+    }
+}
+
+impl TrySub for SignedDecimal {
+    fn try_sub(self, rhs: Self) -> Result<Self, ProgramError> {
+        self.try_add(-rhs)
+    }
+}
+
+impl TryMul<SignedDecimal> for SignedDecimal {
+    fn try_mul(self, rhs: Self) -> Result<Self, ProgramError> {
+        Ok(self.with_sign(rhs.negative, self.magnitude.try_mul(rhs.magnitude)?))
+    }
+}
+
+impl TryMul<Decimal> for SignedDecimal {
+    fn try_mul(self, rhs: Decimal) -> Result<Self, ProgramError> {
+        Ok(self.with_sign(false, self.magnitude.try_mul(rhs)?))
+    }
+}
+
+impl TryMul<Rate> for SignedDecimal {
+    fn try_mul(self, rhs: Rate) -> Result<Self, ProgramError> {
+        Ok(self.with_sign(false, self.magnitude.try_mul(rhs)?))
+    }
+}
+
+impl TryMul<u64> for SignedDecimal {
+    fn try_mul(self, rhs: u64) -> Result<Self, ProgramError> {
+        Ok(self.with_sign(false, self.magnitude.try_mul(rhs)?))
+    }
+}
+
+impl TryDiv<SignedDecimal> for SignedDecimal {
+    fn try_div(self, rhs: Self) -> Result<Self, ProgramError> {
+        Ok(self.with_sign(rhs.negative, self.magnitude.try_div(rhs.magnitude)?))
+    }
+}
+
+impl TryDiv<Decimal> for SignedDecimal {
+    fn try_div(self, rhs: Decimal) -> Result<Self, ProgramError> {
+        Ok(self.with_sign(false, self.magnitude.try_div(rhs)?))
+    }
+}
+
+impl TryDiv<Rate> for SignedDecimal {
+    fn try_div(self, rhs: Rate) -> Result<Self, ProgramError> {
+        Ok(self.with_sign(false, self.magnitude.try_div(rhs)?))
+    }
+}
+
+impl TryDiv<u64> for SignedDecimal {
+    fn try_div(self, rhs: u64) -> Result<Self, ProgramError> {
+        Ok(self.with_sign(false, self.magnitude.try_div(rhs)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    impl Serialize for SignedDecimal {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SignedDecimal {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            String::deserialize(deserializer)?
+                .parse()
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn signed(val: &str) -> SignedDecimal {
+        SignedDecimal::from_str(val).unwrap()
+    }
+
+    #[test]
+    fn test_signed_decimal() {
+        assert_eq!(SignedDecimal::from(-5_i64), signed("-5"));
+        assert_eq!(signed("-0"), SignedDecimal::zero());
+        assert!(!signed("-0").is_negative());
+        assert_eq!(signed("-1.5").to_string(), "-1.500000000000000000");
+        assert_eq!(format!("{:.2}", signed("-1.555")), "-1.56");
+        assert_eq!(
+            SignedDecimal::difference(Decimal::from(2_u64), Decimal::from(5_u64)),
+            signed("-3")
+        );
+        assert_eq!(signed("-1.5").abs(), Decimal::from_percent(150));
+        assert_eq!(-signed("-1.5"), signed("1.5"));
+        assert!(signed("-2") < signed("-1"));
+        assert!(signed("-1") < SignedDecimal::zero());
+        assert!(signed("0.5") > signed("-3"));
+        assert_eq!(Decimal::try_from(signed("-1")).is_err(), true);
+        assert_eq!(Decimal::try_from(signed("1")).unwrap(), Decimal::one());
+    }
+
+    #[test]
+    fn test_signed_arithmetic() {
+        assert_eq!(signed("1.5").try_add(signed("-2")).unwrap(), signed("-0.5"));
+        assert_eq!(signed("-1.5").try_add(signed("2")).unwrap(), signed("0.5"));
+        assert_eq!(
+            signed("-1.5").try_add(signed("-2")).unwrap(),
+            signed("-3.5")
+        );
+        assert_eq!(
+            signed("2").try_add(signed("-2")).unwrap(),
+            SignedDecimal::zero()
+        );
+        assert_eq!(signed("1").try_sub(signed("3")).unwrap(), signed("-2"));
+        assert_eq!(signed("-1").try_sub(signed("-3")).unwrap(), signed("2"));
+        assert_eq!(signed("-1.5").try_mul(signed("-2")).unwrap(), signed("3"));
+        assert_eq!(signed("-1.5").try_mul(2_u64).unwrap(), signed("-3"));
+        assert_eq!(
+            signed("-3").try_mul(Rate::from_percent(50)).unwrap(),
+            signed("-1.5")
+        );
+        assert_eq!(
+            signed("-3").try_div(Decimal::from(2_u64)).unwrap(),
+            signed("-1.5")
+        );
+        assert_eq!(signed("3").try_div(signed("-2")).unwrap(), signed("-1.5"));
+        assert_eq!(signed("-3").try_div(2_u64).unwrap(), signed("-1.5"));
+        assert!(signed("-3").try_div(SignedDecimal::zero()).is_err());
+        assert!(
+            SignedDecimal::new(Decimal(crate::math::uint::U192::MAX), true)
+                .try_add(signed("-1"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_signed_rounding() {
+        assert_eq!(signed("-1.5").try_round_i64().unwrap(), -2);
+        assert_eq!(signed("-1.4").try_round_i64().unwrap(), -1);
+        assert_eq!(signed("-1.4").try_floor_i64().unwrap(), -2);
+        assert_eq!(signed("-1.4").try_ceil_i64().unwrap(), -1);
+        assert_eq!(signed("1.4").try_floor_i64().unwrap(), 1);
+        assert_eq!(signed("1.4").try_ceil_i64().unwrap(), 2);
+        assert_eq!(
+            SignedDecimal::from(i64::MIN).try_round_i64().unwrap(),
+            i64::MIN
+        );
+        assert!(SignedDecimal::from(u64::MAX).try_round_i64().is_err());
+    }
+}
@@ -0,0 +1,888 @@
+//! packs an ordered list of instructions into as few transactions as possible, while keeping
+//! each transaction under the maximum serialized transaction size, the maximum compute
+//! units a transaction may request, and the maximum number of accounts a transaction may lock.
+//!
+//! when address lookup tables are provided, accounts which are neither signers nor invoked
+//! programs are loaded through the tables whenever doing so reduces the size of a transaction,
+//! in which case the transaction must be sent as a v0 transaction, which can be compiled
+//! using `compile_v0_message`
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::pubkey::Pubkey;
+#[cfg(not(target_arch = "bpf"))]
+use anchor_lang::solana_program::{
+    hash::Hash,
+    instruction::CompiledInstruction,
+    message::{v0, MessageHeader},
+};
+use static_pubkey::static_pubkey;
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
+
+/// maximum size of a serialized transaction, including signatures
+pub const PACKET_DATA_SIZE: usize = 1232;
+/// maximum number of compute units a transaction may request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// compute units given to each instruction when a transaction doesn't request a limit
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+/// maximum number of addresses which can be stored in an address lookup table
+pub const MAX_LOOKUP_TABLE_ADDRESSES: usize = 256;
+/// maximum number of accounts a transaction may lock, which includes invoked programs
+/// and accounts loaded from lookup tables
+pub const MAX_TRANSACTION_ACCOUNT_LOCKS: usize = 64;
+/// address of the compute budget program
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    static_pubkey!("ComputeBudget111111111111111111111111111111");
+
+const SIGNATURE_SIZE: usize = 64;
+const PUBKEY_SIZE: usize = 32;
+const MESSAGE_HEADER_SIZE: usize = 3;
+const BLOCKHASH_SIZE: usize = 32;
+
+/// errors returned when packing instructions into transactions
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum PackError {
+    #[error("instruction {0} requires a signature from {1} which is not a signer")]
+    MissingSigner(usize, Pubkey),
+    #[error("instruction {0} does not fit in a single transaction")]
+    InstructionTooLarge(usize),
+    #[error("instruction {0} requires more compute units than a transaction may request")]
+    ComputeUnitLimitExceeded(usize),
+    #[error("instruction {0} references more accounts than a transaction may lock")]
+    TooManyAccountLocks(usize),
+}
+
+/// the contents of an on-chain address lookup table
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AddressLookupTable {
+    /// address of the lookup table account
+    pub address: Pubkey,
+    /// the addresses stored in the table, in order
+    pub addresses: Vec<Pubkey>,
+}
+
+/// an instruction, and the number of compute units it is expected to consume
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackableInstruction {
+    pub instruction: Instruction,
+    pub compute_units: u32,
+}
+
+/// the instructions of a single transaction produced by a `TransactionPacker`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackedTransaction {
+    /// the transaction's instructions, starting with any compute budget instructions
+    pub instructions: Vec<Instruction>,
+    /// addresses of the lookup tables used by the transaction. when empty the transaction
+    /// can be sent as a legacy transaction
+    pub lookup_tables: Vec<Pubkey>,
+    /// the serialized size of the transaction, including signatures
+    pub size: usize,
+    /// the compute units expected to be consumed by the transaction's instructions
+    pub compute_units: u32,
+}
+
+/// splits instructions into transactions, see the module level documentation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionPacker {
+    /// the fee payer, which signs every transaction
+    pub payer: Pubkey,
+    /// the additional signers available to sign transactions
+    pub signers: Vec<Pubkey>,
+    /// lookup tables which may be used to load accounts
+    pub lookup_tables: Vec<AddressLookupTable>,
+    /// when set, every transaction includes an instruction setting the compute unit price
+    /// in micro-lamports
+    pub compute_unit_price: Option<u64>,
+}
+
+impl TransactionPacker {
+    pub fn new(payer: Pubkey, signers: Vec<Pubkey>) -> Self {
+        Self {
+            payer,
+            signers,
+            lookup_tables: vec![],
+            compute_unit_price: None,
+        }
+    }
+    /// packs `instructions` in order, assuming each instruction consumes the default compute
+    /// units given to an instruction
+    pub fn pack(
+        &self,
+        instructions: Vec<Instruction>,
+    ) -> Result<Vec<PackedTransaction>, PackError> {
+        self.pack_with_compute_units(
+            instructions
+                .into_iter()
+                .map(|instruction| PackableInstruction {
+                    instruction,
+                    compute_units: DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT,
+                })
+                .collect(),
+        )
+    }
+    /// packs `instructions` in order, greedily adding instructions to a transaction until
+    /// the next instruction would exceed the transaction size, compute unit or account lock limit.
+    ///
+    /// a compute unit limit instruction is added to any transaction whose instructions are
+    /// expected to consume more than the default compute units, and a compute unit price
+    /// instruction is added to every transaction when `compute_unit_price` is set
+    pub fn pack_with_compute_units(
+        &self,
+        instructions: Vec<PackableInstruction>,
+    ) -> Result<Vec<PackedTransaction>, PackError> {
+        for (index, packable) in instructions.iter().enumerate() {
+            if let Some(signer) = packable
+                .instruction
+                .accounts
+                .iter()
+                .find(|meta| meta.is_signer && !self.is_signer(&meta.pubkey))
+            {
+                return Err(PackError::MissingSigner(index, signer.pubkey));
+            }
+        }
+        let mut transactions = vec![];
+        let mut pending: Vec<PackableInstruction> = vec![];
+        let mut packed: Option<PackedTransaction> = None;
+        for (index, packable) in instructions.into_iter().enumerate() {
+            pending.push(packable);
+            if let Some(transaction) = self.try_pack(&pending) {
+                packed = Some(transaction);
+                continue;
+            }
+            let packable = pending.pop().unwrap();
+            if let Some(transaction) = packed.take() {
+                transactions.push(transaction);
+            }
+            pending = vec![packable];
+            if let Some(transaction) = self.try_pack(&pending) {
+                packed = Some(transaction);
+                continue;
+            }
+            return Err(match self.transaction_instructions(&pending) {
+                None => PackError::ComputeUnitLimitExceeded(index),
+                Some((instructions, _))
+                    if account_locks(&self.payer, &instructions)
+                        > MAX_TRANSACTION_ACCOUNT_LOCKS =>
+                {
+                    PackError::TooManyAccountLocks(index)
+                }
+                Some(_) => PackError::InstructionTooLarge(index),
+            });
+        }
+        transactions.extend(packed);
+        Ok(transactions)
+    }
+    fn is_signer(&self, key: &Pubkey) -> bool {
+        self.payer.eq(key) || self.signers.contains(key)
+    }
+    /// returns the transaction containing `instructions`, or None if the transaction
+    /// exceeds the size, compute unit or account lock limits
+    fn try_pack(&self, instructions: &[PackableInstruction]) -> Option<PackedTransaction> {
+        let (transaction_instructions, compute_units) =
+            self.transaction_instructions(instructions)?;
+        let (size, lookup_tables) =
+            transaction_size(&self.payer, &transaction_instructions, &self.lookup_tables)?;
+        if size > PACKET_DATA_SIZE {
+            return None;
+        }
+        Some(PackedTransaction {
+            instructions: transaction_instructions,
+            lookup_tables,
+            size,
+            compute_units,
+        })
+    }
+    /// returns the instructions of a transaction containing `instructions`, starting with any
+    /// compute budget instructions, and the compute units they are expected to consume.
+    /// returns None if the compute unit limit is exceeded
+    fn transaction_instructions(
+        &self,
+        instructions: &[PackableInstruction],
+    ) -> Option<(Vec<Instruction>, u32)> {
+        let compute_units = instructions
+            .iter()
+            .try_fold(0_u32, |total, packable| {
+                total.checked_add(packable.compute_units)
+            })
+            .filter(|compute_units| *compute_units <= MAX_COMPUTE_UNIT_LIMIT)?;
+        let default_compute_units = std::cmp::min(
+            DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT.saturating_mul(instructions.len() as u32),
+            MAX_COMPUTE_UNIT_LIMIT,
+        );
+        let mut transaction_instructions = vec![];
+        if compute_units > default_compute_units {
+            transaction_instructions.push(set_compute_unit_limit(compute_units));
+        }
+        if let Some(price) = self.compute_unit_price {
+            transaction_instructions.push(set_compute_unit_price(price));
+        }
+        transaction_instructions.extend(
+            instructions
+                .iter()
+                .map(|packable| packable.instruction.clone()),
+        );
+        Some((transaction_instructions, compute_units))
+    }
+}
+
+/// returns the serialized size of a transaction containing `instructions` paid for by `payer`,
+/// and the addresses of the lookup tables used to load accounts. lookup tables are only used
+/// when they reduce the size of the transaction.
+///
+/// returns None if the transaction references more accounts than a transaction may lock
+pub fn transaction_size(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTable],
+) -> Option<(usize, Vec<Pubkey>)> {
+    let plan = AccountPlan::new(payer, instructions, lookup_tables)?;
+    let num_signers = plan
+        .static_keys
+        .iter()
+        .filter(|(_, flags)| flags.is_signer)
+        .count();
+    let instructions_size: usize = instructions
+        .iter()
+        .map(|instruction| {
+            1 + short_vec_size(instruction.accounts.len())
+                + instruction.accounts.len()
+                + short_vec_size(instruction.data.len())
+                + instruction.data.len()
+        })
+        .sum();
+    let mut message_size = MESSAGE_HEADER_SIZE
+        + short_vec_size(plan.static_keys.len())
+        + plan.static_keys.len() * PUBKEY_SIZE
+        + BLOCKHASH_SIZE
+        + short_vec_size(instructions.len())
+        + instructions_size;
+    if !plan.lookups.is_empty() {
+        // version prefix, and the address table lookups
+        message_size += 1
+            + short_vec_size(plan.lookups.len())
+            + plan
+                .lookups
+                .iter()
+                .map(|lookup| lookup.size())
+                .sum::<usize>();
+    }
+    Some((
+        short_vec_size(num_signers) + num_signers * SIGNATURE_SIZE + message_size,
+        plan.lookups.iter().map(|lookup| lookup.table).collect(),
+    ))
+}
+
+/// compiles `instructions` paid for by `payer` into a v0 message, loading accounts through
+/// `lookup_tables` using the same rules as `transaction_size`, such that the serialized
+/// transaction is `transaction_size` bytes.
+///
+/// returns None if the message references more accounts than a transaction may lock
+#[cfg(not(target_arch = "bpf"))]
+pub fn compile_v0_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTable],
+    recent_blockhash: Hash,
+) -> Option<v0::Message> {
+    let plan = AccountPlan::new(payer, instructions, lookup_tables)?;
+    // static keys, followed by the writable and then readonly keys loaded from each table
+    let account_keys: Vec<Pubkey> = plan
+        .static_keys
+        .iter()
+        .map(|(key, _)| *key)
+        .chain(
+            plan.lookups
+                .iter()
+                .flat_map(|lookup| lookup.writable.iter().map(|(key, _)| *key)),
+        )
+        .chain(
+            plan.lookups
+                .iter()
+                .flat_map(|lookup| lookup.readonly.iter().map(|(key, _)| *key)),
+        )
+        .collect();
+    let index_of = |key: &Pubkey| account_keys.iter().position(|k| k == key).unwrap() as u8;
+    let count = |is_signer: bool, is_writable: bool| {
+        plan.static_keys
+            .iter()
+            .filter(|(_, flags)| flags.is_signer == is_signer && flags.is_writable == is_writable)
+            .count() as u8
+    };
+    Some(v0::Message {
+        header: MessageHeader {
+            num_required_signatures: count(true, true) + count(true, false),
+            num_readonly_signed_accounts: count(true, false),
+            num_readonly_unsigned_accounts: count(false, false),
+        },
+        account_keys: plan.static_keys.iter().map(|(key, _)| *key).collect(),
+        recent_blockhash,
+        instructions: instructions
+            .iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: index_of(&instruction.program_id),
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| index_of(&meta.pubkey))
+                    .collect(),
+                data: instruction.data.clone(),
+            })
+            .collect(),
+        address_table_lookups: plan
+            .lookups
+            .iter()
+            .map(|lookup| v0::MessageAddressTableLookup {
+                account_key: lookup.table,
+                writable_indexes: lookup.writable.iter().map(|(_, index)| *index).collect(),
+                readonly_indexes: lookup.readonly.iter().map(|(_, index)| *index).collect(),
+            })
+            .collect(),
+    })
+}
+
+/// returns the number of unique accounts locked by a transaction containing `instructions`
+/// paid for by `payer`, including invoked programs and accounts loaded from lookup tables
+pub fn account_locks(payer: &Pubkey, instructions: &[Instruction]) -> usize {
+    std::iter::once(payer)
+        .chain(instructions.iter().flat_map(|instruction| {
+            std::iter::once(&instruction.program_id)
+                .chain(instruction.accounts.iter().map(|meta| &meta.pubkey))
+        }))
+        .collect::<BTreeSet<_>>()
+        .len()
+}
+
+/// returns the unique addresses in `addresses`, in the order they first appear, truncated
+/// to the maximum number of addresses a lookup table can store
+pub fn lookup_table_addresses(addresses: impl IntoIterator<Item = Pubkey>) -> Vec<Pubkey> {
+    let mut seen = BTreeSet::new();
+    addresses
+        .into_iter()
+        .filter(|address| seen.insert(*address))
+        .take(MAX_LOOKUP_TABLE_ADDRESSES)
+        .collect()
+}
+
+#[derive(Clone, Copy, Default)]
+struct AccountFlags {
+    is_signer: bool,
+    is_writable: bool,
+    is_invoked: bool,
+}
+
+/// the accounts loaded from a single lookup table, and their indexes within the table
+struct LookupPlan {
+    table: Pubkey,
+    writable: Vec<(Pubkey, u8)>,
+    readonly: Vec<(Pubkey, u8)>,
+}
+
+impl LookupPlan {
+    fn size(&self) -> usize {
+        PUBKEY_SIZE
+            + short_vec_size(self.writable.len())
+            + self.writable.len()
+            + short_vec_size(self.readonly.len())
+            + self.readonly.len()
+    }
+}
+
+/// the accounts of a transaction, split into the keys stored in the message, and the keys
+/// loaded from lookup tables
+struct AccountPlan {
+    /// ordered as writable signers starting with the payer, readonly signers, writable
+    /// non-signers and then readonly non-signers
+    static_keys: Vec<(Pubkey, AccountFlags)>,
+    lookups: Vec<LookupPlan>,
+}
+
+impl AccountPlan {
+    fn new(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        lookup_tables: &[AddressLookupTable],
+    ) -> Option<Self> {
+        // keys in the order they first appear
+        let mut keys: Vec<Pubkey> = vec![*payer];
+        let mut flags: BTreeMap<Pubkey, AccountFlags> = BTreeMap::new();
+        flags.insert(
+            *payer,
+            AccountFlags {
+                is_signer: true,
+                is_writable: true,
+                is_invoked: false,
+            },
+        );
+        for instruction in instructions {
+            for meta in instruction.accounts.iter() {
+                let entry = flags.entry(meta.pubkey).or_insert_with(|| {
+                    keys.push(meta.pubkey);
+                    AccountFlags::default()
+                });
+                entry.is_signer |= meta.is_signer;
+                entry.is_writable |= meta.is_writable;
+            }
+            flags
+                .entry(instruction.program_id)
+                .or_insert_with(|| {
+                    keys.push(instruction.program_id);
+                    AccountFlags::default()
+                })
+                .is_invoked = true;
+        }
+        // loading accounts from lookup tables doesn't avoid locking them
+        if keys.len() > MAX_TRANSACTION_ACCOUNT_LOCKS {
+            return None;
+        }
+
+        // signers and invoked programs can't be loaded from lookup tables
+        let mut loadable: BTreeSet<Pubkey> = flags
+            .iter()
+            .filter(|(_, flags)| !flags.is_signer && !flags.is_invoked)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut lookups = vec![];
+        for table in lookup_tables {
+            let mut indexes = BTreeMap::new();
+            for (index, address) in table
+                .addresses
+                .iter()
+                .take(MAX_LOOKUP_TABLE_ADDRESSES)
+                .enumerate()
+            {
+                if loadable.contains(address) {
+                    indexes.entry(*address).or_insert(index as u8);
+                }
+            }
+            let (writable, readonly): (Vec<_>, Vec<_>) = keys
+                .iter()
+                .filter_map(|key| indexes.get(key).map(|index| (*key, *index)))
+                .partition(|(key, _)| flags[key].is_writable);
+            let lookup = LookupPlan {
+                table: table.address,
+                writable,
+                readonly,
+            };
+            let num_loaded = lookup.writable.len() + lookup.readonly.len();
+            if lookup.size() >= num_loaded * PUBKEY_SIZE {
+                continue;
+            }
+            for key in indexes.keys() {
+                loadable.remove(key);
+            }
+            lookups.push(lookup);
+        }
+
+        let loaded: BTreeSet<Pubkey> = lookups
+            .iter()
+            .flat_map(|lookup| lookup.writable.iter().chain(lookup.readonly.iter()))
+            .map(|(key, _)| *key)
+            .collect();
+        let mut static_keys: Vec<(Pubkey, AccountFlags)> = keys
+            .iter()
+            .filter(|key| !loaded.contains(key))
+            .map(|key| (*key, flags[key]))
+            .collect();
+        // stable, so the payer remains first
+        static_keys.sort_by_key(|(_, flags)| (!flags.is_signer, !flags.is_writable));
+        Some(Self {
+            static_keys,
+            lookups,
+        })
+    }
+}
+
+/// returns a compute budget instruction setting the transaction's compute unit limit
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// returns a compute budget instruction setting the transaction's compute unit price,
+/// denominated in micro-lamports
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// returns the number of bytes used to encode `len` as a compact-u16
+fn short_vec_size(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anchor_lang::solana_program::{hash::Hash, instruction::AccountMeta, message::Message};
+
+    fn instruction(program_id: Pubkey, accounts: Vec<AccountMeta>, data_len: usize) -> Instruction {
+        Instruction {
+            program_id,
+            accounts,
+            data: vec![1; data_len],
+        }
+    }
+
+    fn accounts(num_accounts: usize) -> Vec<AccountMeta> {
+        (0..num_accounts)
+            .map(|i| {
+                if i % 2 == 0 {
+                    AccountMeta::new(Pubkey::new_unique(), false)
+                } else {
+                    AccountMeta::new_readonly(Pubkey::new_unique(), false)
+                }
+            })
+            .collect()
+    }
+
+    /// the size of a legacy transaction serialized with the runtime's encoding
+    fn legacy_transaction_size(payer: &Pubkey, instructions: &[Instruction]) -> usize {
+        let mut message = Message::new(instructions, Some(payer));
+        message.recent_blockhash = Hash::new_unique();
+        let num_signatures = message.header.num_required_signatures as usize;
+        short_vec_size(num_signatures) + num_signatures * SIGNATURE_SIZE + message.serialize().len()
+    }
+
+    #[test]
+    fn test_transaction_size_matches_legacy_message() {
+        let payer = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let mut shared = accounts(3);
+        shared.push(AccountMeta::new(signer, true));
+        let instructions = [
+            instruction(program_id, shared.clone(), 40),
+            instruction(program_id, [shared, accounts(50)].concat(), 300),
+            set_compute_unit_limit(400_000),
+        ];
+        for len in 1..=instructions.len() {
+            assert_eq!(
+                transaction_size(&payer, &instructions[..len], &[])
+                    .unwrap()
+                    .0,
+                legacy_transaction_size(&payer, &instructions[..len])
+            );
+        }
+        // the payer and program are locked along with the instruction's accounts
+        assert!(
+            transaction_size(&payer, &[instruction(program_id, accounts(62), 0)], &[]).is_some()
+        );
+        assert!(
+            transaction_size(&payer, &[instruction(program_id, accounts(63), 0)], &[]).is_none()
+        );
+    }
+
+    #[test]
+    fn test_transaction_size_with_lookup_tables() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let metas = accounts(20);
+        let instructions = vec![instruction(program_id, metas.clone(), 10)];
+        let (legacy_size, _) = transaction_size(&payer, &instructions, &[]).unwrap();
+
+        // a table containing a single account is never used
+        let single = AddressLookupTable {
+            address: Pubkey::new_unique(),
+            addresses: vec![metas[0].pubkey],
+        };
+        assert_eq!(
+            transaction_size(&payer, &instructions, std::slice::from_ref(&single)).unwrap(),
+            (legacy_size, vec![])
+        );
+
+        // programs can't be loaded from a table, 10 writable and 10 readonly accounts
+        // are loaded using the table
+        let table = AddressLookupTable {
+            address: Pubkey::new_unique(),
+            addresses: metas
+                .iter()
+                .map(|meta| meta.pubkey)
+                .chain([program_id, Pubkey::new_unique()])
+                .collect(),
+        };
+        assert_eq!(
+            transaction_size(&payer, &instructions, &[single, table.clone()]).unwrap(),
+            (
+                legacy_size - 20 * PUBKEY_SIZE + 1 + 1 + PUBKEY_SIZE + 1 + 10 + 1 + 10,
+                vec![table.address]
+            )
+        );
+    }
+
+    #[test]
+    fn test_pack() {
+        let payer = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let instructions: Vec<Instruction> = (0..4)
+            .map(|_| {
+                let mut metas = accounts(12);
+                metas.push(AccountMeta::new_readonly(signer, true));
+                instruction(program_id, metas, 64)
+            })
+            .collect();
+        let packer = TransactionPacker::new(payer, vec![signer]);
+        let transactions = packer.pack(instructions.clone()).unwrap();
+        assert!(transactions.len() > 1);
+        assert_eq!(
+            transactions
+                .iter()
+                .flat_map(|transaction| transaction.instructions.clone())
+                .collect::<Vec<_>>(),
+            instructions
+        );
+        for (i, transaction) in transactions.iter().enumerate() {
+            assert!(transaction.size <= PACKET_DATA_SIZE);
+            assert_eq!(
+                transaction.size,
+                legacy_transaction_size(&payer, &transaction.instructions)
+            );
+            assert!(transaction.lookup_tables.is_empty());
+            // every transaction except the last is full
+            if let Some(next) = transactions.get(i + 1) {
+                let mut instructions = transaction.instructions.clone();
+                instructions.push(next.instructions[0].clone());
+                assert!(legacy_transaction_size(&payer, &instructions) > PACKET_DATA_SIZE);
+            }
+        }
+
+        // a lookup table containing every account packs all instructions together
+        let mut packer = packer;
+        packer.lookup_tables = vec![AddressLookupTable {
+            address: Pubkey::new_unique(),
+            addresses: instructions
+                .iter()
+                .flat_map(|ix| ix.accounts.iter().map(|meta| meta.pubkey))
+                .collect(),
+        }];
+        let transactions = packer.pack(instructions.clone()).unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].instructions, instructions);
+        assert_eq!(
+            transactions[0].lookup_tables,
+            vec![packer.lookup_tables[0].address]
+        );
+    }
+
+    #[test]
+    fn test_pack_compute_budget() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let packable = |compute_units| PackableInstruction {
+            instruction: instruction(program_id, accounts(2), 8),
+            compute_units,
+        };
+        let mut packer = TransactionPacker::new(payer, vec![]);
+
+        // instructions within the default compute units don't request a limit
+        let transactions = packer
+            .pack_with_compute_units(vec![packable(150_000), packable(250_000)])
+            .unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].instructions.len(), 2);
+        assert_eq!(transactions[0].compute_units, 400_000);
+
+        packer.compute_unit_price = Some(1_000);
+        let transactions = packer
+            .pack_with_compute_units(vec![
+                packable(900_000),
+                packable(400_000),
+                packable(200_000),
+            ])
+            .unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(
+            transactions[0].instructions[..2],
+            [
+                set_compute_unit_limit(1_300_000),
+                set_compute_unit_price(1_000)
+            ]
+        );
+        assert_eq!(transactions[0].instructions.len(), 4);
+        assert_eq!(transactions[0].compute_units, 1_300_000);
+        assert_eq!(
+            transactions[1].instructions[0],
+            set_compute_unit_price(1_000)
+        );
+        assert_eq!(transactions[1].instructions.len(), 2);
+
+        assert_eq!(
+            packer.pack_with_compute_units(vec![packable(100), packable(1_400_001)]),
+            Err(PackError::ComputeUnitLimitExceeded(1))
+        );
+    }
+
+    #[test]
+    fn test_pack_errors() {
+        let payer = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let packer = TransactionPacker::new(payer, vec![]);
+        assert_eq!(
+            packer.pack(vec![
+                instruction(program_id, accounts(2), 8),
+                instruction(program_id, vec![AccountMeta::new(signer, true)], 8),
+            ]),
+            Err(PackError::MissingSigner(1, signer))
+        );
+        assert_eq!(
+            packer.pack(vec![
+                instruction(program_id, accounts(2), 8),
+                instruction(program_id, accounts(2), PACKET_DATA_SIZE),
+            ]),
+            Err(PackError::InstructionTooLarge(1))
+        );
+    }
+
+    #[test]
+    fn test_pack_account_locks() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let instructions: Vec<Instruction> = (0..4)
+            .map(|_| instruction(program_id, accounts(30), 8))
+            .collect();
+        let large = [
+            instruction(program_id, accounts(61), 8),
+            instruction(program_id, accounts(62), 8),
+        ];
+        // a lookup table containing every account keeps the transactions well under the
+        // size limit, so they are only split by the number of accounts locked
+        let mut packer = TransactionPacker::new(payer, vec![]);
+        packer.lookup_tables = vec![AddressLookupTable {
+            address: Pubkey::new_unique(),
+            addresses: instructions
+                .iter()
+                .chain(large.iter())
+                .flat_map(|ix| ix.accounts.iter().map(|meta| meta.pubkey))
+                .collect(),
+        }];
+        assert_eq!(packer.lookup_tables[0].addresses.len(), 243);
+        assert!(transaction_size(&payer, &instructions[..3], &packer.lookup_tables).is_none());
+        let transactions = packer.pack(instructions.clone()).unwrap();
+        assert_eq!(transactions.len(), 2);
+        for transaction in transactions.iter() {
+            assert_eq!(transaction.instructions.len(), 2);
+            assert_eq!(account_locks(&payer, &transaction.instructions), 62);
+            assert!(transaction.size <= PACKET_DATA_SIZE);
+        }
+        assert_eq!(
+            transactions
+                .iter()
+                .flat_map(|transaction| transaction.instructions.clone())
+                .collect::<Vec<_>>(),
+            instructions
+        );
+
+        // the compute budget program is locked when a compute unit price is set, leaving
+        // room for only 61 accounts in a transaction
+        packer.compute_unit_price = Some(1);
+        assert_eq!(packer.pack(vec![large[0].clone()]).unwrap().len(), 1);
+        assert_eq!(
+            packer.pack(vec![instructions[0].clone(), large[1].clone()]),
+            Err(PackError::TooManyAccountLocks(1))
+        );
+    }
+
+    #[test]
+    fn test_compile_v0_message() {
+        use anchor_lang::solana_program::sanitize::Sanitize;
+
+        let payer = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let mut metas = accounts(10);
+        metas.push(AccountMeta::new_readonly(signer, true));
+        metas.push(AccountMeta::new_readonly(program_id, false));
+        let instructions = vec![
+            set_compute_unit_limit(400_000),
+            instruction(program_id, metas.clone(), 20),
+            instruction(program_id, [accounts(3), metas[..4].to_vec()].concat(), 4),
+        ];
+        let table = AddressLookupTable {
+            address: Pubkey::new_unique(),
+            addresses: lookup_table_addresses(
+                [Pubkey::new_unique(), signer, program_id, signer]
+                    .into_iter()
+                    .chain(metas[..8].iter().map(|meta| meta.pubkey))
+                    .chain(metas.iter().map(|meta| meta.pubkey)),
+            ),
+        };
+        assert_eq!(table.addresses.len(), 13);
+        let lookup_tables = [table];
+        let message =
+            compile_v0_message(&payer, &instructions, &lookup_tables, Hash::new_unique()).unwrap();
+        message.sanitize().unwrap();
+        let (size, used_tables) = transaction_size(&payer, &instructions, &lookup_tables).unwrap();
+        assert_eq!(used_tables, vec![lookup_tables[0].address]);
+        assert_eq!(message.serialize().len() + 1 + 2 * SIGNATURE_SIZE, size);
+        assert_eq!(message.header.num_required_signatures, 2);
+        assert_eq!(message.header.num_readonly_signed_accounts, 1);
+        assert_eq!(message.account_keys[..2], [payer, signer]);
+        // the compute budget program, the invoked program, and 3 accounts missing from the table
+        assert_eq!(message.account_keys.len(), 7);
+        assert_eq!(message.address_table_lookups[0].writable_indexes.len(), 5);
+        assert_eq!(message.address_table_lookups[0].readonly_indexes.len(), 5);
+
+        // resolving the compiled instructions returns the original instructions
+        let lookup = &message.address_table_lookups[0];
+        let keys: Vec<Pubkey> = message
+            .account_keys
+            .iter()
+            .copied()
+            .chain(
+                lookup
+                    .writable_indexes
+                    .iter()
+                    .chain(lookup.readonly_indexes.iter())
+                    .map(|index| lookup_tables[0].addresses[*index as usize]),
+            )
+            .collect();
+        let num_static = message.account_keys.len();
+        let num_writable_signed = (message.header.num_required_signatures
+            - message.header.num_readonly_signed_accounts)
+            as usize;
+        let is_writable = |index: usize| {
+            if index < num_static {
+                index < num_writable_signed
+                    || (index >= message.header.num_required_signatures as usize
+                        && index
+                            < num_static - message.header.num_readonly_unsigned_accounts as usize)
+            } else {
+                index < num_static + lookup.writable_indexes.len()
+            }
+        };
+        for (compiled, instruction) in message.instructions.iter().zip(instructions.iter()) {
+            assert_eq!(
+                keys[compiled.program_id_index as usize],
+                instruction.program_id
+            );
+            assert_eq!(compiled.data, instruction.data);
+            for (index, meta) in compiled.accounts.iter().zip(instruction.accounts.iter()) {
+                let index = *index as usize;
+                assert_eq!(keys[index], meta.pubkey);
+                assert_eq!(
+                    index < message.header.num_required_signatures as usize,
+                    meta.is_signer
+                );
+                if meta.is_writable {
+                    assert!(is_writable(index));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! helpers for valuing the lp tokens, and swap amounts of the amms that tulip vaults
+//! and leveraged farms are built on top of
+
+pub mod orca;
+pub mod raydium;
+
+use crate::math::{
+    common::{TryDiv, TryMul},
+    decimal::Decimal,
+    error::MathError,
+};
+use anchor_lang::solana_program::program_error::ProgramError;
+
+/// returns 10^decimals as a Decimal, used to convert native token amounts
+/// into their ui representation
+pub fn decimals_scaler(decimals: u8) -> Result<Decimal, ProgramError> {
+    let scaler = 10_u64
+        .checked_pow(decimals as u32)
+        .ok_or(MathError::MathOverflow)?;
+    Ok(Decimal::from(scaler))
+}
+
+/// converts a native token amount into the usd value of the amount, where
+/// `price` is the price of a single whole token (ie: as returned by pyth)
+pub fn token_value(amount: u64, decimals: u8, price: Decimal) -> Result<Decimal, ProgramError> {
+    Decimal::from(amount)
+        .try_mul(price)?
+        .try_div(decimals_scaler(decimals)?)
+}
+
+/// 100% expressed in basis points
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// applies `slippage_bps` to the quoted `amount_out`, returning the minimum output amount
+/// to use when building a swap instruction
+pub fn minimum_amount_out(amount_out: u64, slippage_bps: u64) -> Result<u64, ProgramError> {
+    let keep_bps = BPS_DENOMINATOR
+        .checked_sub(slippage_bps)
+        .ok_or(MathError::MathOverflow)?;
+    let min_out = (amount_out as u128)
+        .checked_mul(keep_bps as u128)
+        .ok_or(MathError::MathOverflow)?
+        .checked_div(BPS_DENOMINATOR as u128)
+        .ok_or(MathError::MathOverflow)?;
+    // min_out <= amount_out so this can't truncate
+    Ok(min_out as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_token_value() {
+        // 1.5 tokens with 6 decimals at $2
+        let value = token_value(1_500_000, 6, Decimal::from(2_u64)).unwrap();
+        assert_eq!(value, Decimal::from(3_u64));
+        assert_eq!(decimals_scaler(0).unwrap(), Decimal::one());
+        assert_eq!(decimals_scaler(255).is_err(), true);
+    }
+    #[test]
+    fn test_minimum_amount_out() {
+        assert_eq!(minimum_amount_out(1_000_000, 50).unwrap(), 995_000);
+        assert_eq!(minimum_amount_out(1_000_000, 0).unwrap(), 1_000_000);
+        assert_eq!(minimum_amount_out(1_000_000, 10_000).unwrap(), 0);
+        assert_eq!(minimum_amount_out(1_000_000, 10_001).is_err(), true);
+    }
+}
@@ -0,0 +1,443 @@
+//! offline swap quotes for orca pools, which are deployments of the spl token-swap program
+//! supporting both the constant product and stable curves.
+//!
+//! the math here mirrors the on-chain program so that quotes can be used to set the
+//! minimum output amount of swaps made when opening leveraged positions, or withdrawing from vaults
+use super::BPS_DENOMINATOR;
+use crate::math::{
+    common::{TryDiv, TrySub},
+    decimal::Decimal,
+    error::MathError,
+    uint::U256,
+};
+use anchor_lang::solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// size of an orca swap account
+pub const SWAP_ACCOUNT_SIZE: usize = 324;
+/// number of iterations used when approximating the stable curve
+const STABLE_ITERATIONS: u8 = 32;
+/// number of tokens in a pool
+const N_COINS: u8 = 2;
+
+/// the fee schedule of an orca pool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OrcaFees {
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub owner_trade_fee_numerator: u64,
+    pub owner_trade_fee_denominator: u64,
+    pub owner_withdraw_fee_numerator: u64,
+    pub owner_withdraw_fee_denominator: u64,
+    pub host_fee_numerator: u64,
+    pub host_fee_denominator: u64,
+}
+
+impl OrcaFees {
+    /// fee paid to liquidity providers
+    pub fn trading_fee(&self, amount: u64) -> Result<u64, ProgramError> {
+        calculate_fee(amount, self.trade_fee_numerator, self.trade_fee_denominator)
+    }
+    /// fee paid to the pool owner, minted as lp tokens to `pool_fee_account`
+    pub fn owner_trading_fee(&self, amount: u64) -> Result<u64, ProgramError> {
+        calculate_fee(
+            amount,
+            self.owner_trade_fee_numerator,
+            self.owner_trade_fee_denominator,
+        )
+    }
+}
+
+/// the curve used by a pool to price swaps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrcaCurve {
+    /// x * y = k
+    ConstantProduct,
+    /// curve.fi style stable swap, with the given amplification coefficient
+    Stable { amp: u64 },
+}
+
+// deriving requires `#[default]` on enum variants, which is newer than the toolchain we target
+#[allow(clippy::derivable_impls)]
+impl Default for OrcaCurve {
+    fn default() -> Self {
+        OrcaCurve::ConstantProduct
+    }
+}
+
+/// the decoded state of an orca swap account, ie: `AquaFarms::swap_account`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OrcaPool {
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub pool_mint: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub pool_fee_account: Pubkey,
+    pub fees: OrcaFees,
+    pub curve: OrcaCurve,
+}
+
+/// the result of quoting a swap
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OrcaQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub trade_fee: u64,
+    pub owner_fee: u64,
+    /// the fraction of the output lost to moving the pool price, excluding fees.
+    /// a value of 0.01 indicates 1% price impact
+    pub price_impact: Decimal,
+}
+
+impl OrcaPool {
+    /// decodes the raw data of an orca swap account
+    pub fn try_from_slice(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != SWAP_ACCOUNT_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // version 1, initialized
+        if data[0] != 1 || data[1] != 1 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let fees = OrcaFees {
+            trade_fee_numerator: read_u64(data, 227),
+            trade_fee_denominator: read_u64(data, 235),
+            owner_trade_fee_numerator: read_u64(data, 243),
+            owner_trade_fee_denominator: read_u64(data, 251),
+            owner_withdraw_fee_numerator: read_u64(data, 259),
+            owner_withdraw_fee_denominator: read_u64(data, 267),
+            host_fee_numerator: read_u64(data, 275),
+            host_fee_denominator: read_u64(data, 283),
+        };
+        let curve = match data[291] {
+            0 => OrcaCurve::ConstantProduct,
+            2 => OrcaCurve::Stable {
+                amp: read_u64(data, 292),
+            },
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(Self {
+            token_a: read_pubkey(data, 35),
+            token_b: read_pubkey(data, 67),
+            pool_mint: read_pubkey(data, 99),
+            token_a_mint: read_pubkey(data, 131),
+            token_b_mint: read_pubkey(data, 163),
+            pool_fee_account: read_pubkey(data, 195),
+            fees,
+            curve,
+        })
+    }
+    /// quotes swapping `amount_in` tokens into the pool, where `balance_a` and `balance_b` are the
+    /// balances of `token_a` and `token_b`. when `a_to_b` is true, token a is being sold for token b
+    pub fn quote(
+        &self,
+        amount_in: u64,
+        balance_a: u64,
+        balance_b: u64,
+        a_to_b: bool,
+    ) -> Result<OrcaQuote, ProgramError> {
+        let (reserve_in, reserve_out) = if a_to_b {
+            (balance_a, balance_b)
+        } else {
+            (balance_b, balance_a)
+        };
+        if reserve_in == 0 || reserve_out == 0 {
+            return Err(MathError::MathOverflow.into());
+        }
+        let trade_fee = self.fees.trading_fee(amount_in)?;
+        let owner_fee = self.fees.owner_trading_fee(amount_in)?;
+        let amount_in_after_fees = amount_in
+            .checked_sub(trade_fee)
+            .ok_or(MathError::MathOverflow)?
+            .checked_sub(owner_fee)
+            .ok_or(MathError::MathOverflow)?;
+        let amount_out = self.swap_without_fees(amount_in_after_fees, reserve_in, reserve_out)?;
+        Ok(OrcaQuote {
+            amount_in,
+            amount_out,
+            trade_fee,
+            owner_fee,
+            price_impact: self.price_impact(
+                amount_in_after_fees,
+                amount_out,
+                reserve_in,
+                reserve_out,
+            )?,
+        })
+    }
+    /// returns the amount of tokens received for `amount_in`, ignoring fees
+    pub fn swap_without_fees(
+        &self,
+        amount_in: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+    ) -> Result<u64, ProgramError> {
+        let amount_out = match self.curve {
+            OrcaCurve::ConstantProduct => {
+                constant_product_swap(amount_in as u128, reserve_in as u128, reserve_out as u128)
+            }
+            OrcaCurve::Stable { amp } => stable_swap(
+                amp,
+                amount_in as u128,
+                reserve_in as u128,
+                reserve_out as u128,
+            ),
+        }
+        .ok_or(MathError::MathOverflow)?;
+        Ok(u64::try_from(amount_out).map_err(|_| MathError::MathOverflow)?)
+    }
+    fn price_impact(
+        &self,
+        amount_in: u64,
+        amount_out: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+    ) -> Result<Decimal, ProgramError> {
+        if amount_in == 0 {
+            return Ok(Decimal::zero());
+        }
+        // the marginal price of the pool. for the stable curve this is approximated
+        // by quoting a swap that is negligible relative to the pool size
+        let (probe_in, probe_out) = match self.curve {
+            OrcaCurve::ConstantProduct => (reserve_in, reserve_out),
+            OrcaCurve::Stable { .. } => {
+                let probe_in = std::cmp::max(reserve_in / 10_000, 1);
+                (
+                    probe_in,
+                    self.swap_without_fees(probe_in, reserve_in, reserve_out)?,
+                )
+            }
+        };
+        if probe_out == 0 {
+            return Ok(Decimal::zero());
+        }
+        let spot_price = Decimal::from(probe_out).try_div(probe_in)?;
+        let execution_price = Decimal::from(amount_out).try_div(amount_in)?;
+        if execution_price >= spot_price {
+            return Ok(Decimal::zero());
+        }
+        Decimal::one().try_sub(execution_price.try_div(spot_price)?)
+    }
+}
+
+impl OrcaQuote {
+    /// the minimum output amount after applying `slippage_bps`
+    pub fn minimum_amount_out(&self, slippage_bps: u64) -> Result<u64, ProgramError> {
+        super::minimum_amount_out(self.amount_out, slippage_bps)
+    }
+    /// returns true if the price impact is larger than `max_impact_bps`
+    pub fn exceeds_price_impact(&self, max_impact_bps: u64) -> Result<bool, ProgramError> {
+        let max_impact = Decimal::from(max_impact_bps).try_div(BPS_DENOMINATOR)?;
+        Ok(self.price_impact > max_impact)
+    }
+}
+
+/// calculates a fee the same way as the token-swap program, where any non-zero
+/// fee is rounded up to at least 1 token
+fn calculate_fee(amount: u64, numerator: u64, denominator: u64) -> Result<u64, ProgramError> {
+    if numerator == 0 || amount == 0 {
+        return Ok(0);
+    }
+    let fee = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(MathError::MathOverflow)?
+        .checked_div(denominator as u128)
+        .ok_or(MathError::MathOverflow)?;
+    if fee == 0 {
+        return Ok(1);
+    }
+    Ok(u64::try_from(fee).map_err(|_| MathError::MathOverflow)?)
+}
+
+fn constant_product_swap(amount_in: u128, reserve_in: u128, reserve_out: u128) -> Option<u128> {
+    let invariant = reserve_in.checked_mul(reserve_out)?;
+    let new_reserve_in = reserve_in.checked_add(amount_in)?;
+    let new_reserve_out = ceil_div(invariant, new_reserve_in)?;
+    reserve_out.checked_sub(new_reserve_out)
+}
+
+fn stable_swap(amp: u64, amount_in: u128, reserve_in: u128, reserve_out: u128) -> Option<u128> {
+    let leverage = amp.checked_mul(N_COINS as u64)?;
+    let d = compute_d(leverage, reserve_in, reserve_out)?;
+    let new_reserve_out =
+        compute_new_destination_amount(leverage, reserve_in.checked_add(amount_in)?, d)?;
+    reserve_out.checked_sub(new_reserve_out)
+}
+
+/// computes the stable swap invariant `d`
+fn compute_d(leverage: u64, amount_a: u128, amount_b: u128) -> Option<u128> {
+    let amount_a_times_coins = U256::from(amount_a).checked_mul(U256::from(N_COINS))?;
+    let amount_b_times_coins = U256::from(amount_b).checked_mul(U256::from(N_COINS))?;
+    let sum_x = U256::from(amount_a.checked_add(amount_b)?);
+    if sum_x.is_zero() {
+        return Some(0);
+    }
+    let mut d = sum_x;
+    for _ in 0..STABLE_ITERATIONS {
+        let d_product = d
+            .checked_mul(d)?
+            .checked_div(amount_a_times_coins)?
+            .checked_mul(d)?
+            .checked_div(amount_b_times_coins)?;
+        let d_previous = d;
+        // d = (leverage * sum_x + d_product * n_coins) * d / ((leverage - 1) * d + (n_coins + 1) * d_product)
+        let l_val = U256::from(leverage)
+            .checked_mul(sum_x)?
+            .checked_add(d_product.checked_mul(U256::from(N_COINS))?)?
+            .checked_mul(d)?;
+        let r_val = d
+            .checked_mul(U256::from(leverage.checked_sub(1)?))?
+            .checked_add(d_product.checked_mul(U256::from(N_COINS + 1))?)?;
+        d = l_val.checked_div(r_val)?;
+        if d == d_previous {
+            break;
+        }
+    }
+    u128::try_from(d).ok()
+}
+
+/// computes the destination balance of a stable pool given the new source balance, and invariant `d`
+fn compute_new_destination_amount(leverage: u64, new_source_amount: u128, d: u128) -> Option<u128> {
+    let leverage = U256::from(leverage);
+    let new_source_amount = U256::from(new_source_amount);
+    let d = U256::from(d);
+    // c = d^(n+1) / (n^(2n) * prod' * A)
+    let c = d.checked_mul(d)?.checked_mul(d)?.checked_div(
+        new_source_amount
+            .checked_mul(U256::from(N_COINS * N_COINS))?
+            .checked_mul(leverage)?,
+    )?;
+    // b = sum' + d / A
+    let b = new_source_amount.checked_add(d.checked_div(leverage)?)?;
+    // solve y^2 + b*y = c
+    let mut y = d;
+    for _ in 0..STABLE_ITERATIONS {
+        let numerator = y.checked_mul(y)?.checked_add(c)?;
+        let denominator = y
+            .checked_mul(U256::from(2_u8))?
+            .checked_add(b)?
+            .checked_sub(d)?;
+        let y_new = numerator
+            .checked_add(denominator.checked_sub(U256::from(1_u8))?)?
+            .checked_div(denominator)?;
+        if y_new == y {
+            break;
+        }
+        y = y_new;
+    }
+    u128::try_from(y).ok()
+}
+
+fn ceil_div(numerator: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+    numerator
+        .checked_add(denominator.checked_sub(1)?)?
+        .checked_div(denominator)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new(&data[offset..offset + 32])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    fn fees() -> OrcaFees {
+        // orca's standard 0.25% trade fee + 0.05% owner fee
+        OrcaFees {
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            owner_trade_fee_numerator: 5,
+            owner_trade_fee_denominator: 10_000,
+            ..Default::default()
+        }
+    }
+    #[test]
+    fn test_decode() {
+        let mut data = vec![0_u8; SWAP_ACCOUNT_SIZE];
+        data[0] = 1;
+        data[1] = 1;
+        let token_a = Pubkey::new_unique();
+        data[35..67].copy_from_slice(token_a.as_ref());
+        data[227..235].copy_from_slice(&25_u64.to_le_bytes());
+        data[235..243].copy_from_slice(&10_000_u64.to_le_bytes());
+        data[291] = 2;
+        data[292..300].copy_from_slice(&100_u64.to_le_bytes());
+        let pool = OrcaPool::try_from_slice(&data).unwrap();
+        assert_eq!(pool.token_a, token_a);
+        assert_eq!(pool.fees.trade_fee_numerator, 25);
+        assert_eq!(pool.fees.trade_fee_denominator, 10_000);
+        assert_eq!(pool.curve, OrcaCurve::Stable { amp: 100 });
+        data[291] = 1;
+        assert_eq!(OrcaPool::try_from_slice(&data).is_err(), true);
+        data[1] = 0;
+        assert_eq!(OrcaPool::try_from_slice(&data).is_err(), true);
+    }
+    #[test]
+    fn test_constant_product_quote() {
+        let pool = OrcaPool {
+            fees: fees(),
+            ..Default::default()
+        };
+        let quote = pool
+            .quote(1_000_000, 1_000_000_000, 2_000_000_000, true)
+            .unwrap();
+        assert_eq!(quote.trade_fee, 2_500);
+        assert_eq!(quote.owner_fee, 500);
+        // 997_000 in after fees -> 2e9 * 997_000 / 1_000_997_000 = 1_992_013, rounded down
+        assert_eq!(quote.amount_out, 1_992_013);
+        assert!(quote.price_impact > Decimal::zero());
+        assert_eq!(quote.exceeds_price_impact(10).unwrap(), false);
+        assert_eq!(quote.exceeds_price_impact(0).unwrap(), true);
+        assert_eq!(quote.minimum_amount_out(100).unwrap(), 1_972_092);
+
+        // reversing direction uses the other reserve as the source
+        let quote = pool
+            .quote(2_000_000, 1_000_000_000, 2_000_000_000, false)
+            .unwrap();
+        assert!(quote.amount_out < 1_000_000);
+
+        assert_eq!(pool.quote(1, 0, 100, true).is_err(), true);
+        // tiny amounts are still charged a fee
+        assert_eq!(pool.quote(1, 100, 100, true).is_err(), true);
+    }
+    #[test]
+    fn test_stable_quote() {
+        let stable = OrcaPool {
+            fees: fees(),
+            curve: OrcaCurve::Stable { amp: 100 },
+            ..Default::default()
+        };
+        let constant_product = OrcaPool {
+            fees: fees(),
+            ..Default::default()
+        };
+        let stable_quote = stable
+            .quote(100_000_000, 1_000_000_000, 1_000_000_000, true)
+            .unwrap();
+        let cp_quote = constant_product
+            .quote(100_000_000, 1_000_000_000, 1_000_000_000, true)
+            .unwrap();
+        // 0.25% trade fee and 0.05% owner fee are taken from the input
+        assert_eq!(cp_quote.trade_fee, 250_000);
+        assert_eq!(cp_quote.owner_fee, 50_000);
+        assert_eq!(stable_quote.trade_fee, cp_quote.trade_fee);
+        assert_eq!(stable_quote.owner_fee, cp_quote.owner_fee);
+        // 1e9 * 99.7e6 / (1e9 + 99.7e6)
+        assert_eq!(cp_quote.amount_out, 90_661_089);
+        assert_eq!(stable_quote.amount_out, 99_600_714);
+        // the stable curve has far less slippage for balanced pools
+        assert!(stable_quote.amount_out > cp_quote.amount_out);
+        assert!(stable_quote.amount_out < 99_970_000);
+        assert!(stable_quote.price_impact > Decimal::zero());
+        assert!(stable_quote.price_impact < cp_quote.price_impact);
+    }
+}
@@ -0,0 +1,587 @@
+//! valuation of raydium amm lp tokens, derived from the balances held by the amm
+//! in its coin/pc token accounts, and the funds the amm has sitting in its serum open orders account.
+//!
+//! two lp prices are provided:
+//!
+//! * `spot_lp_price` - the total value of the pool reserves divided by the lp supply. this is trivially
+//!   manipulated by anyone willing to skew the pool reserves within a single transaction
+//! * `fair_lp_price` - prices the pool using the oracle prices of both legs, and the constant product
+//!   invariant (`2 * sqrt(k * coin_price * pc_price) / lp_supply`), which is not affected by skewing reserves
+//!
+//! swaps through the amm curve can also be quoted, which is used to determine how much of a
+//! borrowed token must be swapped to reach the pool ratio before adding liquidity
+use super::{decimals_scaler, token_value};
+use crate::math::{
+    common::{TryAdd, TryDiv, TryMul, TrySub},
+    decimal::Decimal,
+    error::MathError,
+    uint::U256,
+};
+use anchor_lang::solana_program::{program_error::ProgramError, program_pack::Pack};
+
+/// offset of the native_coin_total field in a serum open orders account
+pub const OPEN_ORDERS_NATIVE_COIN_TOTAL_OFFSET: usize = 85;
+/// offset of the native_pc_total field in a serum open orders account
+pub const OPEN_ORDERS_NATIVE_PC_TOTAL_OFFSET: usize = 101;
+/// size of a serum open orders account, including the padding
+pub const OPEN_ORDERS_ACCOUNT_SIZE: usize = 3228;
+
+/// the coin and pc amounts an amm has deposited into its serum open orders account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpenOrdersTotals {
+    pub native_coin_total: u64,
+    pub native_pc_total: u64,
+}
+
+impl OpenOrdersTotals {
+    /// parses the coin and pc totals from the raw data of a serum open orders account
+    pub fn try_from_slice(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != OPEN_ORDERS_ACCOUNT_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &data[0..5] != b"serum" {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            native_coin_total: read_u64(data, OPEN_ORDERS_NATIVE_COIN_TOTAL_OFFSET),
+            native_pc_total: read_u64(data, OPEN_ORDERS_NATIVE_PC_TOTAL_OFFSET),
+        })
+    }
+}
+
+/// size of a raydium v4 amm account
+pub const AMM_INFO_ACCOUNT_SIZE: usize = 752;
+
+/// the subset of a raydium v4 amm account needed to quote swaps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RaydiumAmmInfo {
+    pub coin_decimals: u64,
+    pub pc_decimals: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    /// coin which is owed as protocol fees, and excluded from the pool reserves
+    pub need_take_pnl_coin: u64,
+    /// pc which is owed as protocol fees, and excluded from the pool reserves
+    pub need_take_pnl_pc: u64,
+}
+
+impl Default for RaydiumAmmInfo {
+    fn default() -> Self {
+        // raydium charges a 0.25% swap fee on all v4 pools
+        Self {
+            coin_decimals: 0,
+            pc_decimals: 0,
+            swap_fee_numerator: 25,
+            swap_fee_denominator: 10_000,
+            need_take_pnl_coin: 0,
+            need_take_pnl_pc: 0,
+        }
+    }
+}
+
+impl RaydiumAmmInfo {
+    /// parses the raw data of the account named by `LevFarmConfig::amm_id`
+    pub fn try_from_slice(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != AMM_INFO_ACCOUNT_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            coin_decimals: read_u64(data, 32),
+            pc_decimals: read_u64(data, 40),
+            swap_fee_numerator: read_u64(data, 176),
+            swap_fee_denominator: read_u64(data, 184),
+            need_take_pnl_coin: read_u64(data, 192),
+            need_take_pnl_pc: read_u64(data, 200),
+        })
+    }
+    /// returns the swap fee charged on `amount_in`, rounded up
+    pub fn swap_fee(&self, amount_in: u64) -> Result<u64, ProgramError> {
+        if self.swap_fee_denominator == 0 {
+            return Err(MathError::MathOverflow.into());
+        }
+        let numerator = (amount_in as u128)
+            .checked_mul(self.swap_fee_numerator as u128)
+            .ok_or(MathError::MathOverflow)?;
+        let fee = numerator
+            .checked_add(self.swap_fee_denominator as u128 - 1)
+            .ok_or(MathError::MathOverflow)?
+            / self.swap_fee_denominator as u128;
+        Ok(u64::try_from(fee).map_err(|_| MathError::MathOverflow)?)
+    }
+}
+
+/// the result of quoting a swap through the amm
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RaydiumSwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    /// the fraction of the output lost to moving the pool price, excluding fees.
+    /// a value of 0.01 indicates 1% price impact
+    pub price_impact: Decimal,
+}
+
+impl RaydiumSwapQuote {
+    /// the minimum output amount after applying `slippage_bps`
+    pub fn minimum_amount_out(&self, slippage_bps: u64) -> Result<u64, ProgramError> {
+        super::minimum_amount_out(self.amount_out, slippage_bps)
+    }
+}
+
+/// the swap needed to convert a pair of token amounts into the ratio of the pool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BalancedSwap {
+    /// if true coin is swapped for pc, otherwise pc is swapped for coin
+    pub coin_to_pc: bool,
+    pub quote: RaydiumSwapQuote,
+    /// coin available to add as liquidity after the swap
+    pub coin_amount: u64,
+    /// pc available to add as liquidity after the swap
+    pub pc_amount: u64,
+}
+
+/// the reserves of a raydium amm, and the supply of its lp token
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RaydiumPoolReserves {
+    /// total coin held by the amm, in native units
+    pub coin_amount: u64,
+    /// total pc held by the amm, in native units
+    pub pc_amount: u64,
+    /// supply of the lp token mint
+    pub lp_supply: u64,
+    pub coin_decimals: u8,
+    pub pc_decimals: u8,
+    pub lp_decimals: u8,
+}
+
+impl RaydiumPoolReserves {
+    /// combines the balances of the amm coin/pc token accounts with the
+    /// amounts the amm has in its open orders account
+    pub fn new(
+        coin_vault_balance: u64,
+        pc_vault_balance: u64,
+        open_orders: OpenOrdersTotals,
+        lp_supply: u64,
+        coin_decimals: u8,
+        pc_decimals: u8,
+        lp_decimals: u8,
+    ) -> Result<Self, ProgramError> {
+        Ok(Self {
+            coin_amount: coin_vault_balance
+                .checked_add(open_orders.native_coin_total)
+                .ok_or(MathError::MathOverflow)?,
+            pc_amount: pc_vault_balance
+                .checked_add(open_orders.native_pc_total)
+                .ok_or(MathError::MathOverflow)?,
+            lp_supply,
+            coin_decimals,
+            pc_decimals,
+            lp_decimals,
+        })
+    }
+    /// decodes the pool reserves from the raw account data of the accounts
+    /// named by `LevFarmConfig::{amm_coin_account, amm_pc_account, amm_open_orders, lp_mint}`
+    ///
+    /// the coin and pc decimals are not stored in any of these accounts, and must be supplied by the caller
+    pub fn try_from_accounts(
+        amm_coin_account: &[u8],
+        amm_pc_account: &[u8],
+        amm_open_orders: &[u8],
+        lp_mint: &[u8],
+        coin_decimals: u8,
+        pc_decimals: u8,
+    ) -> Result<Self, ProgramError> {
+        let coin_account = spl_token::state::Account::unpack(amm_coin_account)?;
+        let pc_account = spl_token::state::Account::unpack(amm_pc_account)?;
+        let lp_mint = spl_token::state::Mint::unpack(lp_mint)?;
+        Self::new(
+            coin_account.amount,
+            pc_account.amount,
+            OpenOrdersTotals::try_from_slice(amm_open_orders)?,
+            lp_mint.supply,
+            coin_decimals,
+            pc_decimals,
+            lp_mint.decimals,
+        )
+    }
+    /// removes the protocol fees which are held by the amm, but do not belong to liquidity providers.
+    /// the amm excludes these when pricing swaps
+    pub fn exclude_pnl(&self, amm: &RaydiumAmmInfo) -> Result<Self, ProgramError> {
+        let mut reserves = *self;
+        reserves.coin_amount = reserves
+            .coin_amount
+            .checked_sub(amm.need_take_pnl_coin)
+            .ok_or(MathError::MathOverflow)?;
+        reserves.pc_amount = reserves
+            .pc_amount
+            .checked_sub(amm.need_take_pnl_pc)
+            .ok_or(MathError::MathOverflow)?;
+        Ok(reserves)
+    }
+    /// quotes swapping `amount_in` through the amm curve. serum orderbook liquidity is not
+    /// considered, so the returned quote is what the amm itself will give for the swap
+    pub fn quote_swap(
+        &self,
+        amm: &RaydiumAmmInfo,
+        amount_in: u64,
+        coin_to_pc: bool,
+    ) -> Result<RaydiumSwapQuote, ProgramError> {
+        let (reserve_in, reserve_out) = self.swap_reserves(coin_to_pc);
+        if reserve_in == 0 || reserve_out == 0 {
+            return Err(MathError::MathOverflow.into());
+        }
+        let fee = amm.swap_fee(amount_in)?;
+        let amount_in_after_fee = amount_in.checked_sub(fee).ok_or(MathError::MathOverflow)?;
+        let amount_out = (reserve_out as u128)
+            .checked_mul(amount_in_after_fee as u128)
+            .ok_or(MathError::MathOverflow)?
+            .checked_div(
+                (reserve_in as u128)
+                    .checked_add(amount_in_after_fee as u128)
+                    .ok_or(MathError::MathOverflow)?,
+            )
+            .ok_or(MathError::MathOverflow)?;
+        let amount_out = u64::try_from(amount_out).map_err(|_| MathError::MathOverflow)?;
+        let price_impact = if amount_in_after_fee == 0 {
+            Decimal::zero()
+        } else {
+            let spot_price = Decimal::from(reserve_out).try_div(reserve_in)?;
+            let execution_price = Decimal::from(amount_out).try_div(amount_in_after_fee)?;
+            // rounding can leave the execution price at or above the spot price for tiny swaps
+            if execution_price >= spot_price {
+                Decimal::zero()
+            } else {
+                Decimal::one().try_sub(execution_price.try_div(spot_price)?)?
+            }
+        };
+        Ok(RaydiumSwapQuote {
+            amount_in,
+            amount_out,
+            fee,
+            price_impact,
+        })
+    }
+    /// given `coin_amount` and `pc_amount` (ie: the balances of a user farm after borrowing),
+    /// returns the swap which leaves the remaining balances as close as possible to the pool ratio.
+    /// this is the swap that should be made before adding liquidity
+    pub fn balanced_swap(
+        &self,
+        amm: &RaydiumAmmInfo,
+        coin_amount: u64,
+        pc_amount: u64,
+    ) -> Result<BalancedSwap, ProgramError> {
+        let (coin_to_pc, amount_in) =
+            self.balanced_swap_by(coin_amount, pc_amount, |amount_in, coin_to_pc| {
+                Ok(self.quote_swap(amm, amount_in, coin_to_pc)?.amount_out)
+            })?;
+        let quote = if amount_in == 0 {
+            RaydiumSwapQuote::default()
+        } else {
+            self.quote_swap(amm, amount_in, coin_to_pc)?
+        };
+        let (coin_amount, pc_amount) = if coin_to_pc {
+            (
+                coin_amount - amount_in,
+                pc_amount
+                    .checked_add(quote.amount_out)
+                    .ok_or(MathError::MathOverflow)?,
+            )
+        } else {
+            (
+                coin_amount
+                    .checked_add(quote.amount_out)
+                    .ok_or(MathError::MathOverflow)?,
+                pc_amount - amount_in,
+            )
+        };
+        Ok(BalancedSwap {
+            coin_to_pc,
+            quote,
+            coin_amount,
+            pc_amount,
+        })
+    }
+    /// like `balanced_swap`, but quotes swaps using `quote`, which is given the amount in and
+    /// swap direction, and returns the amount out. this allows balancing amounts against pools
+    /// other than raydium amms, such as orca pools whose reserves are stored in this struct.
+    ///
+    /// returns true if coin should be swapped for pc, and the amount of tokens to swap
+    pub fn balanced_swap_by(
+        &self,
+        coin_amount: u64,
+        pc_amount: u64,
+        mut quote: impl FnMut(u64, bool) -> Result<u64, ProgramError>,
+    ) -> Result<(bool, u64), ProgramError> {
+        if self.coin_amount == 0 || self.pc_amount == 0 {
+            return Err(MathError::MathOverflow.into());
+        }
+        let coin_value = U256::from(coin_amount) * U256::from(self.pc_amount);
+        let pc_value = U256::from(pc_amount) * U256::from(self.coin_amount);
+        let coin_to_pc = coin_value > pc_value;
+        let (amount_in, amount_out) = if coin_to_pc {
+            (coin_amount, pc_amount)
+        } else {
+            (pc_amount, coin_amount)
+        };
+        if coin_value == pc_value || amount_in == 0 {
+            return Ok((coin_to_pc, 0));
+        }
+        let (reserve_in, reserve_out) = self.swap_reserves(coin_to_pc);
+        // the remaining ratio decreases monotonically with the amount swapped,
+        // so binary search for the largest swap that doesn't overshoot the pool ratio
+        let mut low = 0_u64;
+        let mut high = amount_in;
+        while low < high {
+            let mid = low + (high - low).checked_add(1).ok_or(MathError::MathOverflow)? / 2;
+            let received = quote(mid, coin_to_pc)?;
+            // (in - swapped) / (out + received) >= (reserve_in + swapped) / (reserve_out - received)
+            let lhs =
+                U256::from(amount_in - mid) * U256::from(reserve_out.saturating_sub(received));
+            let rhs = (U256::from(amount_out) + U256::from(received))
+                * (U256::from(reserve_in) + U256::from(mid));
+            if lhs >= rhs {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Ok((coin_to_pc, low))
+    }
+    /// the amount of coin (in ui units) backing a single lp token
+    pub fn coin_per_lp(&self) -> Result<Decimal, ProgramError> {
+        self.per_lp(self.coin_amount, self.coin_decimals)
+    }
+    /// the amount of pc (in ui units) backing a single lp token
+    pub fn pc_per_lp(&self) -> Result<Decimal, ProgramError> {
+        self.per_lp(self.pc_amount, self.pc_decimals)
+    }
+    /// returns the native coin and pc amounts that `lp_amount` lp tokens are redeemable for
+    pub fn underlying_amounts(&self, lp_amount: u64) -> Result<(u64, u64), ProgramError> {
+        if self.lp_supply == 0 {
+            return Err(MathError::MathOverflow.into());
+        }
+        let share = |amount: u64| -> Result<u64, ProgramError> {
+            let value = (amount as u128)
+                .checked_mul(lp_amount as u128)
+                .ok_or(MathError::MathOverflow)?
+                .checked_div(self.lp_supply as u128)
+                .ok_or(MathError::MathOverflow)?;
+            Ok(u64::try_from(value).map_err(|_| MathError::MathOverflow)?)
+        };
+        Ok((share(self.coin_amount)?, share(self.pc_amount)?))
+    }
+    /// returns the price of a single lp token, calculated as the value of the pool reserves
+    /// divided by the lp supply. this price can be manipulated by skewing the pool reserves, prefer `fair_lp_price`
+    pub fn spot_lp_price(
+        &self,
+        coin_price: Decimal,
+        pc_price: Decimal,
+    ) -> Result<Decimal, ProgramError> {
+        let coin_value = token_value(self.coin_amount, self.coin_decimals, coin_price)?;
+        let pc_value = token_value(self.pc_amount, self.pc_decimals, pc_price)?;
+        coin_value.try_add(pc_value)?.try_div(self.ui_lp_supply()?)
+    }
+    /// returns the manipulation resistant price of a single lp token, using the oracle prices of both legs
+    pub fn fair_lp_price(
+        &self,
+        coin_price: Decimal,
+        pc_price: Decimal,
+    ) -> Result<Decimal, ProgramError> {
+        let coin_value = token_value(self.coin_amount, self.coin_decimals, coin_price)?;
+        let pc_value = token_value(self.pc_amount, self.pc_decimals, pc_price)?;
+        coin_value
+            .try_mul(pc_value)?
+            .try_sqrt()?
+            .try_mul(2_u64)?
+            .try_div(self.ui_lp_supply()?)
+    }
+    /// returns the value of `lp_amount` native lp tokens, at the given lp price
+    pub fn lp_value(&self, lp_amount: u64, lp_price: Decimal) -> Result<Decimal, ProgramError> {
+        token_value(lp_amount, self.lp_decimals, lp_price)
+    }
+    fn swap_reserves(&self, coin_to_pc: bool) -> (u64, u64) {
+        if coin_to_pc {
+            (self.coin_amount, self.pc_amount)
+        } else {
+            (self.pc_amount, self.coin_amount)
+        }
+    }
+    fn ui_lp_supply(&self) -> Result<Decimal, ProgramError> {
+        if self.lp_supply == 0 {
+            return Err(MathError::MathOverflow.into());
+        }
+        Decimal::from(self.lp_supply).try_div(decimals_scaler(self.lp_decimals)?)
+    }
+    fn per_lp(&self, amount: u64, decimals: u8) -> Result<Decimal, ProgramError> {
+        Decimal::from(amount)
+            .try_div(decimals_scaler(decimals)?)?
+            .try_div(self.ui_lp_supply()?)
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    fn reserves() -> RaydiumPoolReserves {
+        // 1000 coin @ 6 decimals, 4000 pc @ 6 decimals, 2000 lp @ 6 decimals
+        RaydiumPoolReserves::new(
+            900_000_000,
+            3_000_000_000,
+            OpenOrdersTotals {
+                native_coin_total: 100_000_000,
+                native_pc_total: 1_000_000_000,
+            },
+            2_000_000_000,
+            6,
+            6,
+            6,
+        )
+        .unwrap()
+    }
+    #[test]
+    fn test_open_orders_totals() {
+        let mut data = vec![0_u8; OPEN_ORDERS_ACCOUNT_SIZE];
+        data[0..5].copy_from_slice(b"serum");
+        data[OPEN_ORDERS_NATIVE_COIN_TOTAL_OFFSET..OPEN_ORDERS_NATIVE_COIN_TOTAL_OFFSET + 8]
+            .copy_from_slice(&420_u64.to_le_bytes());
+        data[OPEN_ORDERS_NATIVE_PC_TOTAL_OFFSET..OPEN_ORDERS_NATIVE_PC_TOTAL_OFFSET + 8]
+            .copy_from_slice(&69_u64.to_le_bytes());
+        let totals = OpenOrdersTotals::try_from_slice(&data).unwrap();
+        assert_eq!(totals.native_coin_total, 420);
+        assert_eq!(totals.native_pc_total, 69);
+        data[0] = 0;
+        assert_eq!(OpenOrdersTotals::try_from_slice(&data).is_err(), true);
+        assert_eq!(
+            OpenOrdersTotals::try_from_slice(&data[0..100]).is_err(),
+            true
+        );
+    }
+    #[test]
+    fn test_per_lp() {
+        let reserves = reserves();
+        assert_eq!(reserves.coin_amount, 1_000_000_000);
+        assert_eq!(reserves.pc_amount, 4_000_000_000);
+        assert_eq!(reserves.coin_per_lp().unwrap(), Decimal::from_percent(50));
+        assert_eq!(reserves.pc_per_lp().unwrap(), Decimal::from(2_u64));
+        assert_eq!(
+            reserves.underlying_amounts(1_000_000).unwrap(),
+            (500_000, 2_000_000)
+        );
+    }
+    #[test]
+    fn test_lp_price() {
+        let reserves = reserves();
+        // balanced pool, coin @ $4, pc @ $1 -> both prices agree
+        let spot = reserves
+            .spot_lp_price(Decimal::from(4_u64), Decimal::one())
+            .unwrap();
+        let fair = reserves
+            .fair_lp_price(Decimal::from(4_u64), Decimal::one())
+            .unwrap();
+        assert_eq!(spot, Decimal::from(4_u64));
+        assert_eq!(fair, Decimal::from(4_u64));
+        assert_eq!(
+            reserves.lp_value(2_500_000, fair).unwrap(),
+            Decimal::from(10_u64)
+        );
+        // oracle disagrees with the pool, fair price is below spot price
+        let spot = reserves
+            .spot_lp_price(Decimal::from(16_u64), Decimal::one())
+            .unwrap();
+        let fair = reserves
+            .fair_lp_price(Decimal::from(16_u64), Decimal::one())
+            .unwrap();
+        assert_eq!(spot, Decimal::from(10_u64));
+        assert_eq!(fair, Decimal::from(8_u64));
+
+        let empty = RaydiumPoolReserves::default();
+        assert_eq!(
+            empty.fair_lp_price(Decimal::one(), Decimal::one()).is_err(),
+            true
+        );
+        assert_eq!(empty.underlying_amounts(1).is_err(), true);
+    }
+    #[test]
+    fn test_amm_info() {
+        let mut data = vec![0_u8; AMM_INFO_ACCOUNT_SIZE];
+        data[32..40].copy_from_slice(&6_u64.to_le_bytes());
+        data[40..48].copy_from_slice(&9_u64.to_le_bytes());
+        data[176..184].copy_from_slice(&25_u64.to_le_bytes());
+        data[184..192].copy_from_slice(&10_000_u64.to_le_bytes());
+        data[192..200].copy_from_slice(&1_u64.to_le_bytes());
+        data[200..208].copy_from_slice(&2_u64.to_le_bytes());
+        let amm = RaydiumAmmInfo::try_from_slice(&data).unwrap();
+        assert_eq!(amm.coin_decimals, 6);
+        assert_eq!(amm.pc_decimals, 9);
+        assert_eq!(amm.swap_fee_numerator, 25);
+        assert_eq!(amm.swap_fee_denominator, 10_000);
+        let reserves = reserves().exclude_pnl(&amm).unwrap();
+        assert_eq!(reserves.coin_amount, 999_999_999);
+        assert_eq!(reserves.pc_amount, 3_999_999_998);
+        assert_eq!(RaydiumAmmInfo::try_from_slice(&data[1..]).is_err(), true);
+    }
+    #[test]
+    fn test_quote_swap() {
+        let reserves = reserves();
+        let amm = RaydiumAmmInfo::default();
+        let quote = reserves.quote_swap(&amm, 1_000_000, true).unwrap();
+        assert_eq!(quote.fee, 2_500);
+        // 4e9 * 997_500 / (1e9 + 997_500)
+        assert_eq!(quote.amount_out, 3_986_023);
+        assert!(quote.price_impact > Decimal::zero());
+        assert!(quote.price_impact < Decimal::from_percent(1));
+        assert_eq!(quote.minimum_amount_out(100).unwrap(), 3_946_162);
+        let quote = reserves.quote_swap(&amm, 4_000_000, false).unwrap();
+        assert_eq!(quote.amount_out, 996_505);
+        // a tiny swap against a pool whose spot price rounds down to zero
+        let skewed = RaydiumPoolReserves {
+            coin_amount: 10_000_000_000_000_000_000,
+            pc_amount: 1,
+            ..reserves
+        };
+        let quote = skewed.quote_swap(&amm, 1_000, true).unwrap();
+        assert_eq!(quote.fee, 3);
+        assert_eq!(quote.amount_out, 0);
+        assert_eq!(quote.price_impact, Decimal::zero());
+        assert_eq!(
+            RaydiumPoolReserves::default()
+                .quote_swap(&amm, 1, true)
+                .is_err(),
+            true
+        );
+    }
+    #[test]
+    fn test_balanced_swap() {
+        let reserves = reserves();
+        let amm = RaydiumAmmInfo::default();
+        // only coin, roughly half of it should be swapped
+        let swap = reserves.balanced_swap(&amm, 10_000_000, 0).unwrap();
+        assert_eq!(swap.coin_to_pc, true);
+        assert!(swap.quote.amount_in > 4_990_000 && swap.quote.amount_in < 5_010_000);
+        assert_eq!(swap.coin_amount, 10_000_000 - swap.quote.amount_in);
+        assert_eq!(swap.pc_amount, swap.quote.amount_out);
+        // remaining amounts are within a unit of the post swap pool ratio
+        let pool_coin = reserves.coin_amount + swap.quote.amount_in;
+        let pool_pc = reserves.pc_amount - swap.quote.amount_out;
+        let ratio_diff = (swap.coin_amount as u128 * pool_pc as u128) as i128
+            - (swap.pc_amount as u128 * pool_coin as u128) as i128;
+        assert!(ratio_diff.unsigned_abs() < pool_pc as u128 * 2);
+
+        // mostly pc, swap pc for coin
+        let swap = reserves.balanced_swap(&amm, 1_000_000, 40_000_000).unwrap();
+        assert_eq!(swap.coin_to_pc, false);
+        assert_eq!(swap.pc_amount, 40_000_000 - swap.quote.amount_in);
+
+        // already balanced, nothing to swap
+        let swap = reserves.balanced_swap(&amm, 1_000_000, 4_000_000).unwrap();
+        assert_eq!(swap.quote.amount_in, 0);
+        assert_eq!(swap.coin_amount, 1_000_000);
+        assert_eq!(swap.pc_amount, 4_000_000);
+    }
+}
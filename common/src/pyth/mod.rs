@@ -223,16 +223,19 @@ pub fn parse_pyth_price(pyth_price: &Price) -> Result<Decimal, ProgramError> {
 mod test {
 
     use super::*;
-    use anchor_client::solana_client::rpc_client::RpcClient;
+    use crate::fixtures::{AccountProvider, JsonFileAccountProvider};
     use anchor_lang::solana_program;
     use static_pubkey::static_pubkey;
     #[test]
     fn test_get_pyth_price_account() {
-        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
-        let tulip_price_account_key =
-            static_pubkey!("5RHxy1NbUR15y34uktDbN1a2SWbhgHwkCZ75yK2RJ1FC");
-        let tulip_price_account = rpc.get_account(&tulip_price_account_key).unwrap();
-        let price = load_pyth_price(&tulip_price_account.data[..]).unwrap();
-        println!("price {:#?}", price);
+        let fixtures = JsonFileAccountProvider::fixtures();
+        for price_account_key in [
+            static_pubkey!("AnLf8tVYCM816gmBjiy8n53eXKKEDydT5piYjjQDPgTB"),
+            static_pubkey!("Gnt27xtC473ZT2Mw5u8wZ68Z3gULkSTb5DuxJy7eJotD"),
+        ] {
+            let price_account = fixtures.get_account(&price_account_key).unwrap();
+            let price = load_pyth_price(price_account.data()).unwrap();
+            assert!(price > Decimal::zero());
+        }
     }
 }
@@ -1,28 +1,121 @@
 //! Functions for converting strings to the "tag format", and taking a tag formatted value
 //! and converting it back into a string. Assumes all data is standard UTF8 encoded
+//!
+//! The `VaultTag` type wraps the raw 32 byte tag used as a seed during vault address derivation.
+//! Tags read from on-chain accounts may contain arbitrary bytes, while tags created from strings
+//! are validated to be at most 32 bytes of UTF8 without any 0 bytes, which are used as padding
 
-pub fn tag(value: &str) -> Option<[u8; 32]> {
-    if value.len().gt(&32) {
-        return None;
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// size of a tag in bytes
+pub const TAG_SIZE: usize = 32;
+
+/// errors returned when creating a tag from a string
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum TagError {
+    #[error("tag is longer than 32 bytes")]
+    TooLong,
+    #[error("tag contains a 0 byte")]
+    ContainsNul,
+    #[error("tag is not valid utf8")]
+    InvalidUtf8,
+}
+
+/// the 32 byte tag of a vault, padded with 0's
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VaultTag([u8; TAG_SIZE]);
+
+impl VaultTag {
+    /// creates a tag from a string, failing if the string is longer than 32 bytes
+    /// or contains a 0 byte
+    pub fn new(value: &str) -> Result<Self, TagError> {
+        if value.len() > TAG_SIZE {
+            return Err(TagError::TooLong);
+        }
+        if value.bytes().any(|b| b == 0) {
+            return Err(TagError::ContainsNul);
+        }
+        let mut tag = [0_u8; TAG_SIZE];
+        tag[..value.len()].copy_from_slice(value.as_bytes());
+        Ok(Self(tag))
+    }
+    pub fn bytes(&self) -> &[u8; TAG_SIZE] {
+        &self.0
+    }
+    /// returns true if the tag is all 0's
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0_u8; TAG_SIZE]
+    }
+    /// returns the tag as a string, with the 0 padding removed. fails if the
+    /// tag is not valid utf8, or contains 0's before the end of the tag
+    pub fn as_str(&self) -> Result<&str, TagError> {
+        let len = self
+            .0
+            .iter()
+            .rposition(|b| *b != 0)
+            .map_or(0, |idx| idx + 1);
+        if self.0[..len].contains(&0) {
+            return Err(TagError::ContainsNul);
+        }
+        std::str::from_utf8(&self.0[..len]).map_err(|_| TagError::InvalidUtf8)
+    }
+    /// returns a copy of the tag with all ascii characters lowercased, which is how
+    /// tags are compared when identifying strategy vaults
+    pub fn to_ascii_lowercase(&self) -> Self {
+        let mut tag = *self;
+        tag.0.make_ascii_lowercase();
+        tag
+    }
+}
+
+impl fmt::Display for VaultTag {
+    /// formats the tag as a string, ignoring 0's and replacing invalid utf8
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_str() {
+            Ok(value) => f.write_str(value),
+            Err(_) => f.write_str(&tag_to_str(&self.0)),
+        }
+    }
+}
+
+impl FromStr for VaultTag {
+    type Err = TagError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl From<[u8; TAG_SIZE]> for VaultTag {
+    fn from(val: [u8; TAG_SIZE]) -> Self {
+        Self(val)
     }
-    let mut _tag = [0_u8; 32];
-    for (idx, b) in value.as_bytes().iter().enumerate() {
-        _tag[idx] = *b;
+}
+
+impl From<VaultTag> for [u8; TAG_SIZE] {
+    fn from(val: VaultTag) -> Self {
+        val.0
+    }
+}
+
+impl AsRef<[u8]> for VaultTag {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
     }
-    Some(_tag)
+}
+
+/// converts a string into a tag byte array, returning None if
+/// the string is larger than 32 bytes or contains a 0 byte
+pub fn tag(value: &str) -> Option<[u8; 32]> {
+    VaultTag::new(value).ok().map(Into::into)
 }
 
 /// converts a tag byte array into a tag string
-/// 0 values are ignored and not parsed into a string
+/// 0 values are ignored and not parsed into a string, while
+/// invalid utf8 is replaced with U+FFFD
 pub fn tag_to_str(data: &[u8; 32]) -> String {
-    let mut value = String::with_capacity(32);
-    data.iter().for_each(|b| {
-        let b = *b;
-        if b != 0 {
-            value.push(b as char);
-        }
-    });
-    value
+    let value: Vec<u8> = data.iter().copied().filter(|b| *b != 0).collect();
+    String::from_utf8_lossy(&value).into_owned()
 }
 
 #[cfg(test)]
@@ -41,4 +134,39 @@ mod tests {
         let got_tag_msg = tag_to_str(&tag_bytes);
         assert_eq!(got_tag_msg, tag_msg);
     }
+    #[test]
+    fn test_vault_tag() {
+        let tag = VaultTag::new("USDCv1").unwrap();
+        assert_eq!(tag.as_str().unwrap(), "USDCv1");
+        assert_eq!(tag.to_string(), "USDCv1");
+        assert_eq!(tag.to_ascii_lowercase(), "usdcv1".parse().unwrap());
+        assert_eq!(<[u8; 32]>::from(tag), super::tag("USDCv1").unwrap());
+        assert!(VaultTag::default().is_empty());
+        assert_eq!(VaultTag::default().as_str().unwrap(), "");
+
+        // multi-byte characters are preserved, and count towards the 32 byte limit
+        let tag = VaultTag::new("tülip").unwrap();
+        assert_eq!(tag.to_string(), "tülip");
+        assert_eq!(tag_to_str(tag.bytes()), "tülip");
+        assert_eq!(
+            VaultTag::new(&"ü".repeat(16))
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .len(),
+            32
+        );
+        assert_eq!(VaultTag::new(&"ü".repeat(17)), Err(TagError::TooLong));
+        assert_eq!(VaultTag::new("a\0b"), Err(TagError::ContainsNul));
+
+        // on-chain tags are accepted as is, but may not be valid strings
+        let mut bytes = [0_u8; 32];
+        bytes[0] = b'a';
+        bytes[2] = b'b';
+        assert_eq!(VaultTag::from(bytes).as_str(), Err(TagError::ContainsNul));
+        assert_eq!(VaultTag::from(bytes).to_string(), "ab");
+        bytes[1] = 0xff;
+        assert_eq!(VaultTag::from(bytes).as_str(), Err(TagError::InvalidUtf8));
+        assert_eq!(VaultTag::from(bytes).to_string(), "a\u{fffd}b");
+    }
 }
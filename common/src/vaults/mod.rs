@@ -1,35 +1,6 @@
-//! provides helper functions for parsing the Tulip V2 vault tag format
+//! provides helper functions for parsing the Tulip V2 vault tag format, re-exported from [crate::tag]
 
-/// parses a string representation of a vault tag
-/// into a 32 byte array . if the length of the string
-/// is less than 32 bytes, the remaining space is filled
-/// with 0's.
-///
-/// returns None if the string is larger than 32 bytes
-pub fn tag(value: &str) -> Option<[u8; 32]> {
-    if value.len().gt(&32) {
-        return None;
-    }
-    let mut _tag = [0_u8; 32];
-    for (idx, b) in value.as_bytes().iter().enumerate() {
-        _tag[idx] = *b;
-    }
-    Some(_tag)
-}
-
-/// converts a byte array representation of a tag into
-/// it's string representation. only useful if the underlying
-/// binary data is utf8
-pub fn tag_to_str(data: &[u8; 32]) -> String {
-    let mut value = String::with_capacity(32);
-    data.iter().for_each(|b| {
-        let b = *b;
-        if b != 0 {
-            value.push(b as char);
-        }
-    });
-    value
-}
+pub use crate::tag::{tag, tag_to_str, VaultTag};
 
 #[cfg(test)]
 mod tests {
@@ -130,6 +130,14 @@ impl ToString for Atrix {
 }
 
 impl Atrix {
+    /// all named atrix farms, excluding placeholders
+    pub const ALL: [Atrix; 5] = [
+        Atrix::SOLUSDC,
+        Atrix::GMTUSDC,
+        Atrix::mSOLUSDC,
+        Atrix::BTCUSDC,
+        Atrix::USDrUSDC,
+    ];
     pub fn name(&self) -> String {
         self.to_string()
     }
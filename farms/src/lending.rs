@@ -180,6 +180,20 @@ impl ToString for Lending {
 }
 
 impl Lending {
+    /// all named lending farms, excluding placeholders
+    pub const ALL: [Lending; 11] = [
+        Lending::TULIP,
+        Lending::SOLEND,
+        Lending::MANGO,
+        Lending::PORT,
+        Lending::LARIX,
+        Lending::PARROT,
+        Lending::USDC,
+        Lending::MULTI_DEPOSIT,
+        Lending::RAY,
+        Lending::USDT,
+        Lending::SOL,
+    ];
     pub fn name(&self) -> String {
         self.to_string()
     }
@@ -33,6 +33,7 @@ use crate::{
     atrix::Atrix, lending::Lending, orca::Orca, quarry::Quarry, raydium::Raydium, unknown::Unknown,
 };
 use anchor_lang::prelude::*;
+use std::{fmt, str::FromStr};
 use tulip_arrform::{arrform, ArrForm};
 
 #[derive(Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, Debug)]
@@ -75,6 +76,21 @@ pub enum Farm {
 }
 
 impl Farm {
+    /// returns every named farm across all platforms, excluding placeholders and unknown farms
+    pub fn all() -> impl Iterator<Item = Farm> {
+        Raydium::ALL
+            .into_iter()
+            .map(Farm::from)
+            .chain(Lending::ALL.into_iter().map(Farm::from))
+            .chain(Orca::ALL.into_iter().map(Farm::from))
+            .chain(Quarry::ALL.into_iter().map(Farm::from))
+            .chain(Atrix::ALL.into_iter().map(Farm::from))
+    }
+    /// returns true if either the farm identifier or farm name is unknown
+    pub fn is_unknown(&self) -> bool {
+        let wire_type: [u64; 2] = (*self).into();
+        wire_type[0] == u64::MAX || wire_type[1] == u64::MAX
+    }
     pub fn name(&self) -> String {
         self.to_string()
     }
@@ -147,6 +163,31 @@ impl From<&str> for Farm {
     }
 }
 
+/// error returned when parsing a string which is not the name of a known farm
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseFarmError(pub String);
+
+impl fmt::Display for ParseFarmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown farm {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseFarmError {}
+
+/// unlike the `From<&str>` implementation, parsing fails instead of
+/// returning an unknown farm
+impl FromStr for Farm {
+    type Err = ParseFarmError;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let farm = farm_from_str(s);
+        if farm.is_unknown() {
+            return Err(ParseFarmError(s.to_string()));
+        }
+        Ok(farm)
+    }
+}
+
 impl Default for Farm {
     fn default() -> Self {
         Self::Unknown {
@@ -179,6 +220,12 @@ impl From<Quarry> for Farm {
     }
 }
 
+impl From<Atrix> for Farm {
+    fn from(val: Atrix) -> Self {
+        Farm::Atrix { name: val }
+    }
+}
+
 impl From<[u64; 2]> for Farm {
     fn from(val: [u64; 2]) -> Self {
         match val[0] {
@@ -275,6 +322,34 @@ fn farm_from_str(val: &str) -> Farm {
         },
     }
 }
+/// farms are serialized as their name, ie: "RAYDIUM-RAY-USDC". placeholder and unknown
+/// farms have no unique name, and fail to serialize
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+    impl Serialize for Farm {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            let name = self.name();
+            if Farm::from_str(&name).as_ref() != Ok(self) {
+                return Err(ser::Error::custom(format!("farm {:?} has no name", self)));
+            }
+            serializer.serialize_str(&name)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Farm {
+        fn deserialize<D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> std::result::Result<Self, D::Error> {
+            <String as Deserialize>::deserialize(deserializer)?
+                .parse()
+                .map_err(de::Error::custom)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -658,4 +733,90 @@ mod test {
         let farm4: [u64; 2] = farm.into();
         assert_eq!(farm3, farm4);
     }
+    #[test]
+    fn test_farm_round_trip() {
+        let mut count = 0;
+        for farm in Farm::all() {
+            count += 1;
+            assert!(!farm.is_unknown());
+            let name = farm.name();
+            assert_eq!(Farm::from_str(&name).unwrap(), farm);
+            assert_eq!(Farm::from(name.as_str()), farm);
+            let wire_type: [u64; 2] = farm.into();
+            assert_eq!(Farm::from(wire_type), farm);
+            assert_eq!(Farm::from(wire_type).name(), name);
+        }
+        assert_eq!(
+            count,
+            Raydium::ALL.len()
+                + Lending::ALL.len()
+                + Orca::ALL.len()
+                + Quarry::ALL.len()
+                + Atrix::ALL.len()
+        );
+        // names are unique, so no two farms share a string representation
+        let mut names: Vec<_> = Farm::all().map(|farm| farm.name()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+    #[test]
+    fn test_farm_wire_round_trip() {
+        // every farm name with a known identifier, including placeholders, round trips
+        // through the wire representation
+        for platform in 0..=4_u64 {
+            for name in (0..128_u64).chain(65536..65664) {
+                let farm = Farm::from([platform, name]);
+                if farm.is_unknown() {
+                    continue;
+                }
+                assert_eq!(<[u64; 2]>::from(farm), [platform, name]);
+            }
+        }
+        assert!(Farm::from([5_u64, 0_u64]).is_unknown());
+        assert!(Farm::from([0_u64, 10_000_u64]).is_unknown());
+        assert_eq!(<[u64; 2]>::from(Farm::default()), [u64::MAX, u64::MAX]);
+    }
+    #[test]
+    fn test_farm_from_str() {
+        assert_eq!(
+            "ATRIX-mSOL-USDC".parse::<Farm>().unwrap(),
+            Farm::Atrix {
+                name: Atrix::mSOLUSDC
+            }
+        );
+        for invalid in [
+            "",
+            "UNKNOWN",
+            "RAYDIUM",
+            "RAYDIUM-UNKNOWN",
+            "RAYDIUM-PLACEHOLDER_A",
+            "LENDING-FOO",
+            "FOO-RAY-USDC",
+        ] {
+            assert_eq!(
+                Farm::from_str(invalid),
+                Err(ParseFarmError(invalid.to_string()))
+            );
+            assert!(Farm::from(invalid).is_unknown());
+        }
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_farm_serde() {
+        use serde::de::{value::StrDeserializer, IntoDeserializer};
+        use serde::Deserialize;
+        for farm in Farm::all() {
+            let name = farm.name();
+            let deserializer: StrDeserializer<serde::de::value::Error> =
+                name.as_str().into_deserializer();
+            assert_eq!(
+                <Farm as Deserialize>::deserialize(deserializer).unwrap(),
+                farm
+            );
+        }
+        let deserializer: StrDeserializer<serde::de::value::Error> =
+            "RAYDIUM-FOO".into_deserializer();
+        assert!(<Farm as Deserialize>::deserialize(deserializer).is_err());
+    }
 }
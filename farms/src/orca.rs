@@ -176,6 +176,21 @@ impl ToString for Orca {
 }
 
 impl Orca {
+    /// all named orca farms, excluding placeholders
+    pub const ALL: [Orca; 12] = [
+        Orca::ATLASUSDC,
+        Orca::POLISUSDC,
+        Orca::ORCASOL,
+        Orca::USDTUSDC,
+        Orca::ORCAUSDC,
+        Orca::BASISUSDC,
+        Orca::SAMOUSDC,
+        Orca::SHDWUSDC,
+        Orca::SHDWSOL,
+        Orca::stSOLUSDC,
+        Orca::wUSTUSDC,
+        Orca::CMFIUSDC,
+    ];
     pub fn name(&self) -> String {
         self.to_string()
     }
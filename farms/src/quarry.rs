@@ -46,6 +46,8 @@ impl From<&str> for Quarry {
 }
 
 impl Quarry {
+    /// all quarry farm variants
+    pub const ALL: [Quarry; 3] = [Quarry::VANILLA, Quarry::SABER, Quarry::SUNNY];
     pub fn name(&self) -> String {
         match self {
             Quarry::SABER => String::from("SABER"),
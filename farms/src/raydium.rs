@@ -192,6 +192,29 @@ impl From<&str> for Raydium {
 }
 
 impl Raydium {
+    /// all named raydium farms, excluding placeholders
+    pub const ALL: [Raydium; 20] = [
+        Raydium::ALEPHUSDC,
+        Raydium::BOPRAY,
+        Raydium::COPEUSDC,
+        Raydium::LIKEUSDC,
+        Raydium::PSYUSDC,
+        Raydium::MERUSDC,
+        Raydium::stSOLUSDC,
+        Raydium::RAY,
+        Raydium::RAYUSDT,
+        Raydium::RAYUSDC,
+        Raydium::RAYSRM,
+        Raydium::RAYSOL,
+        Raydium::RAYETH,
+        Raydium::ROPEUSDC,
+        Raydium::SAMORAY,
+        Raydium::SNYUSDC,
+        Raydium::stSOLUSDT,
+        Raydium::TULIPUSDC,
+        Raydium::ATLASRAY,
+        Raydium::POLISRAY,
+    ];
     pub fn name(&self) -> String {
         match self {
             Raydium::ALEPHUSDC => String::from("ALEPH-USDC"),
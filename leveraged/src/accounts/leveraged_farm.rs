@@ -1,7 +1,13 @@
-use super::Farms;
+use super::{
+    derivations::derive_leveraged_farm_address, try_from_account_data, Farms,
+    LEVERAGED_FARM_ACCOUNT_SIZE,
+};
 use anchor_lang::prelude::*;
+#[cfg(test)]
+use type_layout::TypeLayout;
 
 #[account(zero_copy)]
+#[cfg_attr(test, derive(TypeLayout))]
 pub struct LeveragedFarm {
     /// denotes the account key of the global state account used for access control
     pub global: Pubkey,
@@ -104,3 +110,125 @@ pub struct LeveragedFarm {
     pub sell_slip: u64,
     pub buffer: [u8; 304],
 }
+
+impl LeveragedFarm {
+    /// offset of the farm type, including the account discriminator
+    pub const FARM_TYPE_OFFSET: usize = 1067;
+    /// decodes a leveraged farm account, validating the account discriminator,
+    /// the account size, and the farm type
+    pub fn try_from_slice(data: &[u8]) -> std::result::Result<Self, ProgramError> {
+        if data.len() != LEVERAGED_FARM_ACCOUNT_SIZE
+            || !Farms::is_valid(data[Self::FARM_TYPE_OFFSET])
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        try_from_account_data(data, LEVERAGED_FARM_ACCOUNT_SIZE)
+    }
+    /// decodes the leveraged farm account stored at `address`, additionally validating
+    /// that `address` is derived from the decoded account, returning the account along
+    /// with whether the address was derived using the legacy seeds
+    pub fn try_from_account(
+        address: &Pubkey,
+        data: &[u8],
+    ) -> std::result::Result<(Self, bool), ProgramError> {
+        let farm = Self::try_from_slice(data)?;
+        match farm.is_legacy_address(address) {
+            Some(legacy) => Ok((farm, legacy)),
+            None => Err(ProgramError::InvalidSeeds),
+        }
+    }
+    /// returns the address of the leveraged farm account, derived using either the
+    /// legacy or current seeds
+    pub fn derive_address(&self, legacy: bool) -> Pubkey {
+        derive_leveraged_farm_address(
+            self.solfarm_vault_program,
+            self.serum_market,
+            crate::ID,
+            self.farm_type,
+            legacy,
+        )
+        .0
+    }
+    /// returns Some(true) if `address` is derived using the legacy seeds, Some(false) if
+    /// it is derived using the current seeds, and None if it is not derived from this farm
+    pub fn is_legacy_address(&self, address: &Pubkey) -> Option<bool> {
+        [true, false]
+            .into_iter()
+            .find(|legacy| self.derive_address(*legacy) == *address)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::{field_end_offsets, to_account_data};
+    use static_pubkey::static_pubkey;
+    use tulipv2_sdk_common::fixtures::{AccountProvider, JsonFileAccountProvider};
+
+    #[test]
+    fn test_leveraged_farm_layout() {
+        let offsets = field_end_offsets::<LeveragedFarm>();
+        let farm_type = offsets
+            .iter()
+            .find(|(name, _)| name == "farm_type")
+            .unwrap()
+            .1;
+        assert_eq!(LeveragedFarm::FARM_TYPE_OFFSET, 8 + farm_type - 1);
+        assert_eq!(offsets.last().unwrap().1, LEVERAGED_FARM_ACCOUNT_SIZE - 8);
+        assert_eq!(
+            8 + std::mem::size_of::<LeveragedFarm>(),
+            LEVERAGED_FARM_ACCOUNT_SIZE
+        );
+    }
+
+    #[test]
+    fn test_leveraged_farm_try_from_slice() {
+        let mut farm: LeveragedFarm = bytemuck::Zeroable::zeroed();
+        farm.solfarm_vault_program = Pubkey::new_unique();
+        farm.serum_market = Pubkey::new_unique();
+        farm.farm_type = Farms::RaywhEthRayVault;
+        let mut data = to_account_data(&farm);
+        let decoded = LeveragedFarm::try_from_slice(&data).unwrap();
+        assert_eq!(decoded.farm_type, Farms::RaywhEthRayVault);
+
+        for legacy in [true, false] {
+            let address = farm.derive_address(legacy);
+            assert_eq!(farm.is_legacy_address(&address), Some(legacy));
+            assert_eq!(
+                LeveragedFarm::try_from_account(&address, &data).unwrap().1,
+                legacy
+            );
+        }
+        assert!(LeveragedFarm::try_from_account(&Pubkey::new_unique(), &data).is_err());
+
+        data[LeveragedFarm::FARM_TYPE_OFFSET] = Farms::RaywhEthRayVault as u8 + 1;
+        assert!(LeveragedFarm::try_from_slice(&data).is_err());
+        data[LeveragedFarm::FARM_TYPE_OFFSET] = Farms::Unknown as u8;
+        assert!(LeveragedFarm::try_from_slice(&data).is_ok());
+
+        assert!(LeveragedFarm::try_from_slice(&data[..data.len() - 1]).is_err());
+        data[0] ^= 1;
+        assert!(LeveragedFarm::try_from_slice(&data).is_err());
+    }
+
+    #[test]
+    fn test_leveraged_farm_fixtures() {
+        let provider = JsonFileAccountProvider::fixtures();
+        for (address, farm_type) in [
+            (
+                static_pubkey!("5o3EsLS1NTciKHXVsGNYqQQ8iBBK3dBfSPwCH7wsdtRT"),
+                Farms::OrcaUsdcVault,
+            ),
+            (
+                static_pubkey!("84ayseJgpJavzfeESgRdyfMoDo2bs4J2YUBjMT4iTs66"),
+                Farms::RayUsdcVault,
+            ),
+        ] {
+            let account = provider.get_account(&address).unwrap();
+            assert_eq!(account.owner, crate::ID);
+            let (farm, legacy) = LeveragedFarm::try_from_account(&address, account.data()).unwrap();
+            assert_eq!(farm.farm_type, farm_type);
+            assert_eq!(farm.derive_address(legacy), address);
+        }
+    }
+}
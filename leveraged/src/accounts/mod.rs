@@ -4,7 +4,7 @@ pub mod leveraged_farm;
 pub mod position_info;
 pub mod user_farm;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
 
 pub const POSITION_INFO_ACCOUNT_SIZE: usize = 300;
 pub const USER_FARM_ACCOUNT_SIZE: usize = 247;
@@ -74,6 +74,13 @@ pub enum Position {
     RemovedLiquidityForLiquidation,
 }
 
+impl Position {
+    /// returns true if `value` is the discriminant of a position
+    pub fn is_valid(value: u8) -> bool {
+        value <= Position::RemovedLiquidityForLiquidation as u8
+    }
+}
+
 impl From<u8> for Position {
     fn from(i: u8) -> Position {
         match i {
@@ -393,6 +400,16 @@ impl From<&str> for Farms {
 }
 
 impl Farms {
+    /// returns true if `value` is the discriminant of a farm, including `Farms::Unknown`
+    pub fn is_valid(value: u8) -> bool {
+        value == Farms::Unknown as u8 || Farms::from(value as u64) != Farms::Unknown
+    }
+    /// returns an iterator over all known farms, excluding `Farms::Unknown`
+    pub fn all() -> impl Iterator<Item = Farms> {
+        (0_u64..)
+            .map(Farms::from)
+            .take_while(|farm| *farm != Farms::Unknown)
+    }
     pub fn is_aquafarm(&self) -> bool {
         match self {
             Farms::OrcaUsdcVault
@@ -499,3 +516,116 @@ impl Farms {
         }
     }
 }
+
+/// decodes a zero copy account, validating that `data` is exactly `size` bytes
+/// and begins with the account's discriminator. any enum or bool fields must
+/// be validated by the caller before decoding
+pub(crate) fn try_from_account_data<T: bytemuck::Pod + Discriminator>(
+    data: &[u8],
+    size: usize,
+) -> std::result::Result<T, ProgramError> {
+    if data.len() != size || size != 8 + std::mem::size_of::<T>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if data[0..8] != T::discriminator() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(bytemuck::pod_read_unaligned(&data[8..]))
+}
+
+/// returns the account data of `account`, prefixed with its discriminator
+#[cfg(test)]
+pub(crate) fn to_account_data<T: bytemuck::Pod + Discriminator>(account: &T) -> Vec<u8> {
+    let mut data = T::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(account));
+    data
+}
+
+/// returns the name of each field of a type, along with the offset at which the field
+/// ends, panicking if the type contains any padding
+#[cfg(test)]
+pub(crate) fn field_end_offsets<T: type_layout::TypeLayout>() -> Vec<(String, usize)> {
+    let mut end = 0;
+    T::type_layout()
+        .fields
+        .into_iter()
+        .map(|field| match field {
+            type_layout::Field::Field { name, size, .. } => {
+                end += size;
+                (name.to_string(), end)
+            }
+            type_layout::Field::Padding { size } => panic!("unexpected padding of {} bytes", size),
+        })
+        .collect()
+}
+
+/// implements `TypeLayout` for packed structs, which the derive macro does not support
+/// as it takes references to each field
+#[cfg(test)]
+macro_rules! impl_packed_type_layout {
+    ($ty:ident { $($field:ident),* $(,)? }) => {
+        impl type_layout::TypeLayout for $ty {
+            fn type_layout() -> type_layout::TypeLayoutInfo {
+                fn size_of_pointee<T>(_: *const T) -> usize {
+                    std::mem::size_of::<T>()
+                }
+                let value = std::mem::MaybeUninit::<$ty>::uninit();
+                let base = value.as_ptr();
+                let mut fields = Vec::new();
+                let mut last_field_end = 0;
+                $(
+                    // safety: the field is never read, and addr_of does not create a reference
+                    let field = unsafe { std::ptr::addr_of!((*base).$field) };
+                    let offset = field as usize - base as usize;
+                    if offset > last_field_end {
+                        fields.push(type_layout::Field::Padding {
+                            size: offset - last_field_end,
+                        });
+                    }
+                    fields.push(type_layout::Field::Field {
+                        name: stringify!($field).into(),
+                        ty: "".into(),
+                        size: size_of_pointee(field),
+                    });
+                    last_field_end = offset + size_of_pointee(field);
+                )*
+                let size = std::mem::size_of::<$ty>();
+                if size > last_field_end {
+                    fields.push(type_layout::Field::Padding {
+                        size: size - last_field_end,
+                    });
+                }
+                type_layout::TypeLayoutInfo {
+                    name: stringify!($ty).into(),
+                    size,
+                    alignment: std::mem::align_of::<$ty>(),
+                    fields,
+                }
+            }
+        }
+    };
+}
+#[cfg(test)]
+pub(crate) use impl_packed_type_layout;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_farms_all() {
+        let farms: Vec<Farms> = Farms::all().collect();
+        assert!(!farms.contains(&Farms::Unknown));
+        // discriminants are contiguous, so no farm is skipped
+        let known = (0_u64..=u8::MAX as u64)
+            .filter(|value| Farms::from(*value) != Farms::Unknown)
+            .count();
+        assert_eq!(farms.len(), known);
+        for (idx, farm) in farms.iter().enumerate() {
+            assert_eq!(*farm as usize, idx);
+            assert_eq!(Farms::from(idx as u64), *farm);
+            assert!(Farms::is_valid(*farm as u8));
+        }
+        assert!(Farms::is_valid(Farms::Unknown as u8));
+        assert!(!Farms::is_valid(farms.len() as u8));
+    }
+}
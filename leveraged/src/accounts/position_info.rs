@@ -1,7 +1,9 @@
+use super::{try_from_account_data, POSITION_INFO_ACCOUNT_SIZE};
 use anchor_lang::prelude::*;
 
 /// Position info account to track user position
 #[account(zero_copy)]
+#[repr(C, packed)]
 pub struct PositionInfo {
     pub nonce: u8,               // 1
     pub coin_deposit: u64,       // 9
@@ -32,3 +34,135 @@ pub struct PositionInfo {
     pub close_method: u8,        // 162
     pub buffer: [u8; 130],
 }
+
+impl PositionInfo {
+    /// offset of the old_account field, including the account discriminator
+    pub const OLD_ACCOUNT_OFFSET: usize = 168;
+    /// decodes a position info account, validating the account discriminator,
+    /// the account size, and the old_account flag
+    pub fn try_from_slice(data: &[u8]) -> std::result::Result<Self, ProgramError> {
+        if data.len() != POSITION_INFO_ACCOUNT_SIZE || data[Self::OLD_ACCOUNT_OFFSET] > 1 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        try_from_account_data(data, POSITION_INFO_ACCOUNT_SIZE)
+    }
+    /// returns true if the position info account is flagged as an old account
+    pub fn is_old_account(&self) -> bool {
+        self.old_account
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::{field_end_offsets, impl_packed_type_layout, to_account_data};
+
+    impl_packed_type_layout!(PositionInfo {
+        nonce,
+        coin_deposit,
+        pc_deposit,
+        withdraw_coin,
+        withdraw_pc,
+        open_time,
+        useless_field,
+        repay_coin_percent,
+        repay_pc_percent,
+        buffer_coin,
+        buffer_pc,
+        deposit_lp,
+        withdraw_lp,
+        open_coin_cost,
+        open_pc_cost,
+        withdraw_coin_cost,
+        withdraw_pc_cost,
+        coin_swap,
+        pc_swap,
+        coin_deposit_lp,
+        pc_deposit_lp,
+        coin_withdraw_lp,
+        pc_withdraw_lp,
+        settle_method,
+        withdraw_percent,
+        old_account,
+        close_method,
+        buffer,
+    });
+
+    #[test]
+    fn test_position_info_layout() {
+        // end offsets of each field, excluding the account discriminator
+        let expected = [
+            ("nonce", 1),
+            ("coin_deposit", 9),
+            ("pc_deposit", 17),
+            ("withdraw_coin", 25),
+            ("withdraw_pc", 33),
+            ("open_time", 37),
+            ("useless_field", 39),
+            ("repay_coin_percent", 41),
+            ("repay_pc_percent", 43),
+            ("buffer_coin", 51),
+            ("buffer_pc", 59),
+            ("deposit_lp", 67),
+            ("withdraw_lp", 75),
+            ("open_coin_cost", 83),
+            ("open_pc_cost", 91),
+            ("withdraw_coin_cost", 99),
+            ("withdraw_pc_cost", 107),
+            ("coin_swap", 115),
+            ("pc_swap", 123),
+            ("coin_deposit_lp", 131),
+            ("pc_deposit_lp", 139),
+            ("coin_withdraw_lp", 147),
+            ("pc_withdraw_lp", 155),
+            ("settle_method", 156),
+            ("withdraw_percent", 160),
+            ("old_account", 161),
+            ("close_method", 162),
+            ("buffer", 292),
+        ];
+        let offsets = field_end_offsets::<PositionInfo>();
+        assert_eq!(offsets.len(), expected.len());
+        for ((name, end), (want_name, want_end)) in offsets.iter().zip(expected) {
+            assert_eq!(name, want_name);
+            assert_eq!(*end, want_end, "{}", name);
+        }
+        assert_eq!(
+            8 + std::mem::size_of::<PositionInfo>(),
+            POSITION_INFO_ACCOUNT_SIZE
+        );
+        assert_eq!(PositionInfo::OLD_ACCOUNT_OFFSET, 8 + 161 - 1);
+    }
+
+    #[test]
+    fn test_position_info_try_from_slice() {
+        let mut position_info: PositionInfo = bytemuck::Zeroable::zeroed();
+        position_info.withdraw_percent = 50;
+        position_info.coin_swap = -10;
+        position_info.old_account = true;
+        position_info.close_method = 2;
+        let mut data = to_account_data(&position_info);
+        assert_eq!(data[PositionInfo::OLD_ACCOUNT_OFFSET], 1);
+
+        let decoded = PositionInfo::try_from_slice(&data).unwrap();
+        assert!(decoded.is_old_account());
+        assert_eq!({ decoded.withdraw_percent }, 50);
+        assert_eq!({ decoded.coin_swap }, -10);
+        assert_eq!(decoded.close_method, 2);
+
+        data[PositionInfo::OLD_ACCOUNT_OFFSET] = 0;
+        assert!(!PositionInfo::try_from_slice(&data)
+            .unwrap()
+            .is_old_account());
+        data[PositionInfo::OLD_ACCOUNT_OFFSET] = 2;
+        assert!(PositionInfo::try_from_slice(&data).is_err());
+        data[PositionInfo::OLD_ACCOUNT_OFFSET] = 0;
+
+        assert!(PositionInfo::try_from_slice(&data[..data.len() - 1]).is_err());
+        let mut longer = data.clone();
+        longer.push(0);
+        assert!(PositionInfo::try_from_slice(&longer).is_err());
+        data[0] ^= 1;
+        assert!(PositionInfo::try_from_slice(&data).is_err());
+    }
+}
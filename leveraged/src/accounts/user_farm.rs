@@ -1,8 +1,10 @@
-use super::Position;
+use super::{try_from_account_data, Position, USER_FARM_ACCOUNT_SIZE};
 use anchor_lang::prelude::*;
+use tulipv2_sdk_common::{math::decimal::Decimal, pricing::raydium::RaydiumPoolReserves};
 
 /// represents a collection of olibgations managed by this particular UserFarm account
 #[account(zero_copy)]
+#[repr(C, packed)]
 pub struct UserFarm {
     /// the authority which controlls the UserFarmManager
     pub authority: Pubkey,
@@ -32,6 +34,7 @@ pub struct UserFarm {
 ///
 /// 57 bytes in size
 #[zero_copy]
+#[repr(C, packed)]
 pub struct Obligation {
     // the address of the lending program obligation naccount
     pub obligation_account: Pubkey,
@@ -40,3 +43,139 @@ pub struct Obligation {
     pub deposited_lp_tokens: u64,
     pub position_state: Position,
 }
+
+impl UserFarm {
+    /// offset of the first obligation, including the account discriminator
+    pub const OBLIGATIONS_OFFSET: usize = 76;
+    /// offset of the position state within an obligation
+    pub const POSITION_STATE_OFFSET: usize = 56;
+    /// decodes a user farm account, validating the account discriminator, the
+    /// account size, and the position state of each obligation
+    pub fn try_from_slice(data: &[u8]) -> std::result::Result<Self, ProgramError> {
+        if data.len() != USER_FARM_ACCOUNT_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        for idx in 0..3 {
+            let offset = Self::OBLIGATIONS_OFFSET
+                + idx * std::mem::size_of::<Obligation>()
+                + Self::POSITION_STATE_OFFSET;
+            if !Position::is_valid(data[offset]) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        try_from_account_data(data, USER_FARM_ACCOUNT_SIZE)
+    }
+}
+
+impl Obligation {
+    /// returns the value of the lp tokens deposited by this obligation at the given lp price,
+    /// which is usually obtained from `RaydiumPoolReserves::fair_lp_price`
+    pub fn lp_value(
+        &self,
+        reserves: &RaydiumPoolReserves,
+        lp_price: Decimal,
+    ) -> std::result::Result<Decimal, ProgramError> {
+        reserves.lp_value(self.deposited_lp_tokens, lp_price)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::{field_end_offsets, impl_packed_type_layout, to_account_data};
+
+    impl_packed_type_layout!(UserFarm {
+        authority,
+        leveraged_farm,
+        user_farm_number,
+        number_of_obligations,
+        number_of_user_farms,
+        nonce,
+        obligations,
+    });
+    impl_packed_type_layout!(Obligation {
+        obligation_account,
+        coin_amount,
+        pc_amount,
+        deposited_lp_tokens,
+        position_state,
+    });
+
+    #[test]
+    fn test_user_farm_layout() {
+        assert_eq!(
+            field_end_offsets::<Obligation>(),
+            [
+                ("obligation_account".to_string(), 32),
+                ("coin_amount".to_string(), 40),
+                ("pc_amount".to_string(), 48),
+                ("deposited_lp_tokens".to_string(), 56),
+                ("position_state".to_string(), 57),
+            ]
+        );
+        assert_eq!(
+            field_end_offsets::<UserFarm>(),
+            [
+                ("authority".to_string(), 32),
+                ("leveraged_farm".to_string(), 64),
+                ("user_farm_number".to_string(), 65),
+                ("number_of_obligations".to_string(), 66),
+                ("number_of_user_farms".to_string(), 67),
+                ("nonce".to_string(), 68),
+                ("obligations".to_string(), 239),
+            ]
+        );
+        assert_eq!(8 + std::mem::size_of::<UserFarm>(), USER_FARM_ACCOUNT_SIZE);
+        assert_eq!(UserFarm::OBLIGATIONS_OFFSET, 8 + 68);
+    }
+
+    #[test]
+    fn test_user_farm_try_from_slice() {
+        let obligation = |position_state| Obligation {
+            obligation_account: Pubkey::new_unique(),
+            coin_amount: 1,
+            pc_amount: 2,
+            deposited_lp_tokens: 3,
+            position_state,
+        };
+        let user_farm = UserFarm {
+            authority: Pubkey::new_unique(),
+            leveraged_farm: Pubkey::new_unique(),
+            user_farm_number: 0,
+            number_of_obligations: 2,
+            number_of_user_farms: 1,
+            nonce: 255,
+            obligations: [
+                obligation(Position::Opened),
+                obligation(Position::RemovedLiquidityForLiquidation),
+                obligation(Position::Opening),
+            ],
+        };
+        let mut data = to_account_data(&user_farm);
+        assert_eq!(data.len(), USER_FARM_ACCOUNT_SIZE);
+
+        let decoded = UserFarm::try_from_slice(&data).unwrap();
+        assert_eq!({ decoded.authority }, { user_farm.authority });
+        assert_eq!(decoded.number_of_obligations, 2);
+        for (got, want) in decoded.obligations.iter().zip(user_farm.obligations) {
+            assert_eq!({ got.obligation_account }, { want.obligation_account });
+            assert_eq!({ got.deposited_lp_tokens }, 3);
+            assert_eq!(got.position_state, want.position_state);
+        }
+
+        let position_state = UserFarm::OBLIGATIONS_OFFSET
+            + std::mem::size_of::<Obligation>()
+            + UserFarm::POSITION_STATE_OFFSET;
+        assert_eq!(
+            data[position_state],
+            Position::RemovedLiquidityForLiquidation as u8
+        );
+        data[position_state] += 1;
+        assert!(UserFarm::try_from_slice(&data).is_err());
+        data[position_state] -= 1;
+
+        assert!(UserFarm::try_from_slice(&data[..data.len() - 1]).is_err());
+        data[7] ^= 1;
+        assert!(UserFarm::try_from_slice(&data).is_err());
+    }
+}
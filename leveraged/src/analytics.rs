@@ -0,0 +1,676 @@
+//! provides analytics for leveraged positions, such as the position's current leverage,
+//! equity, debt, the price range within which it is safe from liquidation, and its pnl.
+//!
+//! all values are denominated in the quote currency of the supplied prices (usually usd),
+//! and the lp tokens of a position are valued using `RaydiumPoolReserves::fair_lp_price`.
+//!
+//! the position info account records the cost of the tokens deposited when opening the
+//! position, and of the tokens returned by withdrawals, in native units of the pool's pc
+//! token. these are valued at the current pc price
+
+use crate::{
+    accounts::{position_info::PositionInfo, user_farm::Obligation},
+    portfolio::PositionSummary,
+};
+use anchor_lang::prelude::*;
+use tulipv2_sdk_common::{
+    lending::{obligation::Obligation as LendingObligation, reserve::Reserve},
+    math::{
+        common::{TryAdd, TryDiv, TryMul, TrySub},
+        decimal::Decimal,
+        signed::SignedDecimal,
+    },
+    pricing::{decimals_scaler, raydium::RaydiumPoolReserves},
+};
+
+/// a lending reserve which positions may borrow from, along with its address
+#[derive(Clone, Copy)]
+pub struct BorrowReserve<'a> {
+    pub address: Pubkey,
+    pub reserve: &'a Reserve,
+}
+
+/// market state used to value the positions of a single leveraged farm
+#[derive(Clone, Copy)]
+pub struct PositionMarket<'a> {
+    /// reserves of the amm the farm provides liquidity to
+    pub pool: RaydiumPoolReserves,
+    /// price of a single whole coin token
+    pub coin_price: Decimal,
+    /// price of a single whole pc token
+    pub pc_price: Decimal,
+    /// the reserve coin tokens are borrowed from
+    pub coin_reserve: BorrowReserve<'a>,
+    /// the reserve pc tokens are borrowed from
+    pub pc_reserve: BorrowReserve<'a>,
+}
+
+impl<'a> PositionMarket<'a> {
+    fn coin_value(&self, amount: Decimal) -> std::result::Result<Decimal, ProgramError> {
+        amount
+            .try_mul(self.coin_price)?
+            .try_div(decimals_scaler(self.pool.coin_decimals)?)
+    }
+    fn pc_value(&self, amount: Decimal) -> std::result::Result<Decimal, ProgramError> {
+        amount
+            .try_mul(self.pc_price)?
+            .try_div(decimals_scaler(self.pool.pc_decimals)?)
+    }
+}
+
+/// the coin prices between which a position can not be liquidated, assuming
+/// the pc price remains constant. a bound of None means the position can not
+/// be liquidated by the coin price moving in that direction
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LiquidationPriceRange {
+    pub lower: Option<Decimal>,
+    pub upper: Option<Decimal>,
+}
+
+/// analytics for a single leveraged position
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionAnalytics {
+    /// value of the lp tokens, and any coin and pc tokens held by the position
+    pub position_value: Decimal,
+    /// outstanding coin debt including accrued interest, in native units
+    pub coin_debt: Decimal,
+    /// outstanding pc debt including accrued interest, in native units
+    pub pc_debt: Decimal,
+    /// value of the outstanding coin and pc debt
+    pub debt_value: Decimal,
+    /// position value minus debt value, negative if the position is insolvent
+    pub equity: SignedDecimal,
+    /// position value divided by equity, None if the position has no equity
+    pub leverage: Option<Decimal>,
+    /// debt value divided by position value
+    pub debt_ratio: Decimal,
+    /// debt ratio at which the position can be liquidated
+    pub liquidation_threshold: Decimal,
+    /// None if the position is already liquidatable
+    pub liquidation_price_range: Option<LiquidationPriceRange>,
+    /// unix timestamp at which the position was opened
+    pub open_time: i64,
+    /// recorded cost of the coin and pc tokens deposited by the user when opening the position
+    pub deposit_value: Decimal,
+    /// recorded cost of the coin and pc tokens returned to the user by withdrawals
+    pub withdrawn_value: Decimal,
+    /// net coin tokens bought by the position's swaps, negative if sold, in native units
+    pub coin_swap: i64,
+    /// net pc tokens bought by the position's swaps, negative if sold, in native units
+    pub pc_swap: i64,
+    /// portion of withdrawn coin tokens used to repay debt
+    pub repay_coin_percent: Decimal,
+    /// portion of withdrawn pc tokens used to repay debt
+    pub repay_pc_percent: Decimal,
+    /// pnl of the withdrawn portion of the position
+    pub realized_pnl: SignedDecimal,
+    /// pnl of the portion of the position which remains open
+    pub unrealized_pnl: SignedDecimal,
+}
+
+impl PositionAnalytics {
+    /// computes the analytics of a position.
+    ///
+    /// pnl is measured against the recorded cost of the deposited tokens, with realized pnl
+    /// being the recorded cost of withdrawals minus the cost basis of the withdrawn lp tokens
+    pub fn new(
+        obligation: &Obligation,
+        position_info: &PositionInfo,
+        lending_obligation: &LendingObligation,
+        market: &PositionMarket,
+    ) -> std::result::Result<Self, ProgramError> {
+        let lp_price = market
+            .pool
+            .fair_lp_price(market.coin_price, market.pc_price)?;
+        let lp_value = market
+            .pool
+            .lp_value(obligation.deposited_lp_tokens, lp_price)?;
+        let idle_coin_value = market.coin_value(Decimal::from(obligation.coin_amount))?;
+        let idle_pc_value = market.pc_value(Decimal::from(obligation.pc_amount))?;
+        let position_value = lp_value.try_add(idle_coin_value)?.try_add(idle_pc_value)?;
+
+        let mut coin_debt = Decimal::zero();
+        let mut pc_debt = Decimal::zero();
+        let mut liquidation_threshold: Option<u8> = None;
+        for borrow in lending_obligation.borrows.iter() {
+            let (debt, reserve) = if borrow.borrow_reserve == market.coin_reserve.address {
+                (&mut coin_debt, market.coin_reserve.reserve)
+            } else if borrow.borrow_reserve == market.pc_reserve.address {
+                (&mut pc_debt, market.pc_reserve.reserve)
+            } else {
+                return Err(ProgramError::InvalidAccountData);
+            };
+            *debt = debt.try_add(accrued_debt(
+                borrow.borrowed_amount_wads,
+                borrow.cumulative_borrow_rate_wads,
+                reserve,
+            )?)?;
+            // the most conservative threshold of the borrowed reserves applies
+            let threshold = reserve.config.liquidation_threshold;
+            liquidation_threshold =
+                Some(liquidation_threshold.map_or(threshold, |t| t.min(threshold)));
+        }
+        let liquidation_threshold = liquidation_threshold
+            .map(Decimal::from_percent)
+            .unwrap_or_else(Decimal::one);
+        let coin_debt_value = market.coin_value(coin_debt)?;
+        let pc_debt_value = market.pc_value(pc_debt)?;
+        let debt_value = coin_debt_value.try_add(pc_debt_value)?;
+
+        let equity = SignedDecimal::difference(position_value, debt_value);
+        let leverage = if equity.is_negative() || equity.is_zero() {
+            None
+        } else {
+            Some(position_value.try_div(equity.abs())?)
+        };
+        let debt_ratio = if position_value == Decimal::zero() {
+            Decimal::zero()
+        } else {
+            debt_value.try_div(position_value)?
+        };
+        let liquidation_price_range = if debt_value != Decimal::zero()
+            && debt_value >= position_value.try_mul(liquidation_threshold)?
+        {
+            None
+        } else {
+            Some(liquidation_price_range(
+                lp_value,
+                idle_coin_value,
+                idle_pc_value,
+                coin_debt_value,
+                pc_debt_value,
+                liquidation_threshold,
+                market.coin_price,
+            )?)
+        };
+
+        let deposit_value = market.pc_value(
+            Decimal::from(position_info.open_coin_cost)
+                .try_add(Decimal::from(position_info.open_pc_cost))?,
+        )?;
+        let withdrawn_value = market.pc_value(
+            Decimal::from(position_info.withdraw_coin_cost)
+                .try_add(Decimal::from(position_info.withdraw_pc_cost))?,
+        )?;
+        // the portion of the deposits attributed to withdrawals is proportional
+        // to the portion of the position's lp tokens which have been withdrawn
+        let withdrawn_deposit_value = if position_info.deposit_lp == 0 {
+            Decimal::zero()
+        } else {
+            deposit_value
+                .try_mul(position_info.withdraw_lp.min(position_info.deposit_lp))?
+                .try_div(position_info.deposit_lp)?
+        };
+        let open_deposit_value = deposit_value.try_sub(withdrawn_deposit_value)?;
+        let realized_pnl = SignedDecimal::difference(withdrawn_value, withdrawn_deposit_value);
+        let unrealized_pnl = equity.try_sub(SignedDecimal::from(open_deposit_value))?;
+
+        Ok(Self {
+            position_value,
+            coin_debt,
+            pc_debt,
+            debt_value,
+            equity,
+            leverage,
+            debt_ratio,
+            liquidation_threshold,
+            liquidation_price_range,
+            open_time: i64::from(position_info.open_time),
+            deposit_value,
+            withdrawn_value,
+            coin_swap: position_info.coin_swap,
+            pc_swap: position_info.pc_swap,
+            repay_coin_percent: percent(position_info.repay_coin_percent)?,
+            repay_pc_percent: percent(position_info.repay_pc_percent)?,
+            realized_pnl,
+            unrealized_pnl,
+        })
+    }
+    /// computes the analytics of a position loaded by the portfolio reader, returning
+    /// None if the position's position info or lending obligation account does not exist
+    pub fn from_summary(
+        summary: &PositionSummary,
+        market: &PositionMarket,
+    ) -> std::result::Result<Option<Self>, ProgramError> {
+        let (position_info, lending_obligation) =
+            match (&summary.position_info, &summary.lending_obligation) {
+                (Some(position_info), Some(lending_obligation)) => {
+                    (position_info, lending_obligation)
+                }
+                _ => return Ok(None),
+            };
+        let obligation = Obligation {
+            obligation_account: summary.obligation_account,
+            coin_amount: summary.coin_amount,
+            pc_amount: summary.pc_amount,
+            deposited_lp_tokens: summary.deposited_lp_tokens,
+            position_state: summary.position_state,
+        };
+        Self::new(&obligation, position_info, lending_obligation, market).map(Some)
+    }
+    /// total pnl of the position, the sum of the realized and unrealized pnl
+    pub fn pnl(&self) -> std::result::Result<SignedDecimal, ProgramError> {
+        self.realized_pnl.try_add(self.unrealized_pnl)
+    }
+    /// returns true if the debt ratio has reached the liquidation threshold
+    pub fn is_liquidatable(&self) -> bool {
+        self.liquidation_price_range.is_none()
+    }
+}
+
+fn percent(value: u16) -> std::result::Result<Decimal, ProgramError> {
+    Decimal::from(u64::from(value)).try_div(100_u64)
+}
+
+/// returns the borrowed amount in native units, including interest accrued since
+/// the obligation was last refreshed
+fn accrued_debt(
+    borrowed_amount_wads: Decimal,
+    cumulative_borrow_rate_wads: Decimal,
+    reserve: &Reserve,
+) -> std::result::Result<Decimal, ProgramError> {
+    let reserve_rate = reserve.liquidity.cumulative_borrow_rate_wads;
+    if cumulative_borrow_rate_wads == Decimal::zero() || reserve_rate <= cumulative_borrow_rate_wads
+    {
+        return Ok(borrowed_amount_wads);
+    }
+    borrowed_amount_wads
+        .try_mul(reserve_rate)?
+        .try_div(cumulative_borrow_rate_wads)
+}
+
+/// when the coin price changes by a factor of `s^2`, constant product pools rebalance such
+/// that the value of the lp tokens changes by a factor of `s`. the position is liquidatable when
+///
+///   coin_debt * s^2 + pc_debt >= threshold * (lp * s + idle_coin * s^2 + idle_pc)
+///
+/// so the bounds of the healthy range are the positive roots of `a * s^2 - b * s + c`.
+///
+/// the position must currently be healthy, otherwise `InvalidArgument` may be returned
+fn liquidation_price_range(
+    lp_value: Decimal,
+    idle_coin_value: Decimal,
+    idle_pc_value: Decimal,
+    coin_debt_value: Decimal,
+    pc_debt_value: Decimal,
+    threshold: Decimal,
+    coin_price: Decimal,
+) -> std::result::Result<LiquidationPriceRange, ProgramError> {
+    let a = SignedDecimal::difference(coin_debt_value, idle_coin_value.try_mul(threshold)?);
+    let b = lp_value.try_mul(threshold)?;
+    let c = SignedDecimal::difference(pc_debt_value, idle_pc_value.try_mul(threshold)?);
+    let price = |s: Decimal| -> std::result::Result<Option<Decimal>, ProgramError> {
+        if s == Decimal::zero() {
+            return Ok(None);
+        }
+        Ok(Some(coin_price.try_mul(s)?.try_mul(s)?))
+    };
+    if a.is_zero() {
+        if c.is_negative() || c.is_zero() || b == Decimal::zero() {
+            return Ok(LiquidationPriceRange::default());
+        }
+        return Ok(LiquidationPriceRange {
+            lower: price(c.abs().try_div(b)?)?,
+            upper: None,
+        });
+    }
+    if a.is_negative() && (c.is_negative() || c.is_zero()) {
+        return Ok(LiquidationPriceRange::default());
+    }
+    // the position is currently healthy, so the discriminant is positive
+    let discriminant = SignedDecimal::from(b.try_mul(b)?).try_sub(a.try_mul(c)?.try_mul(4_u64)?)?;
+    if discriminant.is_negative() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let discriminant = discriminant.abs().try_sqrt()?;
+    let two_a = a.abs().try_mul(2_u64)?;
+    if a.is_negative() {
+        return Ok(LiquidationPriceRange {
+            lower: price(discriminant.try_sub(b)?.try_div(two_a)?)?,
+            upper: None,
+        });
+    }
+    let lower = SignedDecimal::difference(b, discriminant);
+    Ok(LiquidationPriceRange {
+        lower: if lower.is_negative() {
+            None
+        } else {
+            price(lower.abs().try_div(two_a)?)?
+        },
+        upper: price(b.try_add(discriminant)?.try_div(two_a)?)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::Position;
+    use tulipv2_sdk_common::lending::obligation::ObligationLiquidity;
+
+    fn reserve(cumulative_borrow_rate: Decimal) -> Reserve {
+        let mut reserve = Reserve::default();
+        reserve.config.liquidation_threshold = 80;
+        reserve.liquidity.cumulative_borrow_rate_wads = cumulative_borrow_rate;
+        reserve
+    }
+
+    /// a pool with equal coin and pc reserves, where both tokens and the lp
+    /// token have no decimals, so each lp token is worth 2
+    fn market<'a>(coin_reserve: &'a Reserve, pc_reserve: &'a Reserve) -> PositionMarket<'a> {
+        PositionMarket {
+            pool: RaydiumPoolReserves {
+                coin_amount: 1_000,
+                pc_amount: 1_000,
+                lp_supply: 1_000,
+                ..Default::default()
+            },
+            coin_price: Decimal::one(),
+            pc_price: Decimal::one(),
+            coin_reserve: BorrowReserve {
+                address: Pubkey::new_unique(),
+                reserve: coin_reserve,
+            },
+            pc_reserve: BorrowReserve {
+                address: Pubkey::new_unique(),
+                reserve: pc_reserve,
+            },
+        }
+    }
+
+    fn obligation(coin_amount: u64, deposited_lp_tokens: u64) -> Obligation {
+        Obligation {
+            obligation_account: Pubkey::new_unique(),
+            coin_amount,
+            pc_amount: 0,
+            deposited_lp_tokens,
+            position_state: Position::Opened,
+        }
+    }
+
+    /// a lending obligation borrowing `coin_debt` and `pc_debt`, with debt last
+    /// refreshed at a cumulative borrow rate of 1
+    fn lending_obligation(
+        market: &PositionMarket,
+        coin_debt: u64,
+        pc_debt: u64,
+    ) -> LendingObligation {
+        let mut obligation = LendingObligation::default();
+        for (reserve, debt) in [
+            (market.coin_reserve.address, coin_debt),
+            (market.pc_reserve.address, pc_debt),
+        ] {
+            if debt > 0 {
+                let mut liquidity = ObligationLiquidity::new(reserve, Decimal::one());
+                liquidity.borrowed_amount_wads = Decimal::from(debt);
+                obligation.borrows.push(liquidity);
+            }
+        }
+        obligation
+    }
+
+    fn position_info() -> PositionInfo {
+        let mut position_info = <PositionInfo as bytemuck::Zeroable>::zeroed();
+        position_info.coin_deposit = 50;
+        position_info.pc_deposit = 50;
+        position_info.open_coin_cost = 50;
+        position_info.open_pc_cost = 50;
+        position_info.deposit_lp = 100;
+        position_info
+    }
+
+    fn assert_approx(value: Option<Decimal>, want: f64) {
+        let value = value.unwrap().to_f64_lossy();
+        assert!((value - want).abs() < 1e-6, "{} != {}", value, want);
+    }
+
+    #[test]
+    fn test_healthy_position() {
+        let reserve = reserve(Decimal::one());
+        let market = market(&reserve, &reserve);
+        let mut position_info = position_info();
+        // half of the lp tokens were withdrawn for 60, against 50 of deposits
+        position_info.withdraw_lp = 50;
+        position_info.withdraw_coin = 30;
+        position_info.withdraw_pc = 30;
+        position_info.withdraw_coin_cost = 30;
+        position_info.withdraw_pc_cost = 30;
+        let analytics = PositionAnalytics::new(
+            &obligation(0, 100),
+            &position_info,
+            &lending_obligation(&market, 50, 50),
+            &market,
+        )
+        .unwrap();
+        assert_eq!(analytics.position_value, Decimal::from(200_u64));
+        assert_eq!(analytics.debt_value, Decimal::from(100_u64));
+        assert_eq!(
+            analytics.equity,
+            SignedDecimal::from(Decimal::from(100_u64))
+        );
+        assert_eq!(analytics.leverage, Some(Decimal::from(2_u64)));
+        assert_eq!(analytics.debt_ratio, Decimal::from_percent(50));
+        assert_eq!(analytics.liquidation_threshold, Decimal::from_percent(80));
+        assert_eq!(analytics.deposit_value, Decimal::from(100_u64));
+        assert_eq!(analytics.withdrawn_value, Decimal::from(60_u64));
+        assert_eq!(
+            analytics.realized_pnl,
+            SignedDecimal::from(Decimal::from(10_u64))
+        );
+        assert_eq!(
+            analytics.unrealized_pnl,
+            SignedDecimal::from(Decimal::from(50_u64))
+        );
+        assert_eq!(
+            analytics.pnl().unwrap(),
+            SignedDecimal::from(Decimal::from(60_u64))
+        );
+        assert!(!analytics.is_liquidatable());
+        // a > 0, the position is liquidatable if the coin price moves too far in either
+        // direction, with 50s^2 - 160s + 50 = 0 giving s = (160 -+ sqrt(15600)) / 100
+        let range = analytics.liquidation_price_range.unwrap();
+        let sqrt_discriminant = 15_600_f64.sqrt();
+        assert_approx(range.lower, ((160.0 - sqrt_discriminant) / 100.0).powi(2));
+        assert_approx(range.upper, ((160.0 + sqrt_discriminant) / 100.0).powi(2));
+    }
+
+    #[test]
+    fn test_recorded_costs() {
+        let reserve = reserve(Decimal::one());
+        let mut market = market(&reserve, &reserve);
+        // the coin price has doubled since the position was opened, which the
+        // recorded costs reflect rather than the current coin price
+        market.coin_price = Decimal::from(2_u64);
+        market.pool.pc_amount = 2_000;
+        let mut position_info = position_info();
+        position_info.open_time = 1_650_000_000;
+        position_info.coin_swap = -10;
+        position_info.pc_swap = 20;
+        position_info.repay_coin_percent = 25;
+        // a quarter of the lp tokens were withdrawn for 40 coin, recorded at 60 pc
+        position_info.withdraw_lp = 25;
+        position_info.withdraw_coin = 40;
+        position_info.withdraw_coin_cost = 60;
+        let analytics = PositionAnalytics::new(
+            &obligation(0, 100),
+            &position_info,
+            &lending_obligation(&market, 50, 50),
+            &market,
+        )
+        .unwrap();
+        // the recorded deposit cost differs from the 150 the deposits are worth now
+        assert_eq!(analytics.deposit_value, Decimal::from(100_u64));
+        assert_eq!(analytics.withdrawn_value, Decimal::from(60_u64));
+        assert_eq!(
+            analytics.realized_pnl,
+            SignedDecimal::from(Decimal::from(35_u64))
+        );
+        // 100 lp tokens worth 400, minus 150 of debt, minus the 75 cost of the open position
+        assert_eq!(analytics.position_value, Decimal::from(400_u64));
+        assert_eq!(
+            analytics.unrealized_pnl,
+            SignedDecimal::from(Decimal::from(175_u64))
+        );
+        assert_eq!(analytics.open_time, 1_650_000_000);
+        assert_eq!(analytics.coin_swap, -10);
+        assert_eq!(analytics.pc_swap, 20);
+        assert_eq!(analytics.repay_coin_percent, Decimal::from_percent(25));
+        assert_eq!(analytics.repay_pc_percent, Decimal::zero());
+    }
+
+    #[test]
+    fn test_liquidation_price_range_branches() {
+        let reserve = reserve(Decimal::one());
+        let market = market(&reserve, &reserve);
+        // a < 0 and c > 0, 100 idle coin and no coin debt, so the position can only be
+        // liquidated by the coin price falling. -80s^2 - 160s + 100 = 0 gives s = 0.5
+        let analytics = PositionAnalytics::new(
+            &obligation(100, 100),
+            &position_info(),
+            &lending_obligation(&market, 0, 100),
+            &market,
+        )
+        .unwrap();
+        assert_eq!(analytics.position_value, Decimal::from(300_u64));
+        let range = analytics.liquidation_price_range.unwrap();
+        assert_approx(range.lower, 0.25);
+        assert_eq!(range.upper, None);
+
+        // a == 0, the coin debt matches the threshold adjusted idle coin.
+        // -160s + 40 = 0 gives s = 0.25
+        let analytics = PositionAnalytics::new(
+            &obligation(100, 100),
+            &position_info(),
+            &lending_obligation(&market, 80, 40),
+            &market,
+        )
+        .unwrap();
+        let range = analytics.liquidation_price_range.unwrap();
+        assert_approx(range.lower, 0.0625);
+        assert_eq!(range.upper, None);
+
+        // a < 0 and c <= 0 can't be liquidated by the coin price moving
+        let range = liquidation_price_range(
+            Decimal::from(200_u64),
+            Decimal::from(100_u64),
+            Decimal::from(100_u64),
+            Decimal::zero(),
+            Decimal::zero(),
+            Decimal::from_percent(80),
+            Decimal::one(),
+        )
+        .unwrap();
+        assert_eq!(range, LiquidationPriceRange::default());
+
+        // an unhealthy position with no real roots is rejected, rather than
+        // taking the square root of the absolute value of the discriminant
+        assert_eq!(
+            liquidation_price_range(
+                Decimal::from(10_u64),
+                Decimal::zero(),
+                Decimal::zero(),
+                Decimal::from(100_u64),
+                Decimal::from(100_u64),
+                Decimal::from_percent(80),
+                Decimal::one(),
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_liquidatable_position() {
+        let reserve = reserve(Decimal::one());
+        let market = market(&reserve, &reserve);
+        let analytics = PositionAnalytics::new(
+            &obligation(0, 100),
+            &position_info(),
+            &lending_obligation(&market, 100, 100),
+            &market,
+        )
+        .unwrap();
+        assert_eq!(analytics.debt_value, Decimal::from(200_u64));
+        assert!(analytics.equity.is_zero());
+        assert_eq!(analytics.leverage, None);
+        assert_eq!(analytics.debt_ratio, Decimal::one());
+        assert!(analytics.is_liquidatable());
+        assert_eq!(analytics.liquidation_price_range, None);
+        assert_eq!(
+            analytics.unrealized_pnl,
+            SignedDecimal::new(Decimal::from(100_u64), true)
+        );
+    }
+
+    #[test]
+    fn test_position_without_debt() {
+        let reserve = reserve(Decimal::one());
+        let market = market(&reserve, &reserve);
+        let analytics = PositionAnalytics::new(
+            &obligation(0, 50),
+            &position_info(),
+            &LendingObligation::default(),
+            &market,
+        )
+        .unwrap();
+        assert_eq!(analytics.position_value, Decimal::from(100_u64));
+        assert_eq!(analytics.debt_value, Decimal::zero());
+        assert_eq!(analytics.leverage, Some(Decimal::one()));
+        assert_eq!(analytics.debt_ratio, Decimal::zero());
+        // without borrows the liquidation threshold defaults to 100%
+        assert_eq!(analytics.liquidation_threshold, Decimal::one());
+        assert!(!analytics.is_liquidatable());
+        assert_eq!(
+            analytics.liquidation_price_range,
+            Some(LiquidationPriceRange::default())
+        );
+    }
+
+    #[test]
+    fn test_debt_accrual() {
+        let rate = Decimal::from_percent(110);
+        let borrowed = Decimal::from(100_u64);
+        // interest accrues by the growth of the reserve's cumulative borrow rate
+        assert_eq!(
+            accrued_debt(borrowed, Decimal::one(), &reserve(rate)).unwrap(),
+            Decimal::from(110_u64)
+        );
+        // debt is unchanged if the reserve rate hasn't grown, or was never recorded
+        assert_eq!(
+            accrued_debt(borrowed, rate, &reserve(rate)).unwrap(),
+            borrowed
+        );
+        assert_eq!(
+            accrued_debt(borrowed, rate, &reserve(Decimal::one())).unwrap(),
+            borrowed
+        );
+        assert_eq!(
+            accrued_debt(borrowed, Decimal::zero(), &reserve(rate)).unwrap(),
+            borrowed
+        );
+
+        // the accrued coin debt is reflected by the position's analytics
+        let coin_reserve = reserve(rate);
+        let pc_reserve = reserve(Decimal::one());
+        let market = market(&coin_reserve, &pc_reserve);
+        let analytics = PositionAnalytics::new(
+            &obligation(0, 100),
+            &position_info(),
+            &lending_obligation(&market, 50, 50),
+            &market,
+        )
+        .unwrap();
+        assert_eq!(analytics.coin_debt, Decimal::from(55_u64));
+        assert_eq!(analytics.pc_debt, Decimal::from(50_u64));
+        assert_eq!(analytics.debt_value, Decimal::from(105_u64));
+
+        // borrows from a reserve other than the market's are rejected
+        let mut lending_obligation = lending_obligation(&market, 50, 0);
+        lending_obligation.borrows[0].borrow_reserve = Pubkey::new_unique();
+        assert!(PositionAnalytics::new(
+            &obligation(0, 100),
+            &position_info(),
+            &lending_obligation,
+            &market,
+        )
+        .is_err());
+    }
+}
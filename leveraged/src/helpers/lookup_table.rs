@@ -0,0 +1,464 @@
+//! provides deterministic address lookup table contents for leveraged farms, allowing
+//! the instructions used to open, top up and close positions to be compiled into v0
+//! messages using [tulipv2_sdk_common::packer::compile_v0_message]
+
+use super::verify::config_address_is_legacy;
+use crate::accounts::{aqua_farms::AquaFarms, Farms};
+use anchor_lang::prelude::*;
+use solana_program::{system_program, sysvar};
+use tulipv2_sdk_common::{
+    config::{
+        levfarm::LevFarmConfig, ORCA_AQUAFARM_PROGRAM, ORCA_SWAP_PROGRAM, RAYDIUM_LIQUIDITY_V4,
+    },
+    packer::lookup_table_addresses,
+};
+
+/// a leveraged farm configuration module compiled into the sdk
+struct FarmConfig {
+    farm: Farms,
+    config: LevFarmConfig,
+    /// the vault info account used by raydium vaults
+    vault_info_account: Option<Pubkey>,
+}
+
+/// pushes the configuration of a `tulipv2_sdk_common::config::levfarm` module when its
+/// feature is enabled. raydium modules may configure a legacy vault info account, which
+/// takes precedence over the current account when set
+macro_rules! push_farm_config {
+    ($configs:ident, $feature:tt, $module:ident, $farm:ident) => {
+        #[cfg(feature = $feature)]
+        $configs.push(FarmConfig {
+            farm: Farms::$farm,
+            config: tulipv2_sdk_common::config::levfarm::$module::get_lev_farm_config(),
+            vault_info_account: None,
+        });
+    };
+    ($configs:ident, $feature:tt, $module:ident, $farm:ident, vault_info) => {
+        #[cfg(feature = $feature)]
+        $configs.push(FarmConfig {
+            farm: Farms::$farm,
+            config: tulipv2_sdk_common::config::levfarm::$module::get_lev_farm_config(),
+            vault_info_account: Some(
+                tulipv2_sdk_common::config::levfarm::$module::vault_config::VAULT_INFO_ACCOUNT,
+            ),
+        });
+    };
+    ($configs:ident, $feature:tt, $module:ident, $farm:ident, legacy_vault_info) => {
+        #[cfg(feature = $feature)]
+        $configs.push(FarmConfig {
+            farm: Farms::$farm,
+            config: tulipv2_sdk_common::config::levfarm::$module::get_lev_farm_config(),
+            vault_info_account: {
+                use tulipv2_sdk_common::{config::levfarm::$module::vault_config, DEFAULT_KEY};
+                if vault_config::OLD_VAULT_INFO_ACCOUNT.eq(&DEFAULT_KEY) {
+                    Some(vault_config::VAULT_INFO_ACCOUNT)
+                } else {
+                    Some(vault_config::OLD_VAULT_INFO_ACCOUNT)
+                }
+            },
+        });
+    };
+}
+
+/// returns the leveraged farm configuration modules enabled by their features. the
+/// remaining orca modules don't yet contain a configuration, and are omitted.
+///
+/// some modules are placeholders holding another farm's configuration, so the configured
+/// leveraged farm account must be verified to be derived for the farm before it is used
+#[allow(unused_mut)]
+fn farm_configs() -> Vec<FarmConfig> {
+    let mut configs = vec![];
+    push_farm_config!(
+        configs,
+        "orca-orcausdc-levfarm",
+        orca_orcausdc,
+        OrcaUsdcVault
+    );
+    push_farm_config!(
+        configs,
+        "ray-rayusdc-levfarm",
+        ray_rayusdc,
+        RayUsdcVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-raysol-levfarm",
+        ray_raysol,
+        RaySolVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-rayusdt-levfarm",
+        ray_rayusdt,
+        RayUsdtVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-raysrm-levfarm",
+        ray_raysrm,
+        RaySrmVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-rayeth-levfarm",
+        ray_rayeth,
+        RayEthVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-likeusdc-levfarm",
+        ray_likeusdc,
+        LikeUsdcVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-atlasray-levfarm",
+        ray_atlasray,
+        AtlasRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-polisray-levfarm",
+        ray_polisray,
+        PolisRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-msolusdc-levfarm",
+        ray_msolusdc,
+        mSolUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-msolusdt-levfarm",
+        ray_msolusdt,
+        mSolUsdtRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-samoray-levfarm",
+        ray_samoray,
+        SamoRayRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-whethusdc-levfarm",
+        ray_whethusdc,
+        whEthUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-whethsol-levfarm",
+        ray_whethsol,
+        whEthSolRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-starsusdc-levfarm",
+        ray_starsusdc,
+        StarsUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-geneusdc-levfarm",
+        ray_geneusdc,
+        GeneUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-generay-levfarm",
+        ray_generay,
+        GeneRayRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-dflusdc-levfarm",
+        ray_dflusdc,
+        DflUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-solusdc-levfarm",
+        ray_solusdc,
+        SolUsdcRayVault,
+        vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-solusdcray-levfarm",
+        ray_solusdcray,
+        SolUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-solusdt-levfarm",
+        ray_solusdt,
+        SolUsdtRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-realusdc-levfarm",
+        ray_realusdc,
+        RealUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-mbsusdc-levfarm",
+        ray_mbsusdc,
+        MbsUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-stsolusdc-levfarm",
+        ray_stsolusdc,
+        stSolUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-stsolusdt-levfarm",
+        ray_stsolusdt,
+        stSolUsdtRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-btcstsol-levfarm",
+        ray_btcstsol,
+        BtcstSolRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-ethstsol-levfarm",
+        ray_ethstsol,
+        EthstSolRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-walephusdc-levfarm",
+        ray_walephusdc,
+        wAlephUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-slclusdc-levfarm",
+        ray_slclusdc,
+        SlclUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-prismusdc-levfarm",
+        ray_prismusdc,
+        PrismUsdcRayVault,
+        legacy_vault_info
+    );
+    push_farm_config!(
+        configs,
+        "ray-raywheth-levfarm",
+        ray_raywheth,
+        RaywhEthRayVault,
+        legacy_vault_info
+    );
+    configs
+}
+
+/// returns the addresses shared by every position of `farm` in a deterministic order
+/// suitable for storing in an address lookup table, or `None` if the farm has no
+/// leveraged farm configuration, or its configuration module is not enabled.
+///
+/// user specific accounts such as the user farm, obligations and token accounts are not
+/// included, as they differ between users
+pub fn farm_lookup_table_addresses(farm: Farms) -> Option<Vec<Pubkey>> {
+    farm_configs()
+        .into_iter()
+        .filter(|farm_config| farm_config.farm == farm)
+        .find(|farm_config| config_address_is_legacy(&farm_config.config, farm).is_some())
+        .map(|farm_config| farm_config_addresses(&farm_config))
+}
+
+/// returns the lookup table addresses of every farm with a leveraged farm configuration
+/// enabled by its feature
+pub fn farm_lookup_tables() -> Vec<(Farms, Vec<Pubkey>)> {
+    let mut tables: Vec<(Farms, Vec<Pubkey>)> = vec![];
+    for farm_config in farm_configs() {
+        if tables.iter().any(|(farm, _)| *farm == farm_config.farm)
+            || config_address_is_legacy(&farm_config.config, farm_config.farm).is_none()
+        {
+            continue;
+        }
+        tables.push((farm_config.farm, farm_config_addresses(&farm_config)));
+    }
+    tables
+}
+
+fn farm_config_addresses(farm_config: &FarmConfig) -> Vec<Pubkey> {
+    let FarmConfig {
+        farm,
+        config,
+        vault_info_account,
+    } = farm_config;
+    let farm = *farm;
+    let mut addresses = vec![
+        crate::ID,
+        spl_token::id(),
+        system_program::id(),
+        sysvar::clock::id(),
+        sysvar::rent::id(),
+    ];
+    addresses.extend(config.lookup_table_addresses());
+    addresses.extend(*vault_info_account);
+    if farm.is_aquafarm() {
+        let aqua_farm = AquaFarms::from(farm);
+        addresses.extend_from_slice(&[
+            ORCA_SWAP_PROGRAM,
+            ORCA_AQUAFARM_PROGRAM,
+            aqua_farm.swap_account(),
+            aqua_farm.swap_authority(),
+            aqua_farm.swap_token_a(),
+            aqua_farm.swap_token_b(),
+            aqua_farm.swap_pool_mint(),
+            aqua_farm.pool_fee_account(),
+            aqua_farm.account(),
+            aqua_farm.base_token_vault(),
+            aqua_farm.reward_token_vault(),
+            aqua_farm.global_farm(),
+            aqua_farm.global_base_token_vault(),
+            aqua_farm.global_reward_token_vault(),
+            aqua_farm.farm_token_mint(),
+            aqua_farm.convert_authority(),
+        ]);
+        if aqua_farm.is_double_dip() {
+            addresses.extend_from_slice(&[
+                aqua_farm.global_farm_dd(),
+                aqua_farm.global_base_token_vault_dd(),
+                aqua_farm.global_reward_token_vault_dd(),
+                aqua_farm.farm_token_mint_dd(),
+                aqua_farm.convert_authority_dd(),
+            ]);
+        }
+    } else {
+        addresses.push(RAYDIUM_LIQUIDITY_V4);
+    }
+    lookup_table_addresses(addresses)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::helpers::{
+        lev_farm_config, new_create_user_farm_ix, new_top_up_position_ix, vault_info_account,
+        verify,
+    };
+    use solana_program::sanitize::Sanitize;
+    use tulipv2_sdk_common::packer::{
+        compile_v0_message, transaction_size, AddressLookupTable, MAX_LOOKUP_TABLE_ADDRESSES,
+    };
+
+    #[test]
+    fn test_farm_lookup_tables() {
+        let tables = farm_lookup_tables();
+        let farms: Vec<Farms> = tables.iter().map(|(farm, _)| *farm).collect();
+        let unique: std::collections::BTreeSet<_> = farms.iter().map(|farm| *farm as u8).collect();
+        assert_eq!(unique.len(), farms.len());
+        // every enabled configuration of a farm has a table, including farms
+        // which aren't supported by `lev_farm_config`
+        let configs = farm_configs();
+        for farm_config in configs.iter() {
+            if verify::config_address_is_legacy(&farm_config.config, farm_config.farm).is_some() {
+                assert!(farms.contains(&farm_config.farm));
+            }
+        }
+        assert!(farms.contains(&Farms::SolUsdcRayVault));
+        assert!(lev_farm_config(Farms::SolUsdcRayVault).is_none());
+        // placeholder modules holding the RAY-USDC configuration are skipped
+        let placeholder = configs
+            .iter()
+            .find(|farm_config| farm_config.farm == Farms::AtlasRayVault)
+            .unwrap();
+        assert_eq!(
+            placeholder.config.account,
+            lev_farm_config(Farms::RayUsdcVault).unwrap().account
+        );
+        assert!(!farms.contains(&Farms::AtlasRayVault));
+        assert_eq!(farm_lookup_table_addresses(Farms::AtlasRayVault), None);
+
+        for (farm, addresses) in tables {
+            assert_eq!(Some(&addresses), farm_lookup_table_addresses(farm).as_ref());
+            assert!(addresses.len() <= MAX_LOOKUP_TABLE_ADDRESSES);
+            let unique: std::collections::BTreeSet<_> = addresses.iter().collect();
+            assert_eq!(unique.len(), addresses.len());
+            let farm_config = configs
+                .iter()
+                .find(|farm_config| {
+                    farm_config.farm == farm
+                        && verify::config_address_is_legacy(&farm_config.config, farm).is_some()
+                })
+                .unwrap();
+            assert!(addresses.contains(&farm_config.config.account));
+            assert!(addresses.contains(&farm_config.config.lending_market));
+            if let Some(vault_info_account) = farm_config.vault_info_account {
+                assert!(addresses.contains(&vault_info_account));
+            }
+            // the tables of farms supported by `lev_farm_config` are unchanged
+            if let Some(config) = lev_farm_config(farm) {
+                assert_eq!(config.account, farm_config.config.account);
+                assert_eq!(farm_config.vault_info_account, vault_info_account(farm));
+            }
+        }
+    }
+
+    #[test]
+    fn test_compile_with_farm_lookup_table() {
+        let authority = Pubkey::new_unique();
+        let farm = Farms::OrcaUsdcVault;
+        let table = AddressLookupTable {
+            address: Pubkey::new_unique(),
+            addresses: farm_lookup_table_addresses(farm).unwrap(),
+        };
+        let instructions = vec![
+            new_create_user_farm_ix(authority, farm).unwrap(),
+            new_top_up_position_ix(authority, farm, 0, 0, 1, 1).unwrap(),
+        ];
+        let legacy_size = transaction_size(&authority, &instructions, &[]).unwrap().0;
+        let (size, used) =
+            transaction_size(&authority, &instructions, std::slice::from_ref(&table)).unwrap();
+        assert!(size < legacy_size);
+        assert_eq!(used, vec![table.address]);
+
+        let message = compile_v0_message(
+            &authority,
+            &instructions,
+            std::slice::from_ref(&table),
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.instructions.len(), instructions.len());
+        message.sanitize().unwrap();
+    }
+}
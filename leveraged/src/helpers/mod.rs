@@ -2,16 +2,21 @@ use crate::{
     accounts::{
         derivations::{
             derive_user_farm_address, derive_user_farm_obligation_address,
-            derive_user_farm_obligation_vault_address,
+            derive_user_farm_obligation_vault_address, derive_user_position_info_address,
         },
         Farms,
     },
     instructions::{
         add_liquidity_stats, deposit_borrow_dual, deposit_raydium_vault, orca_add_liquidity_queue,
-        swap_tokens_raydium_stats, withdraw_orca_vault, withdraw_raydium_vault_close,
+        swap_tokens_orca_stats, swap_tokens_raydium_stats, top_up_position_stats,
+        withdraw_orca_vault, withdraw_orca_vault_dd_close, withdraw_raydium_vault_close,
     },
 };
 
+pub mod lookup_table;
+pub mod planner;
+pub mod verify;
+
 use super::*;
 use crate::instructions::{create_user_farm, create_user_farm_obligation};
 use anchor_lang::prelude::*;
@@ -128,6 +133,28 @@ pub fn new_swap_tokens_raydium_stats_ix(
         obligation_index,
     )
 }
+#[allow(clippy::too_many_arguments)]
+pub fn new_swap_tokens_orca_stats_ix<'info>(
+    accounts: swap_tokens_orca_stats::NewSerumSwap<'info>,
+    serum_fee_recipient: &AccountInfo<'info>,
+    lending_market_account: &AccountInfo<'info>,
+    lending_market_authority: &AccountInfo<'info>,
+    lending_program: &AccountInfo<'info>,
+    lp_mint: &AccountInfo<'info>,
+    position_info_account: &AccountInfo<'info>,
+    obligation_index: u8,
+) -> Option<Instruction> {
+    swap_tokens_orca_stats::swap_tokens_orca_stats(
+        accounts,
+        serum_fee_recipient,
+        lending_market_account,
+        lending_market_authority,
+        lending_program,
+        lp_mint,
+        position_info_account,
+        obligation_index,
+    )
+}
 pub fn new_add_liquidity_stats_ix(
     accounts: Box<add_liquidity_stats::AddLiquidity>,
     position_info_account: Pubkey,
@@ -262,6 +289,95 @@ pub fn new_withdraw_orca_vault_without_shares_ix<'info>(
 ) -> Option<Instruction> {
     withdraw_orca_vault::withdraw_orca_vault_without_shares(accounts, obligation_index)
 }
+#[allow(clippy::too_many_arguments)]
+pub fn new_withdraw_orca_vault_dd_close_ix<'info>(
+    accounts: withdraw_orca_vault_dd_close::WithdrawOrcaVaultDoubleDip<'info>,
+    lending_market_account: &AccountInfo<'info>,
+    user_farm_obligation: &AccountInfo<'info>,
+    lending_market_authority: &AccountInfo<'info>,
+    lending_program: &AccountInfo<'info>,
+    position_info_account: &AccountInfo<'info>,
+    obligation_index: u8,
+    withdraw_percent: u8,
+    close_method: u8,
+) -> Option<Instruction> {
+    withdraw_orca_vault_dd_close::withdraw_orca_vault_dd_close(
+        accounts,
+        lending_market_account,
+        user_farm_obligation,
+        lending_market_authority,
+        lending_program,
+        position_info_account,
+        obligation_index,
+        withdraw_percent,
+        close_method,
+    )
+}
+pub fn new_top_up_position_stats_ix(
+    accounts: Box<top_up_position_stats::TopUpPosition>,
+    position_info_account: Pubkey,
+    coin_amount: u64,
+    pc_amount: u64,
+    obligation_index: u8,
+) -> Option<Instruction> {
+    top_up_position_stats::top_up_position(
+        accounts,
+        position_info_account,
+        coin_amount,
+        pc_amount,
+        obligation_index,
+    )
+}
+/// returns an instruction that deposits `coin_amount` and `pc_amount` from the authority's
+/// associated token accounts into an open position, without borrowing any additional liquidity.
+/// the deposited tokens are then swapped, added as liquidity and deposited into the vault
+/// using the same instructions used when opening a position, see [planner::plan_top_up]
+pub fn new_top_up_position_ix(
+    authority: Pubkey,
+    farm: Farms,
+    user_farm_index: u64,
+    obligation_index: u8,
+    coin_amount: u64,
+    pc_amount: u64,
+) -> Option<Instruction> {
+    let levfarm_config = lev_farm_config(farm)?;
+    let user_farm = derive_user_farm_address(authority, crate::ID, user_farm_index, farm).0;
+    let user_farm_obligation =
+        derive_user_farm_obligation_address(authority, user_farm, crate::ID, obligation_index).0;
+    let position_info_account =
+        derive_user_position_info_address(user_farm, crate::ID, obligation_index).0;
+    new_top_up_position_stats_ix(
+        Box::new(top_up_position_stats::TopUpPosition {
+            authority,
+            user_farm,
+            leveraged_farm: levfarm_config.account,
+            user_farm_obligation,
+            coin_source_token_account: spl_associated_token_account::get_associated_token_address(
+                &authority,
+                &levfarm_config.base_token_mint,
+            ),
+            coin_destination_token_account: levfarm_config.base_token_account,
+            pc_source_token_account: spl_associated_token_account::get_associated_token_address(
+                &authority,
+                &levfarm_config.quote_token_mint,
+            ),
+            pc_destination_token_account: levfarm_config.quote_token_account,
+            coin_deposit_reserve_account: levfarm_config.base_reserve,
+            pc_deposit_reserve_account: levfarm_config.quote_reserve,
+            coin_reserve_liquidity_oracle: levfarm_config.coin_price_account,
+            pc_reserve_liquidity_oracle: levfarm_config.pc_price_account,
+            lending_market_account: levfarm_config.lending_market,
+            derived_lending_market_authority: levfarm_config.lending_market_authority,
+            clock: sysvar::clock::id(),
+            lending_program: LENDING_PROGRAM,
+            token_program: spl_token::id(),
+        }),
+        position_info_account,
+        coin_amount,
+        pc_amount,
+        obligation_index,
+    )
+}
 pub fn lev_farm_config(farm: Farms) -> Option<LevFarmConfig> {
     match farm {
         Farms::RayUsdcVault => {
@@ -274,7 +390,7 @@ pub fn lev_farm_config(farm: Farms) -> Option<LevFarmConfig> {
             #[cfg(not(feature = "ray-raysrm-levfarm"))]
             unimplemented!("requires raysrm-levfarm feature to be activated");
             #[cfg(feature = "ray-raysrm-levfarm")]
-            return Some(tulipv2_sdk_common::config::levfarm::ray_rayusdc::get_lev_farm_config());
+            return Some(tulipv2_sdk_common::config::levfarm::ray_raysrm::get_lev_farm_config());
         }
         Farms::RayUsdtVault => {
             #[cfg(not(feature = "ray-rayusdt-levfarm"))]
@@ -329,3 +445,17 @@ pub fn vault_info_account(farm: Farms) -> Option<Pubkey> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tulipv2_sdk_common::config::levfarm::{ray_raysrm, ray_rayusdc};
+
+    #[test]
+    fn test_lev_farm_config_ray_srm() {
+        // the ray-srm farm used to return the ray-usdc config
+        let config = lev_farm_config(Farms::RaySrmVault).unwrap();
+        assert_eq!(config.account, ray_raysrm::get_lev_farm_config().account);
+        assert_ne!(config.account, ray_rayusdc::get_lev_farm_config().account);
+    }
+}
@@ -0,0 +1,712 @@
+//! planners for modifying open positions, either by topping up the position with
+//! additional collateral, or by withdrawing a percentage of the position.
+//!
+//! the planners return the amounts expected to be moved by each step, along with the
+//! ordered steps themselves, which should be executed using the builders in [super]
+
+use crate::accounts::{Farms, Position};
+use anchor_lang::prelude::*;
+use tulipv2_sdk_common::{
+    math::error::MathError,
+    pricing::{
+        orca::OrcaPool,
+        raydium::{RaydiumAmmInfo, RaydiumPoolReserves},
+    },
+};
+
+/// the kind of vault a leveraged farm deposits its lp tokens into
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevFarmKind {
+    Raydium,
+    Orca,
+    /// an orca farm whose farm tokens are deposited into a double dip aquafarm
+    OrcaDoubleDip,
+}
+
+impl LevFarmKind {
+    /// returns the kind of the given farm. whether an orca farm is double dipped
+    /// is not known by the sdk, and must be supplied by the caller
+    pub fn new(farm: Farms, double_dip: bool) -> Self {
+        match (farm.is_aquafarm(), double_dip) {
+            (false, _) => LevFarmKind::Raydium,
+            (true, false) => LevFarmKind::Orca,
+            (true, true) => LevFarmKind::OrcaDoubleDip,
+        }
+    }
+    pub fn is_orca(&self) -> bool {
+        !matches!(self, LevFarmKind::Raydium)
+    }
+}
+
+/// the pool used to quote swaps made by a position. the pool's reserves are given
+/// by a `RaydiumPoolReserves`, which for orca pools holds the balances of token a
+/// as the coin amount and token b as the pc amount
+#[derive(Clone, Copy, Debug)]
+pub enum SwapPool<'a> {
+    Raydium(&'a RaydiumAmmInfo),
+    Orca(&'a OrcaPool),
+}
+
+impl<'a> SwapPool<'a> {
+    /// returns the amount received for swapping `amount_in`
+    pub fn quote(
+        &self,
+        reserves: &RaydiumPoolReserves,
+        amount_in: u64,
+        coin_to_pc: bool,
+    ) -> std::result::Result<u64, ProgramError> {
+        if amount_in == 0 {
+            return Ok(0);
+        }
+        match self {
+            SwapPool::Raydium(amm) => {
+                Ok(reserves.quote_swap(amm, amount_in, coin_to_pc)?.amount_out)
+            }
+            SwapPool::Orca(pool) => Ok(pool
+                .quote(
+                    amount_in,
+                    reserves.coin_amount,
+                    reserves.pc_amount,
+                    coin_to_pc,
+                )?
+                .amount_out),
+        }
+    }
+}
+
+/// a step taken when topping up a position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopUpStep {
+    /// deposit collateral into the position, see `new_top_up_position_ix`
+    DepositCollateral,
+    /// swap the collateral into the pool ratio, see `new_swap_tokens_raydium_stats_ix`
+    /// and `new_swap_tokens_orca_stats_ix`
+    Swap,
+    /// add liquidity, see `new_add_liquidity_stats_ix` and `new_orca_add_liquidity_queue_ix`
+    AddLiquidity,
+    /// deposit the lp tokens into the vault, see `new_deposit_raydium_vault_ix`
+    DepositVault,
+}
+
+impl TopUpStep {
+    /// the state the position is left in after the step
+    pub fn position_state(&self) -> Position {
+        match self {
+            TopUpStep::DepositCollateral => Position::TopUp,
+            TopUpStep::Swap => Position::TopUpSwapped,
+            TopUpStep::AddLiquidity => Position::TopUpAddedLiquidity,
+            TopUpStep::DepositVault => Position::Opened,
+        }
+    }
+}
+
+/// the expected result of topping up a position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TopUpPlan {
+    pub kind: LevFarmKind,
+    /// coin deposited by the user
+    pub coin_amount: u64,
+    /// pc deposited by the user
+    pub pc_amount: u64,
+    /// if true coin is swapped for pc, otherwise pc is swapped for coin
+    pub coin_to_pc: bool,
+    pub swap_amount_in: u64,
+    pub swap_amount_out: u64,
+    /// coin added as liquidity after the swap
+    pub coin_liquidity: u64,
+    /// pc added as liquidity after the swap
+    pub pc_liquidity: u64,
+    /// the expected amount of lp tokens added to the position
+    pub lp_amount: u64,
+    pub steps: Vec<TopUpStep>,
+}
+
+/// plans topping up an open position with `coin_amount` and `pc_amount`, without borrowing.
+///
+/// returns `ProgramError::InvalidArgument` for double dip orca farms, as the sdk has no
+/// instruction to deposit the topped up farm tokens into the double dip aquafarm
+pub fn plan_top_up(
+    kind: LevFarmKind,
+    pool: &RaydiumPoolReserves,
+    swap_pool: SwapPool,
+    coin_amount: u64,
+    pc_amount: u64,
+) -> std::result::Result<TopUpPlan, ProgramError> {
+    if kind == LevFarmKind::OrcaDoubleDip {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (coin_to_pc, swap_amount_in) =
+        pool.balanced_swap_by(coin_amount, pc_amount, |amount_in, coin_to_pc| {
+            swap_pool.quote(pool, amount_in, coin_to_pc)
+        })?;
+    let swap_amount_out = swap_pool.quote(pool, swap_amount_in, coin_to_pc)?;
+    let mut reserves = *pool;
+    let (coin_liquidity, pc_liquidity) = if coin_to_pc {
+        reserves.coin_amount = checked_add(reserves.coin_amount, swap_amount_in)?;
+        reserves.pc_amount = checked_sub(reserves.pc_amount, swap_amount_out)?;
+        (
+            coin_amount - swap_amount_in,
+            checked_add(pc_amount, swap_amount_out)?,
+        )
+    } else {
+        reserves.pc_amount = checked_add(reserves.pc_amount, swap_amount_in)?;
+        reserves.coin_amount = checked_sub(reserves.coin_amount, swap_amount_out)?;
+        (
+            checked_add(coin_amount, swap_amount_out)?,
+            pc_amount - swap_amount_in,
+        )
+    };
+    // liquidity is added at the pool ratio, so the lp tokens minted are
+    // limited by whichever side is smaller relative to the pool reserves
+    let lp_amount = std::cmp::min(
+        mul_div(coin_liquidity, reserves.lp_supply, reserves.coin_amount)?,
+        mul_div(pc_liquidity, reserves.lp_supply, reserves.pc_amount)?,
+    );
+    let mut steps = vec![
+        TopUpStep::DepositCollateral,
+        TopUpStep::Swap,
+        TopUpStep::AddLiquidity,
+    ];
+    // orca lp tokens are deposited into the vault when liquidity is added
+    if !kind.is_orca() {
+        steps.push(TopUpStep::DepositVault);
+    }
+    Ok(TopUpPlan {
+        kind,
+        coin_amount,
+        pc_amount,
+        coin_to_pc,
+        swap_amount_in,
+        swap_amount_out,
+        coin_liquidity,
+        pc_liquidity,
+        lp_amount,
+        steps,
+    })
+}
+
+/// a step taken when withdrawing a percentage of a position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WithdrawStep {
+    /// withdraw the farm tokens from the double dip aquafarm, see `new_withdraw_orca_vault_dd_close_ix`
+    WithdrawDoubleDip,
+    /// withdraw the lp tokens from the vault, see `new_withdraw_raydium_vault_ix`
+    /// and `new_withdraw_orca_vault_close_ix`
+    WithdrawVault,
+    RemoveLiquidity,
+    SwapForRepaying,
+    Repay,
+}
+
+impl WithdrawStep {
+    /// the state the position is left in after the step
+    pub fn position_state(&self) -> Position {
+        match self {
+            WithdrawStep::WithdrawDoubleDip => Position::WithdrawnOrcaDoubleDip,
+            WithdrawStep::WithdrawVault => Position::Withdrawing,
+            WithdrawStep::RemoveLiquidity => Position::RemovedLiquidity,
+            WithdrawStep::SwapForRepaying => Position::SwappedForRepaying,
+            WithdrawStep::Repay => Position::Repaid,
+        }
+    }
+}
+
+/// the expected result of withdrawing a percentage of a position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialWithdrawPlan {
+    pub kind: LevFarmKind,
+    pub withdraw_percent: u8,
+    /// lp tokens withdrawn from the vault
+    pub lp_amount: u64,
+    /// coin received when removing liquidity
+    pub coin_amount: u64,
+    /// pc received when removing liquidity
+    pub pc_amount: u64,
+    /// coin debt repaid, proportional to the percentage withdrawn
+    pub coin_repay: u64,
+    /// pc debt repaid, proportional to the percentage withdrawn
+    pub pc_repay: u64,
+    /// if true coin is swapped for pc, otherwise pc is swapped for coin
+    pub coin_to_pc: bool,
+    /// the amount swapped to cover any shortfall in repaying either debt, 0 if no swap is needed
+    pub swap_amount_in: u64,
+    pub swap_amount_out: u64,
+    /// coin returned to the user after repaying
+    pub coin_returned: u64,
+    /// pc returned to the user after repaying
+    pub pc_returned: u64,
+    pub steps: Vec<WithdrawStep>,
+}
+
+/// plans withdrawing `withdraw_percent` of a position holding `deposited_lp_tokens`, repaying the
+/// same percentage of `coin_debt` and `pc_debt`, which are the position's outstanding debts in
+/// native units. a withdraw percent of 100 closes the position.
+///
+/// returns `ProgramError::InsufficientFunds` if the withdrawn tokens can't cover the debt being repaid
+pub fn plan_partial_withdraw(
+    kind: LevFarmKind,
+    pool: &RaydiumPoolReserves,
+    swap_pool: SwapPool,
+    deposited_lp_tokens: u64,
+    coin_debt: u64,
+    pc_debt: u64,
+    withdraw_percent: u8,
+) -> std::result::Result<PartialWithdrawPlan, ProgramError> {
+    if withdraw_percent == 0 || withdraw_percent > 100 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let lp_amount = mul_div(deposited_lp_tokens, withdraw_percent as u64, 100)?;
+    let (coin_amount, pc_amount) = pool.underlying_amounts(lp_amount)?;
+    let coin_repay = mul_div_ceil(coin_debt, withdraw_percent as u64, 100)?;
+    let pc_repay = mul_div_ceil(pc_debt, withdraw_percent as u64, 100)?;
+
+    // at most one side can be short, as the surplus of the other side is swapped to cover it
+    let (coin_to_pc, shortfall, surplus) = if coin_amount < coin_repay {
+        (
+            false,
+            coin_repay - coin_amount,
+            pc_amount.checked_sub(pc_repay),
+        )
+    } else if pc_amount < pc_repay {
+        (
+            true,
+            pc_repay - pc_amount,
+            coin_amount.checked_sub(coin_repay),
+        )
+    } else {
+        (false, 0, Some(0))
+    };
+    let surplus = surplus.ok_or(ProgramError::InsufficientFunds)?;
+    let (swap_amount_in, swap_amount_out) = if shortfall == 0 {
+        (0, 0)
+    } else {
+        // the amount received increases monotonically with the amount swapped,
+        // so binary search for the smallest swap which covers the shortfall
+        if swap_pool.quote(pool, surplus, coin_to_pc)? < shortfall {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        let mut low = 1_u64;
+        let mut high = surplus;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if swap_pool.quote(pool, mid, coin_to_pc)? >= shortfall {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        (low, swap_pool.quote(pool, low, coin_to_pc)?)
+    };
+    let (coin_returned, pc_returned) = if swap_amount_in == 0 {
+        (coin_amount - coin_repay, pc_amount - pc_repay)
+    } else if coin_to_pc {
+        (
+            surplus - swap_amount_in,
+            checked_add(pc_amount, swap_amount_out)? - pc_repay,
+        )
+    } else {
+        (
+            checked_add(coin_amount, swap_amount_out)? - coin_repay,
+            surplus - swap_amount_in,
+        )
+    };
+
+    let mut steps = Vec::with_capacity(5);
+    if kind == LevFarmKind::OrcaDoubleDip {
+        steps.push(WithdrawStep::WithdrawDoubleDip);
+    }
+    steps.extend_from_slice(&[WithdrawStep::WithdrawVault, WithdrawStep::RemoveLiquidity]);
+    if swap_amount_in > 0 {
+        steps.push(WithdrawStep::SwapForRepaying);
+    }
+    steps.push(WithdrawStep::Repay);
+    Ok(PartialWithdrawPlan {
+        kind,
+        withdraw_percent,
+        lp_amount,
+        coin_amount,
+        pc_amount,
+        coin_repay,
+        pc_repay,
+        coin_to_pc,
+        swap_amount_in,
+        swap_amount_out,
+        coin_returned,
+        pc_returned,
+        steps,
+    })
+}
+
+fn checked_add(a: u64, b: u64) -> std::result::Result<u64, ProgramError> {
+    Ok(a.checked_add(b).ok_or(MathError::MathOverflow)?)
+}
+
+fn checked_sub(a: u64, b: u64) -> std::result::Result<u64, ProgramError> {
+    Ok(a.checked_sub(b).ok_or(MathError::MathOverflow)?)
+}
+
+fn mul_div(
+    amount: u64,
+    numerator: u64,
+    denominator: u64,
+) -> std::result::Result<u64, ProgramError> {
+    if denominator == 0 {
+        return Err(MathError::MathOverflow.into());
+    }
+    let value = amount as u128 * numerator as u128 / denominator as u128;
+    Ok(u64::try_from(value).map_err(|_| MathError::MathOverflow)?)
+}
+
+fn mul_div_ceil(
+    amount: u64,
+    numerator: u64,
+    denominator: u64,
+) -> std::result::Result<u64, ProgramError> {
+    if denominator == 0 {
+        return Err(MathError::MathOverflow.into());
+    }
+    let value = (amount as u128 * numerator as u128)
+        .checked_add(denominator as u128 - 1)
+        .ok_or(MathError::MathOverflow)?
+        / denominator as u128;
+    Ok(u64::try_from(value).map_err(|_| MathError::MathOverflow)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tulipv2_sdk_common::pricing::orca::OrcaFees;
+
+    fn pool() -> RaydiumPoolReserves {
+        // 1000 coin, 4000 pc and 2000 lp, all with 6 decimals
+        RaydiumPoolReserves {
+            coin_amount: 1_000_000_000,
+            pc_amount: 4_000_000_000,
+            lp_supply: 2_000_000_000,
+            coin_decimals: 6,
+            pc_decimals: 6,
+            lp_decimals: 6,
+        }
+    }
+
+    fn orca_pool() -> OrcaPool {
+        OrcaPool {
+            fees: OrcaFees {
+                trade_fee_numerator: 25,
+                trade_fee_denominator: 10_000,
+                owner_trade_fee_numerator: 5,
+                owner_trade_fee_denominator: 10_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_top_up() {
+        let pool = pool();
+        let amm = RaydiumAmmInfo::default();
+        // only coin, roughly half of it is swapped for pc
+        let plan = plan_top_up(
+            LevFarmKind::Raydium,
+            &pool,
+            SwapPool::Raydium(&amm),
+            10_000_000,
+            0,
+        )
+        .unwrap();
+        let swap = pool.balanced_swap(&amm, 10_000_000, 0).unwrap();
+        assert!(plan.coin_to_pc);
+        assert_eq!(plan.swap_amount_in, swap.quote.amount_in);
+        assert_eq!(plan.swap_amount_out, swap.quote.amount_out);
+        assert!(plan.swap_amount_in > 4_990_000 && plan.swap_amount_in < 5_010_000);
+        assert_eq!(plan.coin_liquidity, 10_000_000 - plan.swap_amount_in);
+        assert_eq!(plan.pc_liquidity, plan.swap_amount_out);
+        // lp tokens are minted against the reserves after the swap
+        let coin_reserve = pool.coin_amount + plan.swap_amount_in;
+        let pc_reserve = pool.pc_amount - plan.swap_amount_out;
+        assert_eq!(
+            plan.lp_amount,
+            std::cmp::min(
+                plan.coin_liquidity as u128 * pool.lp_supply as u128 / coin_reserve as u128,
+                plan.pc_liquidity as u128 * pool.lp_supply as u128 / pc_reserve as u128,
+            ) as u64
+        );
+        assert_eq!(
+            plan.steps,
+            vec![
+                TopUpStep::DepositCollateral,
+                TopUpStep::Swap,
+                TopUpStep::AddLiquidity,
+                TopUpStep::DepositVault,
+            ]
+        );
+        assert_eq!(
+            plan.steps.last().unwrap().position_state(),
+            Position::Opened
+        );
+
+        // mostly pc, so pc is swapped for coin
+        let plan = plan_top_up(
+            LevFarmKind::Raydium,
+            &pool,
+            SwapPool::Raydium(&amm),
+            1_000_000,
+            40_000_000,
+        )
+        .unwrap();
+        assert!(!plan.coin_to_pc);
+        assert!(plan.swap_amount_in > 0);
+        assert_eq!(plan.coin_liquidity, 1_000_000 + plan.swap_amount_out);
+        assert_eq!(plan.pc_liquidity, 40_000_000 - plan.swap_amount_in);
+    }
+
+    #[test]
+    fn test_plan_top_up_orca() {
+        let pool = pool();
+        let orca_pool = orca_pool();
+        // already at the pool ratio, so nothing is swapped
+        let plan = plan_top_up(
+            LevFarmKind::Orca,
+            &pool,
+            SwapPool::Orca(&orca_pool),
+            1_000_000,
+            4_000_000,
+        )
+        .unwrap();
+        assert_eq!(plan.swap_amount_in, 0);
+        assert_eq!(plan.swap_amount_out, 0);
+        assert_eq!(plan.coin_liquidity, 1_000_000);
+        assert_eq!(plan.pc_liquidity, 4_000_000);
+        assert_eq!(plan.lp_amount, 2_000_000);
+        // orca lp tokens are deposited into the vault when adding liquidity
+        assert_eq!(
+            plan.steps,
+            vec![
+                TopUpStep::DepositCollateral,
+                TopUpStep::Swap,
+                TopUpStep::AddLiquidity,
+            ]
+        );
+
+        // double dip top ups can't be re-deposited into the double dip aquafarm
+        assert_eq!(
+            plan_top_up(
+                LevFarmKind::OrcaDoubleDip,
+                &pool,
+                SwapPool::Orca(&orca_pool),
+                1_000_000,
+                4_000_000,
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        // an empty pool can't be quoted
+        assert!(plan_top_up(
+            LevFarmKind::Orca,
+            &RaydiumPoolReserves::default(),
+            SwapPool::Orca(&orca_pool),
+            1_000_000,
+            0,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_plan_partial_withdraw() {
+        let pool = pool();
+        let amm = RaydiumAmmInfo::default();
+        let swap_pool = SwapPool::Raydium(&amm);
+        for withdraw_percent in [0, 101, u8::MAX] {
+            assert_eq!(
+                plan_partial_withdraw(
+                    LevFarmKind::Raydium,
+                    &pool,
+                    swap_pool,
+                    10_000_000,
+                    0,
+                    0,
+                    withdraw_percent,
+                ),
+                Err(ProgramError::InvalidArgument)
+            );
+        }
+
+        // both debts are covered by the withdrawn tokens, so nothing is swapped
+        let plan = plan_partial_withdraw(
+            LevFarmKind::Raydium,
+            &pool,
+            swap_pool,
+            10_000_000,
+            2_000_000,
+            4_000_001,
+            50,
+        )
+        .unwrap();
+        assert_eq!(plan.lp_amount, 5_000_000);
+        assert_eq!(plan.coin_amount, 2_500_000);
+        assert_eq!(plan.pc_amount, 10_000_000);
+        assert_eq!(plan.coin_repay, 1_000_000);
+        // repayments round up, so the debt left is never more than the percentage kept
+        assert_eq!(plan.pc_repay, 2_000_001);
+        assert_eq!(plan.swap_amount_in, 0);
+        assert_eq!(plan.swap_amount_out, 0);
+        assert_eq!(plan.coin_returned, 1_500_000);
+        assert_eq!(plan.pc_returned, 7_999_999);
+        assert_eq!(
+            plan.steps,
+            vec![
+                WithdrawStep::WithdrawVault,
+                WithdrawStep::RemoveLiquidity,
+                WithdrawStep::Repay,
+            ]
+        );
+
+        // pc is short, so the coin surplus is swapped to cover it
+        let plan = plan_partial_withdraw(
+            LevFarmKind::Raydium,
+            &pool,
+            swap_pool,
+            10_000_000,
+            2_000_000,
+            24_000_000,
+            50,
+        )
+        .unwrap();
+        assert!(plan.coin_to_pc);
+        assert_eq!(plan.pc_repay, 12_000_000);
+        let shortfall = plan.pc_repay - plan.pc_amount;
+        // the smallest swap covering the shortfall is made
+        assert_eq!(
+            swap_pool.quote(&pool, plan.swap_amount_in, true).unwrap(),
+            plan.swap_amount_out
+        );
+        assert!(plan.swap_amount_out >= shortfall);
+        assert!(
+            swap_pool
+                .quote(&pool, plan.swap_amount_in - 1, true)
+                .unwrap()
+                < shortfall
+        );
+        assert_eq!(
+            plan.coin_returned,
+            plan.coin_amount - plan.coin_repay - plan.swap_amount_in
+        );
+        assert_eq!(
+            plan.pc_returned,
+            plan.pc_amount + plan.swap_amount_out - plan.pc_repay
+        );
+        assert_eq!(
+            plan.steps,
+            vec![
+                WithdrawStep::WithdrawVault,
+                WithdrawStep::RemoveLiquidity,
+                WithdrawStep::SwapForRepaying,
+                WithdrawStep::Repay,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_partial_withdraw_double_dip() {
+        let pool = pool();
+        let orca_pool = orca_pool();
+        let swap_pool = SwapPool::Orca(&orca_pool);
+        // withdrawing everything closes the position, with coin short and the pc surplus
+        // being swapped to cover it
+        let plan = plan_partial_withdraw(
+            LevFarmKind::OrcaDoubleDip,
+            &pool,
+            swap_pool,
+            10_000_000,
+            6_000_000,
+            4_000_000,
+            100,
+        )
+        .unwrap();
+        assert_eq!(plan.lp_amount, 10_000_000);
+        assert_eq!(plan.coin_amount, 5_000_000);
+        assert_eq!(plan.pc_amount, 20_000_000);
+        assert_eq!(plan.coin_repay, 6_000_000);
+        assert_eq!(plan.pc_repay, 4_000_000);
+        assert!(!plan.coin_to_pc);
+        assert!(plan.swap_amount_out >= 1_000_000);
+        assert!(
+            swap_pool
+                .quote(&pool, plan.swap_amount_in - 1, false)
+                .unwrap()
+                < 1_000_000
+        );
+        assert_eq!(
+            plan.coin_returned,
+            plan.coin_amount + plan.swap_amount_out - plan.coin_repay
+        );
+        assert_eq!(plan.pc_returned, 16_000_000 - plan.swap_amount_in);
+        assert_eq!(
+            plan.steps,
+            vec![
+                WithdrawStep::WithdrawDoubleDip,
+                WithdrawStep::WithdrawVault,
+                WithdrawStep::RemoveLiquidity,
+                WithdrawStep::SwapForRepaying,
+                WithdrawStep::Repay,
+            ]
+        );
+        assert_eq!(
+            plan.steps
+                .iter()
+                .map(WithdrawStep::position_state)
+                .collect::<Vec<_>>(),
+            vec![
+                Position::WithdrawnOrcaDoubleDip,
+                Position::Withdrawing,
+                Position::RemovedLiquidity,
+                Position::SwappedForRepaying,
+                Position::Repaid,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_partial_withdraw_insufficient_funds() {
+        let pool = pool();
+        let amm = RaydiumAmmInfo::default();
+        let swap_pool = SwapPool::Raydium(&amm);
+        // both sides are short, leaving no surplus to swap
+        assert_eq!(
+            plan_partial_withdraw(
+                LevFarmKind::Raydium,
+                &pool,
+                swap_pool,
+                10_000_000,
+                6_000_000,
+                21_000_000,
+                100,
+            ),
+            Err(ProgramError::InsufficientFunds)
+        );
+        // swapping the entire pc surplus doesn't cover the coin shortfall
+        assert_eq!(
+            plan_partial_withdraw(
+                LevFarmKind::Raydium,
+                &pool,
+                swap_pool,
+                10_000_000,
+                20_000_000,
+                0,
+                100,
+            ),
+            Err(ProgramError::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn test_mul_div_ceil() {
+        assert_eq!(mul_div_ceil(3, 50, 100).unwrap(), 2);
+        assert_eq!(mul_div_ceil(4, 50, 100).unwrap(), 2);
+        assert_eq!(mul_div_ceil(0, 50, 100).unwrap(), 0);
+        assert_eq!(mul_div_ceil(u64::MAX, 100, 100).unwrap(), u64::MAX);
+        assert!(mul_div_ceil(u64::MAX, 101, 100).is_err());
+        assert!(mul_div_ceil(1, 1, 0).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! provides helpers for verifying the statically configured addresses of a leveraged farm
+//! against the on-chain `LeveragedFarm` account, which is the source of truth used by the
+//! leveraged farm program when validating instruction accounts
+
+use crate::accounts::{
+    derivations::derive_leveraged_farm_address, leveraged_farm::LeveragedFarm, Farms,
+};
+use anchor_lang::prelude::*;
+use tulipv2_sdk_common::config::levfarm::LevFarmConfig;
+
+/// a field whose configured address differs from the address stored on-chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigMismatch {
+    /// the name of the mismatched `LevFarmConfig` field
+    pub field: &'static str,
+    /// the address stored in the `LevFarmConfig`
+    pub config: Pubkey,
+    /// the address stored in the `LeveragedFarm` account, or for the `account` field
+    /// the address derived from the config
+    pub on_chain: Pubkey,
+}
+
+/// compares `config` against the decoded leveraged farm account `farm`, returning every
+/// mismatched field. `lending_market_authority` is not stored by the leveraged farm
+/// account, and is not verified.
+///
+/// additionally `config.account` is verified to be derived from the config's vault program
+/// and serum market, using either the legacy or current seeds
+pub fn verify_lev_farm_config(farm: &LeveragedFarm, config: &LevFarmConfig) -> Vec<ConfigMismatch> {
+    let fields = [
+        (
+            "lending_program",
+            config.lending_program,
+            farm.lending_program,
+        ),
+        ("global", config.global, farm.global),
+        (
+            "vault_account",
+            config.vault_account,
+            farm.solfarm_vault_address,
+        ),
+        (
+            "base_token_account",
+            config.base_token_account,
+            farm.base_token_account,
+        ),
+        (
+            "quote_token_account",
+            config.quote_token_account,
+            farm.quote_token_account,
+        ),
+        ("serum_market", config.serum_market, farm.serum_market),
+        (
+            "serum_request_queue",
+            config.serum_request_queue,
+            farm.serum_request_queue,
+        ),
+        (
+            "serum_event_queue",
+            config.serum_event_queue,
+            farm.serum_event_queue,
+        ),
+        ("serum_bids", config.serum_bids, farm.serum_market_bids),
+        ("serum_asks", config.serum_asks, farm.serum_market_asks),
+        (
+            "serum_coin_vault",
+            config.serum_coin_vault,
+            farm.serum_coin_vault_account,
+        ),
+        (
+            "serum_pc_vault",
+            config.serum_pc_vault,
+            farm.serum_pc_vault_account,
+        ),
+        (
+            "serum_fee_receiver",
+            config.serum_fee_receiver,
+            farm.serum_fee_recipient,
+        ),
+        (
+            "serum_open_orders",
+            config.serum_open_orders,
+            farm.serum_open_orders_account,
+        ),
+        ("lp_mint", config.lp_mint, farm.raydium_lp_mint_address),
+        ("amm_id", config.amm_id, farm.raydium_amm_id),
+        (
+            "amm_open_orders",
+            config.amm_open_orders,
+            farm.raydium_amm_open_orders,
+        ),
+        (
+            "amm_quantities_or_target_orders",
+            config.amm_quantities_or_target_orders,
+            farm.raydium_amm_quantities_or_target_orders,
+        ),
+        (
+            "amm_coin_account",
+            config.amm_coin_account,
+            farm.raydium_coin_account,
+        ),
+        (
+            "amm_pc_account",
+            config.amm_pc_account,
+            farm.raydium_pc_account,
+        ),
+        (
+            "amm_temp_account",
+            config.amm_temp_account,
+            farm.raydium_pool_temp_token_account,
+        ),
+        (
+            "amm_withdraw_queue",
+            config.amm_withdraw_queue,
+            farm.raydium_pool_withdraw_queue,
+        ),
+        ("lending_market", config.lending_market, farm.lending_market),
+        (
+            "lp_price_account",
+            config.lp_price_account,
+            farm.lp_token_price_account,
+        ),
+        (
+            "coin_price_account",
+            config.coin_price_account,
+            farm.coin_price_account,
+        ),
+        (
+            "pc_price_account",
+            config.pc_price_account,
+            farm.pc_price_account,
+        ),
+        (
+            "coin_reserve_fee_receiver",
+            config.coin_reserve_fee_receiver,
+            farm.coin_reserve_liquidity_fee_receiver,
+        ),
+        (
+            "pc_reserve_fee_receiver",
+            config.pc_reserve_fee_receiver,
+            farm.pc_reserve_liquidity_fee_receiver,
+        ),
+        ("base_reserve", config.base_reserve, farm.base_reserve),
+        ("quote_reserve", config.quote_reserve, farm.quote_reserve),
+        (
+            "solfarm_vault_program",
+            config.solfarm_vault_program,
+            farm.solfarm_vault_program,
+        ),
+        ("base_token_mint", config.base_token_mint, farm.base_mint),
+        ("quote_token_mint", config.quote_token_mint, farm.quote_mint),
+    ];
+    let mut mismatches: Vec<ConfigMismatch> = fields
+        .into_iter()
+        .filter(|(_, config, on_chain)| config != on_chain)
+        .map(|(field, config, on_chain)| ConfigMismatch {
+            field,
+            config,
+            on_chain,
+        })
+        .collect();
+    if config_address_is_legacy(config, farm.farm_type).is_none() {
+        mismatches.push(ConfigMismatch {
+            field: "account",
+            config: config.account,
+            on_chain: derive_config_address(config, farm.farm_type, false),
+        });
+    }
+    mismatches
+}
+
+/// returns Some(true) if `config.account` is derived using the legacy seeds, Some(false)
+/// if it is derived using the current seeds, and None if the address is not derived
+/// from the config
+pub fn config_address_is_legacy(config: &LevFarmConfig, farm: Farms) -> Option<bool> {
+    [true, false]
+        .into_iter()
+        .find(|legacy| derive_config_address(config, farm, *legacy) == config.account)
+}
+
+fn derive_config_address(config: &LevFarmConfig, farm: Farms, legacy: bool) -> Pubkey {
+    derive_leveraged_farm_address(
+        config.solfarm_vault_program,
+        config.serum_market,
+        crate::ID,
+        farm,
+        legacy,
+    )
+    .0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use static_pubkey::static_pubkey;
+    use tulipv2_sdk_common::{
+        config::levfarm::{orca_orcausdc, ray_rayusdc},
+        fixtures::{AccountProvider, JsonFileAccountProvider},
+    };
+
+    fn load_farm(address: Pubkey) -> LeveragedFarm {
+        let account = JsonFileAccountProvider::fixtures()
+            .get_account(&address)
+            .unwrap();
+        LeveragedFarm::try_from_account(&address, account.data())
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_verify_lev_farm_config_fixtures() {
+        for (farm, config) in [
+            (
+                load_farm(static_pubkey!(
+                    "5o3EsLS1NTciKHXVsGNYqQQ8iBBK3dBfSPwCH7wsdtRT"
+                )),
+                orca_orcausdc::get_lev_farm_config(),
+            ),
+            (
+                load_farm(static_pubkey!(
+                    "84ayseJgpJavzfeESgRdyfMoDo2bs4J2YUBjMT4iTs66"
+                )),
+                ray_rayusdc::get_lev_farm_config(),
+            ),
+        ] {
+            assert_eq!(verify_lev_farm_config(&farm, &config), vec![]);
+            assert_eq!(
+                config_address_is_legacy(&config, farm.farm_type),
+                Some(false)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_lev_farm_config_mismatches() {
+        let farm = load_farm(static_pubkey!(
+            "5o3EsLS1NTciKHXVsGNYqQQ8iBBK3dBfSPwCH7wsdtRT"
+        ));
+        let mut config = orca_orcausdc::get_lev_farm_config();
+        let stale = Pubkey::new_unique();
+        config.amm_coin_account = stale;
+        config.lending_market = stale;
+        assert_eq!(
+            verify_lev_farm_config(&farm, &config),
+            vec![
+                ConfigMismatch {
+                    field: "amm_coin_account",
+                    config: stale,
+                    on_chain: farm.raydium_coin_account,
+                },
+                ConfigMismatch {
+                    field: "lending_market",
+                    config: stale,
+                    on_chain: farm.lending_market,
+                },
+            ]
+        );
+
+        let mut config = orca_orcausdc::get_lev_farm_config();
+        config.account = stale;
+        assert_eq!(config_address_is_legacy(&config, farm.farm_type), None);
+        assert_eq!(
+            verify_lev_farm_config(&farm, &config),
+            vec![ConfigMismatch {
+                field: "account",
+                config: stale,
+                on_chain: orca_orcausdc::farm_config::ACCOUNT,
+            }]
+        );
+    }
+}
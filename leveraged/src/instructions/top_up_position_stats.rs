@@ -35,12 +35,66 @@ pub struct DepositObligationCollateral<'info> {
     pub token_program: AccountInfo<'info>,
 }
 
+/// the accounts used by `top_up_position_stats`, for use when building
+/// the instruction off-chain
+pub struct TopUpPosition {
+    pub authority: Pubkey,
+    pub user_farm: Pubkey,
+    pub leveraged_farm: Pubkey,
+    pub user_farm_obligation: Pubkey,
+    pub coin_source_token_account: Pubkey,
+    pub coin_destination_token_account: Pubkey,
+    pub pc_source_token_account: Pubkey,
+    pub pc_destination_token_account: Pubkey,
+    pub coin_deposit_reserve_account: Pubkey,
+    pub pc_deposit_reserve_account: Pubkey,
+    pub coin_reserve_liquidity_oracle: Pubkey,
+    pub pc_reserve_liquidity_oracle: Pubkey,
+    pub lending_market_account: Pubkey,
+    pub derived_lending_market_authority: Pubkey,
+    pub clock: Pubkey,
+    pub lending_program: Pubkey,
+    pub token_program: Pubkey,
+}
+
 pub fn top_up_position_stats<'info>(
     accounts: DepositObligationCollateral<'info>,
     position_info_account: &AccountInfo<'info>,
     coin_amount: u64,
     pc_amount: u64,
     obligation_index: u8,
+) -> Option<Instruction> {
+    top_up_position_stats_ix(
+        accounts.to_account_metas(None),
+        position_info_account.key(),
+        coin_amount,
+        pc_amount,
+        obligation_index,
+    )
+}
+
+pub fn top_up_position(
+    accounts: Box<TopUpPosition>,
+    position_info_account: Pubkey,
+    coin_amount: u64,
+    pc_amount: u64,
+    obligation_index: u8,
+) -> Option<Instruction> {
+    top_up_position_stats_ix(
+        accounts.to_account_metas(None),
+        position_info_account,
+        coin_amount,
+        pc_amount,
+        obligation_index,
+    )
+}
+
+fn top_up_position_stats_ix(
+    mut accounts: Vec<AccountMeta>,
+    position_info_account: Pubkey,
+    coin_amount: u64,
+    pc_amount: u64,
+    obligation_index: u8,
 ) -> Option<Instruction> {
     let ix_sighash = GlobalSighashDB.get_deprecated("top_up_position_stats")?;
     let mut ix_data = Vec::with_capacity((8 * 3) + 1);
@@ -49,8 +103,7 @@ pub fn top_up_position_stats<'info>(
     ix_data.extend_from_slice(&AnchorSerialize::try_to_vec(&pc_amount).unwrap());
     ix_data.extend_from_slice(&AnchorSerialize::try_to_vec(&obligation_index).unwrap());
 
-    let mut accounts = accounts.to_account_metas(None);
-    accounts.push(AccountMeta::new(position_info_account.key(), false));
+    accounts.push(AccountMeta::new(position_info_account, false));
 
     Some(Instruction {
         program_id: crate::ID,
@@ -58,3 +111,117 @@ pub fn top_up_position_stats<'info>(
         data: ix_data,
     })
 }
+
+impl ToAccountMetas for TopUpPosition {
+    fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new(self.user_farm, false),
+            AccountMeta::new_readonly(self.leveraged_farm, false),
+            AccountMeta::new(self.user_farm_obligation, false),
+            AccountMeta::new(self.coin_source_token_account, false),
+            AccountMeta::new(self.coin_destination_token_account, false),
+            AccountMeta::new(self.pc_source_token_account, false),
+            AccountMeta::new(self.pc_destination_token_account, false),
+            AccountMeta::new(self.coin_deposit_reserve_account, false),
+            AccountMeta::new(self.pc_deposit_reserve_account, false),
+            AccountMeta::new_readonly(self.coin_reserve_liquidity_oracle, false),
+            AccountMeta::new_readonly(self.pc_reserve_liquidity_oracle, false),
+            AccountMeta::new_readonly(self.lending_market_account, false),
+            AccountMeta::new_readonly(self.derived_lending_market_authority, false),
+            AccountMeta::new_readonly(self.clock, false),
+            AccountMeta::new_readonly(self.lending_program, false),
+            AccountMeta::new_readonly(self.token_program, false),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::{
+        derivations::{
+            derive_user_farm_address, derive_user_farm_obligation_address,
+            derive_user_position_info_address,
+        },
+        Farms,
+    };
+    use crate::helpers::new_top_up_position_ix;
+
+    #[test]
+    fn test_top_up_position_account_order() {
+        let keys: Vec<Pubkey> = (0..17).map(|_| Pubkey::new_unique()).collect();
+        let top_up = TopUpPosition {
+            authority: keys[0],
+            user_farm: keys[1],
+            leveraged_farm: keys[2],
+            user_farm_obligation: keys[3],
+            coin_source_token_account: keys[4],
+            coin_destination_token_account: keys[5],
+            pc_source_token_account: keys[6],
+            pc_destination_token_account: keys[7],
+            coin_deposit_reserve_account: keys[8],
+            pc_deposit_reserve_account: keys[9],
+            coin_reserve_liquidity_oracle: keys[10],
+            pc_reserve_liquidity_oracle: keys[11],
+            lending_market_account: keys[12],
+            derived_lending_market_authority: keys[13],
+            clock: keys[14],
+            lending_program: keys[15],
+            token_program: keys[16],
+        };
+        // the client accounts generated by anchor for `DepositObligationCollateral`
+        // hold the on-chain account order and constraints
+        let deposit =
+            __client_accounts_deposit_obligation_collateral::DepositObligationCollateral {
+                authority: keys[0],
+                user_farm: keys[1],
+                leveraged_farm: keys[2],
+                user_farm_obligation: keys[3],
+                coin_source_token_account: keys[4],
+                coin_destination_token_account: keys[5],
+                pc_source_token_account: keys[6],
+                pc_destination_token_account: keys[7],
+                coin_deposit_reserve_account: keys[8],
+                pc_deposit_reserve_account: keys[9],
+                coin_reserve_liquidity_oracle: keys[10],
+                pc_reserve_liquidity_oracle: keys[11],
+                lending_market_account: keys[12],
+                derived_lending_market_authority: keys[13],
+                clock: keys[14],
+                lending_program: keys[15],
+                token_program: keys[16],
+            };
+        assert_eq!(
+            top_up.to_account_metas(None),
+            deposit.to_account_metas(None)
+        );
+
+        // the position info account is appended after the deposit accounts
+        let authority = Pubkey::new_unique();
+        let ix = new_top_up_position_ix(authority, Farms::OrcaUsdcVault, 0, 1, 2, 3).unwrap();
+        assert_eq!(ix.program_id, crate::ID);
+        assert_eq!(ix.accounts.len(), 18);
+        assert_eq!(ix.accounts[0], AccountMeta::new_readonly(authority, true));
+        assert_eq!(
+            ix.accounts[14],
+            AccountMeta::new_readonly(solana_program::sysvar::clock::id(), false)
+        );
+        let user_farm = derive_user_farm_address(authority, crate::ID, 0, Farms::OrcaUsdcVault).0;
+        assert_eq!(ix.accounts[1], AccountMeta::new(user_farm, false));
+        assert_eq!(
+            ix.accounts[3].pubkey,
+            derive_user_farm_obligation_address(authority, user_farm, crate::ID, 1).0
+        );
+        assert_eq!(
+            ix.accounts[17],
+            AccountMeta::new(
+                derive_user_position_info_address(user_farm, crate::ID, 1).0,
+                false
+            )
+        );
+        assert_eq!(&ix.data[8..16], &2_u64.to_le_bytes());
+        assert_eq!(&ix.data[16..24], &3_u64.to_le_bytes());
+        assert_eq!(ix.data[24], 1);
+    }
+}
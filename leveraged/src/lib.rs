@@ -1,6 +1,8 @@
 pub mod accounts;
+pub mod analytics;
 pub mod helpers;
 pub mod instructions;
+pub mod portfolio;
 
 use anchor_lang::{solana_program, solana_program::pubkey::Pubkey};
 
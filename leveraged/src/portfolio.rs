@@ -0,0 +1,328 @@
+//! provides helpers for enumerating all leveraged positions owned by a wallet.
+//!
+//! accounts are loaded through a caller supplied fetch callback, which returns the
+//! data of the account at the given address, or None if the account does not exist.
+//! this keeps the portfolio reader independent of any particular rpc client.
+
+use crate::accounts::{
+    derivations::{
+        derive_user_farm_address, derive_user_farm_obligation_address,
+        derive_user_position_info_address,
+    },
+    position_info::PositionInfo,
+    user_farm::{Obligation, UserFarm},
+    Farms, Position,
+};
+use anchor_lang::prelude::*;
+use solana_program::program_pack::Pack;
+use tulipv2_sdk_common::lending::obligation::Obligation as LendingObligation;
+
+/// the number of obligations a single user farm account can manage
+pub const MAX_OBLIGATIONS_PER_USER_FARM: u8 = 3;
+
+/// summarizes a single leveraged position, that is a single obligation slot of a user farm
+#[derive(Clone)]
+pub struct PositionSummary {
+    pub farm: Farms,
+    /// the address of the user farm account managing the position
+    pub user_farm: Pubkey,
+    /// the index used to derive the user farm address
+    pub user_farm_index: u64,
+    /// the index of the obligation within the user farm
+    pub obligation_index: u8,
+    /// the address of the lending obligation account backing the position
+    pub obligation_account: Pubkey,
+    pub coin_amount: u64,
+    pub pc_amount: u64,
+    pub deposited_lp_tokens: u64,
+    pub position_state: Position,
+    /// the position info account, or None if it has not been created
+    pub position_info: Option<PositionInfo>,
+    /// the lending obligation, or None if the account does not exist
+    pub lending_obligation: Option<LendingObligation>,
+}
+
+impl PositionSummary {
+    /// returns true if the position has been opened, and not yet closed or liquidated
+    pub fn is_open(&self) -> bool {
+        !matches!(
+            self.position_state,
+            Position::Closed | Position::Liquidated | Position::ExitingAndLiquidated
+        )
+    }
+}
+
+/// returns one position summary per obligation owned by `authority` across all farms.
+///
+/// accounts which do not exist, and obligations which have been closed are skipped, while
+/// accounts which exist but fail to decode result in an error
+pub fn load_portfolio(
+    authority: Pubkey,
+    mut fetch: impl FnMut(&Pubkey) -> Option<Vec<u8>>,
+) -> std::result::Result<Vec<PositionSummary>, ProgramError> {
+    let mut positions = Vec::new();
+    for farm in Farms::all() {
+        positions.extend(load_farm_positions(authority, farm, &mut fetch)?);
+    }
+    Ok(positions)
+}
+
+/// returns one position summary per obligation owned by `authority` within `farm`,
+/// skipping closed obligations.
+///
+/// the number of user farms is only tracked by the first user farm, so if it does
+/// not exist the authority has no positions within the farm
+pub fn load_farm_positions(
+    authority: Pubkey,
+    farm: Farms,
+    mut fetch: impl FnMut(&Pubkey) -> Option<Vec<u8>>,
+) -> std::result::Result<Vec<PositionSummary>, ProgramError> {
+    let mut positions = Vec::new();
+    let first_user_farm = derive_user_farm_address(authority, crate::ID, 0, farm).0;
+    let number_of_user_farms = match fetch(&first_user_farm) {
+        Some(data) => UserFarm::try_from_slice(&data)?.number_of_user_farms.max(1),
+        None => return Ok(positions),
+    };
+    for user_farm_index in 0..number_of_user_farms as u64 {
+        let user_farm_address =
+            derive_user_farm_address(authority, crate::ID, user_farm_index, farm).0;
+        let user_farm = match fetch(&user_farm_address) {
+            Some(data) => UserFarm::try_from_slice(&data)?,
+            None => continue,
+        };
+        let number_of_obligations = user_farm
+            .number_of_obligations
+            .min(MAX_OBLIGATIONS_PER_USER_FARM);
+        for obligation_index in 0..number_of_obligations {
+            let obligation: Obligation = user_farm.obligations[obligation_index as usize];
+            if obligation.position_state == Position::Closed {
+                continue;
+            }
+            let obligation_account = derive_user_farm_obligation_address(
+                authority,
+                user_farm_address,
+                crate::ID,
+                obligation_index,
+            )
+            .0;
+            let position_info_account =
+                derive_user_position_info_address(user_farm_address, crate::ID, obligation_index).0;
+            let position_info = fetch(&position_info_account)
+                .map(|data| PositionInfo::try_from_slice(&data))
+                .transpose()?;
+            let lending_obligation = fetch(&obligation_account)
+                .map(|data| decode_lending_obligation(&data))
+                .transpose()?;
+            positions.push(PositionSummary {
+                farm,
+                user_farm: user_farm_address,
+                user_farm_index,
+                obligation_index,
+                obligation_account,
+                coin_amount: obligation.coin_amount,
+                pc_amount: obligation.pc_amount,
+                deposited_lp_tokens: obligation.deposited_lp_tokens,
+                position_state: obligation.position_state,
+                position_info,
+                lending_obligation,
+            });
+        }
+    }
+    Ok(positions)
+}
+
+/// decodes a lending obligation, ignoring any trailing data
+fn decode_lending_obligation(data: &[u8]) -> std::result::Result<LendingObligation, ProgramError> {
+    if data.len() < LendingObligation::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    LendingObligation::unpack(&data[0..LendingObligation::LEN])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::to_account_data;
+    use std::collections::HashMap;
+    use tulipv2_sdk_common::lending::obligation::InitObligationParams;
+
+    /// an in-memory set of accounts for a single authority
+    struct Accounts {
+        authority: Pubkey,
+        accounts: HashMap<Pubkey, Vec<u8>>,
+    }
+
+    impl Accounts {
+        fn new() -> Self {
+            Self {
+                authority: Pubkey::new_unique(),
+                accounts: HashMap::new(),
+            }
+        }
+        fn user_farm_address(&self, farm: Farms, index: u64) -> Pubkey {
+            derive_user_farm_address(self.authority, crate::ID, index, farm).0
+        }
+        /// stores a user farm with an obligation for each of `states`
+        fn add_user_farm(
+            &mut self,
+            farm: Farms,
+            index: u64,
+            number_of_user_farms: u8,
+            states: &[Position],
+        ) -> Pubkey {
+            let address = self.user_farm_address(farm, index);
+            let mut user_farm = <UserFarm as bytemuck::Zeroable>::zeroed();
+            user_farm.authority = self.authority;
+            user_farm.user_farm_number = index as u8;
+            user_farm.number_of_user_farms = number_of_user_farms;
+            user_farm.number_of_obligations = states.len() as u8;
+            for (idx, state) in states.iter().enumerate() {
+                user_farm.obligations[idx].coin_amount = idx as u64 + 1;
+                user_farm.obligations[idx].deposited_lp_tokens = 100 * (idx as u64 + 1);
+                user_farm.obligations[idx].position_state = *state;
+            }
+            self.accounts.insert(address, to_account_data(&user_farm));
+            address
+        }
+        fn add_position_info(&mut self, user_farm: Pubkey, obligation_index: u8) {
+            let mut position_info = <PositionInfo as bytemuck::Zeroable>::zeroed();
+            position_info.deposit_lp = 42;
+            self.accounts.insert(
+                derive_user_position_info_address(user_farm, crate::ID, obligation_index).0,
+                to_account_data(&position_info),
+            );
+        }
+        fn add_lending_obligation(&mut self, user_farm: Pubkey, obligation_index: u8) {
+            let obligation = LendingObligation::new(InitObligationParams {
+                current_slot: 1,
+                lending_market: Pubkey::new_unique(),
+                owner: self.authority,
+                deposits: vec![],
+                borrows: vec![],
+                lp_decimals: 6,
+                coin_decimals: 6,
+                pc_decimals: 6,
+            });
+            let mut data = vec![0_u8; LendingObligation::LEN];
+            obligation.pack_into_slice(&mut data);
+            self.accounts.insert(
+                derive_user_farm_obligation_address(
+                    self.authority,
+                    user_farm,
+                    crate::ID,
+                    obligation_index,
+                )
+                .0,
+                data,
+            );
+        }
+        fn fetch(&self) -> impl FnMut(&Pubkey) -> Option<Vec<u8>> + '_ {
+            |address| self.accounts.get(address).cloned()
+        }
+    }
+
+    #[test]
+    fn test_load_farm_positions_missing_first_user_farm() {
+        let mut accounts = Accounts::new();
+        // the second user farm is never read without the first
+        accounts.add_user_farm(Farms::RayUsdcVault, 1, 0, &[Position::Opened]);
+        let positions =
+            load_farm_positions(accounts.authority, Farms::RayUsdcVault, accounts.fetch()).unwrap();
+        assert!(positions.is_empty());
+        assert!(load_portfolio(accounts.authority, accounts.fetch())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_load_farm_positions_multiple_user_farms() {
+        let mut accounts = Accounts::new();
+        let farm = Farms::OrcaUsdcVault;
+        let first = accounts.add_user_farm(farm, 0, 3, &[Position::Opened, Position::Closed]);
+        // the second user farm was never created, and is skipped
+        let third = accounts.add_user_farm(
+            farm,
+            2,
+            0,
+            &[
+                Position::Closed,
+                Position::Withdrawing,
+                Position::Liquidated,
+            ],
+        );
+        accounts.add_position_info(first, 0);
+        accounts.add_lending_obligation(first, 0);
+
+        let positions = load_farm_positions(accounts.authority, farm, accounts.fetch()).unwrap();
+        let slots: Vec<(Pubkey, u64, u8, Position)> = positions
+            .iter()
+            .map(|position| {
+                (
+                    position.user_farm,
+                    position.user_farm_index,
+                    position.obligation_index,
+                    position.position_state,
+                )
+            })
+            .collect();
+        // closed obligations are skipped
+        assert_eq!(
+            slots,
+            vec![
+                (first, 0, 0, Position::Opened),
+                (third, 2, 1, Position::Withdrawing),
+                (third, 2, 2, Position::Liquidated),
+            ]
+        );
+        assert!(positions[0].is_open());
+        assert!(positions[1].is_open());
+        assert!(!positions[2].is_open());
+        assert_eq!(positions[1].coin_amount, 2);
+        assert_eq!(positions[2].deposited_lp_tokens, 300);
+        assert_eq!(
+            positions[0].obligation_account,
+            derive_user_farm_obligation_address(accounts.authority, first, crate::ID, 0).0
+        );
+        // only the first position has a position info and lending obligation account
+        assert_eq!(
+            { positions[0].position_info.as_ref().unwrap().deposit_lp },
+            42
+        );
+        assert_eq!(
+            positions[0].lending_obligation.as_ref().unwrap().owner,
+            accounts.authority
+        );
+        assert!(positions[1..]
+            .iter()
+            .all(|position| position.position_info.is_none()
+                && position.lending_obligation.is_none()));
+
+        // the portfolio includes positions of every farm
+        accounts.add_user_farm(Farms::RayUsdcVault, 0, 1, &[Position::Opened]);
+        let portfolio = load_portfolio(accounts.authority, accounts.fetch()).unwrap();
+        assert_eq!(portfolio.len(), 4);
+        assert_eq!(
+            portfolio
+                .iter()
+                .filter(|position| position.farm == Farms::RayUsdcVault)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_load_farm_positions_invalid_accounts() {
+        let mut accounts = Accounts::new();
+        let farm = Farms::RayUsdcVault;
+        let user_farm = accounts.add_user_farm(farm, 0, 1, &[Position::Opened]);
+        accounts.accounts.insert(
+            derive_user_position_info_address(user_farm, crate::ID, 0).0,
+            vec![0_u8; 10],
+        );
+        assert!(load_farm_positions(accounts.authority, farm, accounts.fetch()).is_err());
+        accounts
+            .accounts
+            .insert(accounts.user_farm_address(farm, 0), vec![1_u8; 10]);
+        assert!(load_farm_positions(accounts.authority, farm, accounts.fetch()).is_err());
+    }
+}
@@ -0,0 +1,22 @@
+#! /bin/bash
+
+# records all accounts listed in fixtures/accounts.txt into fixtures/accounts, which are used
+# by the offline tests. to add a fixture, append the account address to fixtures/accounts.txt
+
+OUTPUT_DIR="fixtures/accounts"
+URL="$1"
+FORCE="$2"
+while IFS= read -r account; do
+
+    if [[ -e "$OUTPUT_DIR/$account.json" && "$FORCE" != "--force" ]]; then
+        echo "account $account already recorded, please run with --force flag to overwrite"
+        continue
+    fi
+
+    solana \
+        --url "$URL" \
+        account "$account" \
+        --output json \
+        --output-file "$OUTPUT_DIR/$account.json"
+
+done < fixtures/accounts.txt
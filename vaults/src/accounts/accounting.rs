@@ -0,0 +1,150 @@
+//! per-user accounting of deposit tracking accounts, reporting the cost basis, profit
+//! and pending rewards of a user's position within a vault.
+//!
+//! vault rewards are compounded into the vault's exchange rate, so the value of a position
+//! is derived from the vault's `total_shares` and `total_deposited_tokens`. pending rewards
+//! are instead derived from the tracking account's reward checkpoint, `last_pending_reward`
+//! and `reward_per_share_paid`
+use super::tracking::DepositTrackingV1;
+use anchor_lang::solana_program::program_error::ProgramError;
+use tulipv2_sdk_common::math::{common::WAD, error::MathError, uint::U256};
+use tulipv2_sdk_common::traits::vault::TokenizedShares;
+
+/// the precision that reward per share values are scaled by
+pub const REWARD_PER_SHARE_PRECISION: u128 = WAD as u128;
+
+/// a snapshot of a user's position in a vault, with all amounts denominated in
+/// the vault's underlying asset unless otherwise noted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrackingStatement {
+    /// amount of shares held by the tracking account
+    pub shares: u64,
+    /// the underlying deposited for the shares currently held
+    pub cost_basis: u64,
+    /// the underlying the shares would redeem for at the vault's current exchange rate
+    pub current_value: u64,
+    /// `current_value` minus `cost_basis`
+    pub unrealized_profit: i128,
+    /// the profit realized by previous withdrawals, ie: the underlying withdrawn
+    /// minus the cost basis of the withdrawn shares
+    pub realized_profit: i128,
+    /// rewards owed to the tracking account, ie: `last_pending_reward` plus the rewards
+    /// accrued by the shares since `reward_per_share_paid`
+    pub pending_rewards: u64,
+}
+
+impl TrackingStatement {
+    /// returns the sum of realized and unrealized profit
+    pub fn total_profit(&self) -> i128 {
+        self.realized_profit + self.unrealized_profit
+    }
+}
+
+impl DepositTrackingV1 {
+    /// returns the rewards owed to the tracking account, where `reward_per_share` is the
+    /// vault's current reward per share, scaled by `REWARD_PER_SHARE_PRECISION`
+    pub fn pending_rewards(&self, reward_per_share: u128) -> Result<u64, ProgramError> {
+        let net_reward_per_share = reward_per_share
+            .checked_sub(self.reward_per_share_paid)
+            .ok_or(MathError::MathOverflow)?;
+        let accrued = U256::from(self.shares)
+            .checked_mul(U256::from(net_reward_per_share))
+            .ok_or(MathError::MathOverflow)?
+            / U256::from(REWARD_PER_SHARE_PRECISION);
+        let accrued = u64::try_from(accrued).map_err(|_| MathError::MathOverflow)?;
+        Ok(self
+            .last_pending_reward
+            .checked_add(accrued)
+            .ok_or(MathError::MathOverflow)?)
+    }
+    /// returns the underlying that the tracking account's shares redeem for, using
+    /// the same rounding as the vault program does during withdrawals
+    pub fn current_value(&self, vault: &impl TokenizedShares) -> u64 {
+        if self.shares == 0 || vault.total_shares() == 0 {
+            return 0;
+        }
+        vault.underlying_to_redeem(self.shares)
+    }
+    /// returns an accounting statement of the tracking account, valued against `vault`,
+    /// with pending rewards calculated at `reward_per_share`
+    pub fn statement(
+        &self,
+        vault: &impl TokenizedShares,
+        reward_per_share: u128,
+    ) -> Result<TrackingStatement, ProgramError> {
+        let current_value = self.current_value(vault);
+        // the cost basis of withdrawn shares is whatever principal is no longer deposited
+        let withdrawn_cost_basis = self
+            .total_deposited_underlying
+            .checked_sub(self.deposited_balance)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Ok(TrackingStatement {
+            shares: self.shares,
+            cost_basis: self.deposited_balance,
+            current_value,
+            unrealized_profit: current_value as i128 - self.deposited_balance as i128,
+            realized_profit: self.total_withdrawn_underlying as i128 - withdrawn_cost_basis as i128,
+            pending_rewards: self.pending_rewards(reward_per_share)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::vault_base::VaultBaseV1;
+    #[test]
+    fn test_statement() {
+        let vault = VaultBaseV1 {
+            total_deposited_balance: 1_100,
+            total_shares: 1_000,
+            ..Default::default()
+        };
+        let mut tracking = <DepositTrackingV1 as bytemuck::Zeroable>::zeroed();
+        tracking.shares = 500;
+        tracking.deposited_balance = 480;
+        tracking.total_deposited_underlying = 600;
+        tracking.total_withdrawn_underlying = 130;
+        tracking.last_pending_reward = 12;
+        tracking.reward_per_share_paid = 2 * REWARD_PER_SHARE_PRECISION;
+        // 500 shares accrue 0.01 rewards per share on top of the 12 already pending
+        let reward_per_share = 2 * REWARD_PER_SHARE_PRECISION + REWARD_PER_SHARE_PRECISION / 100;
+        let statement = tracking.statement(&vault, reward_per_share).unwrap();
+        assert_eq!(
+            statement,
+            TrackingStatement {
+                shares: 500,
+                cost_basis: 480,
+                current_value: 550,
+                unrealized_profit: 70,
+                realized_profit: 10,
+                pending_rewards: 17,
+            }
+        );
+        assert_eq!(statement.total_profit(), 80);
+        assert_eq!(tracking.pending_rewards(reward_per_share).unwrap(), 17);
+        // nothing has accrued since the checkpoint
+        assert_eq!(
+            tracking
+                .pending_rewards(tracking.reward_per_share_paid)
+                .unwrap(),
+            12
+        );
+        // the reward per share can't be below the one already paid
+        assert!(tracking
+            .pending_rewards(REWARD_PER_SHARE_PRECISION)
+            .is_err());
+        // a position valued below its cost basis still has its pending rewards
+        let vault = VaultBaseV1 {
+            total_deposited_balance: 900,
+            total_shares: 1_000,
+            ..Default::default()
+        };
+        let statement = tracking.statement(&vault, reward_per_share).unwrap();
+        assert_eq!(statement.current_value, 450);
+        assert_eq!(statement.unrealized_profit, -30);
+        assert_eq!(statement.pending_rewards, 17);
+        // an empty vault has nothing to redeem
+        assert_eq!(tracking.current_value(&VaultBaseV1::default()), 0);
+    }
+}
@@ -0,0 +1,395 @@
+//! decoding of the atrix farm program's farm, crop, staker and harvester accounts, and the
+//! reward math used to estimate the staked lp and pending crop rewards of atrix vaults.
+//!
+//! a farm distributes rewards through up to 4 crops, each of which emits `reward_amount_per_day`
+//! of its reward mint to stakers in proportion to their stake. a staker tracks the lp staked by
+//! an authority, while a harvester tracks the rewards owed to an authority by a single crop
+use super::account_discriminator;
+use super::atrix_vault::AtrixVaultV1;
+use anchor_lang::prelude::*;
+use so_defi_atrix::addresses as atrix_addresses;
+use tulipv2_sdk_common::math::error::MathError;
+use tulipv2_sdk_common::math::uint::U256;
+
+pub use so_defi_atrix::prelude::atrix_accounts::farm::{
+    CropAccount, FarmAccount, HarvesterAccount, StakerAccount,
+};
+
+/// number of seconds in a day, used to convert a crop's daily emissions into a per second rate
+pub const SECONDS_PER_DAY: u128 = 86_400;
+/// precision multiplier used by the atrix farm program for a crop's accrued reward per stake
+pub const ACCRUED_REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// the staked lp and estimated rewards of an atrix vault which have yet to be harvested
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AtrixRewardsEstimate {
+    /// amount of lp tokens staked by the vault's staker account
+    pub staked_amount: u64,
+    /// pending rewards denominated in the reward mint of the farm's first crop
+    pub pending_crop_1: u64,
+    /// pending rewards denominated in the reward mint of the farm's second crop, always
+    /// 0 for vaults which are not dual crop
+    pub pending_crop_2: u64,
+}
+
+/// decodes a farm account, validating the account discriminator
+pub fn decode_farm_account(data: &[u8]) -> std::result::Result<FarmAccount, ProgramError> {
+    decode_account(data, "FarmAccount")
+}
+
+/// decodes a crop account, validating the account discriminator
+pub fn decode_crop_account(data: &[u8]) -> std::result::Result<CropAccount, ProgramError> {
+    decode_account(data, "CropAccount")
+}
+
+/// decodes a staker account, validating the account discriminator
+pub fn decode_staker_account(data: &[u8]) -> std::result::Result<StakerAccount, ProgramError> {
+    decode_account(data, "StakerAccount")
+}
+
+/// decodes a harvester account, validating the account discriminator
+pub fn decode_harvester_account(
+    data: &[u8],
+) -> std::result::Result<HarvesterAccount, ProgramError> {
+    decode_account(data, "HarvesterAccount")
+}
+
+/// returns the address of the given farm account, which is derived from the farm's base
+pub fn farm_address(farm: &FarmAccount) -> Pubkey {
+    atrix_addresses::find_farm_address(farm.base).0
+}
+
+/// returns the accrued reward per stake of `crop` as of `current_ts`, including rewards
+/// emitted since the crop was last updated. `total_staked` is the amount of lp tokens held
+/// by the farm's stake token account
+pub fn crop_reward_per_stake(
+    crop: &CropAccount,
+    total_staked: u64,
+    current_ts: i64,
+) -> std::result::Result<u128, ProgramError> {
+    if total_staked == 0 || current_ts <= crop.last_reward_timestamp {
+        return Ok(crop.accrued_reward_per_stake);
+    }
+    let elapsed = (current_ts - crop.last_reward_timestamp) as u128;
+    let reward = U256::from(elapsed)
+        .checked_mul(U256::from(crop.reward_amount_per_day))
+        .ok_or(MathError::MathOverflow)?
+        .checked_mul(U256::from(ACCRUED_REWARD_PRECISION))
+        .ok_or(MathError::MathOverflow)?
+        / U256::from(SECONDS_PER_DAY)
+        / U256::from(total_staked);
+    let reward = u128::try_from(reward).map_err(|_| MathError::MathOverflow)?;
+    Ok(crop
+        .accrued_reward_per_stake
+        .checked_add(reward)
+        .ok_or(MathError::MathOverflow)?)
+}
+
+/// returns the rewards owed by `crop` to the authority of `staker` and `harvester` as of
+/// `current_ts`, including rewards which were earned but not yet harvested
+pub fn pending_crop_rewards(
+    crop: &CropAccount,
+    staker: &StakerAccount,
+    harvester: &HarvesterAccount,
+    total_staked: u64,
+    current_ts: i64,
+) -> std::result::Result<u64, ProgramError> {
+    let reward_per_stake = crop_reward_per_stake(crop, total_staked, current_ts)?;
+    let accumulated = U256::from(staker.staked_amount)
+        .checked_mul(U256::from(reward_per_stake))
+        .ok_or(MathError::MathOverflow)?
+        / U256::from(ACCRUED_REWARD_PRECISION);
+    let pending = accumulated
+        .checked_sub(U256::from(harvester.reward_debt))
+        .ok_or(MathError::MathOverflow)?
+        .checked_add(U256::from(harvester.earned_rewards))
+        .ok_or(MathError::MathOverflow)?;
+    Ok(u64::try_from(pending).map_err(|_| MathError::MathOverflow)?)
+}
+
+impl AtrixVaultV1 {
+    /// returns the addresses of the crops farmed by the vault, which is the farm's first crop,
+    /// followed by the farm's second crop for dual crop vaults
+    pub fn crop_addresses(
+        &self,
+        farm: &FarmAccount,
+    ) -> std::result::Result<Vec<Pubkey>, ProgramError> {
+        let num_crops = if self.dual_crop == 0 { 1 } else { 2 };
+        farm.crop_accounts[..num_crops]
+            .iter()
+            .map(|crop| crop.ok_or(ProgramError::InvalidAccountData))
+            .collect()
+    }
+    /// returns the addresses of the harvester accounts owned by the vault pda, one for each
+    /// of the vault's crops. the first address is `self.vault_harvester_account`
+    pub fn harvester_addresses(
+        &self,
+        farm: &FarmAccount,
+    ) -> std::result::Result<Vec<Pubkey>, ProgramError> {
+        Ok(self
+            .crop_addresses(farm)?
+            .into_iter()
+            .map(|crop| atrix_addresses::find_harvester_address(crop, self.base.pda).0)
+            .collect())
+    }
+    /// estimates the staked lp and unharvested rewards of the vault as of `current_ts`, where
+    /// `farm` is the account at `self.atrix_farm_account`, `staker` is the account at
+    /// `self.vault_staker_account`, and `crops` contains each crop at `self.crop_addresses()`
+    /// paired with the harvester at the matching `self.harvester_addresses()`.
+    ///
+    /// `total_staked` is the amount of lp tokens held by `farm.farm_stake_token_account`
+    pub fn estimate_rewards(
+        &self,
+        farm: &FarmAccount,
+        staker: &StakerAccount,
+        crops: &[(CropAccount, HarvesterAccount)],
+        total_staked: u64,
+        current_ts: i64,
+    ) -> std::result::Result<AtrixRewardsEstimate, ProgramError> {
+        if farm_address(farm) != self.atrix_farm_account
+            || staker.farm_account != self.atrix_farm_account
+            || staker.authority != self.base.pda
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let crop_addresses = self.crop_addresses(farm)?;
+        if crops.len() != crop_addresses.len() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut pending = [0_u64; 2];
+        for (i, ((crop, harvester), crop_address)) in
+            crops.iter().zip(crop_addresses.iter()).enumerate()
+        {
+            if crop.farm_account != self.atrix_farm_account
+                || atrix_addresses::find_crop_address(self.atrix_farm_account, crop.reward_mint).0
+                    != *crop_address
+                || harvester.crop_account != *crop_address
+                || harvester.authority != self.base.pda
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            pending[i] = pending_crop_rewards(crop, staker, harvester, total_staked, current_ts)?;
+        }
+        Ok(AtrixRewardsEstimate {
+            staked_amount: staker.staked_amount,
+            pending_crop_1: pending[0],
+            pending_crop_2: pending[1],
+        })
+    }
+}
+
+fn decode_account<T: AnchorDeserialize>(
+    data: &[u8],
+    account_name: &str,
+) -> std::result::Result<T, ProgramError> {
+    if data.len() < 8 {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    if data[0..8] != account_discriminator(account_name) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    T::deserialize(&mut &data[..]).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn account_data(account: &impl AnchorSerialize, account_name: &str) -> Vec<u8> {
+        let mut data = account.try_to_vec().unwrap();
+        data[0..8].copy_from_slice(&account_discriminator(account_name));
+        data
+    }
+
+    struct Fixture {
+        vault: AtrixVaultV1,
+        farm: FarmAccount,
+        staker: StakerAccount,
+        crops: Vec<(CropAccount, HarvesterAccount)>,
+    }
+
+    fn new_fixture(dual_crop: u8) -> Fixture {
+        // the account types contain a private discriminator field, so they can't
+        // be constructed using struct update syntax
+        let mut farm = FarmAccount::default();
+        farm.base = Pubkey::new_unique();
+        farm.bump = 255;
+        farm.state_mint = Pubkey::new_unique();
+        farm.farm_stake_token_account = Pubkey::new_unique();
+        farm.authority = Pubkey::new_unique();
+        let farm_key = farm_address(&farm);
+        let mut vault = AtrixVaultV1::new();
+        vault.base.pda = Pubkey::new_unique();
+        vault.atrix_farm_account = farm_key;
+        vault.vault_staker_account =
+            atrix_addresses::find_staker_address(farm_key, vault.base.pda).0;
+        vault.dual_crop = dual_crop;
+        let mut staker = StakerAccount::default();
+        staker.bump = 254;
+        staker.farm_account = farm_key;
+        staker.authority = vault.base.pda;
+        staker.staked_amount = 1_000_000;
+        let mut crops = vec![];
+        for (i, (reward_amount_per_day, reward_debt, earned_rewards)) in
+            [(10_000, 0, 0), (86_400, 5, 3)].into_iter().enumerate()
+        {
+            let mut crop = CropAccount::default();
+            crop.farm_account = farm_key;
+            crop.reward_mint = Pubkey::new_unique();
+            crop.reward_amount_per_day = reward_amount_per_day;
+            crop.crop_reward_token_account = Pubkey::new_unique();
+            crop.accrued_reward_per_stake = 2 * ACCRUED_REWARD_PRECISION;
+            crop.last_reward_timestamp = 1_000;
+            let crop_key = atrix_addresses::find_crop_address(farm_key, crop.reward_mint).0;
+            farm.crop_accounts[i] = Some(crop_key);
+            let mut harvester = HarvesterAccount::default();
+            harvester.crop_account = crop_key;
+            harvester.reward_debt = reward_debt;
+            harvester.earned_rewards = earned_rewards;
+            harvester.authority = vault.base.pda;
+            crops.push((crop, harvester));
+        }
+        vault.vault_harvester_account =
+            atrix_addresses::find_harvester_address(farm.crop_accounts[0].unwrap(), vault.base.pda)
+                .0;
+        Fixture {
+            crops: crops[..if dual_crop == 0 { 1 } else { 2 }].to_vec(),
+            vault,
+            farm,
+            staker,
+        }
+    }
+
+    #[test]
+    fn test_decode_farm_accounts() {
+        let fixture = new_fixture(1);
+        let data = account_data(&fixture.farm, "FarmAccount");
+        let farm = decode_farm_account(&data).unwrap();
+        assert_eq!(farm.base, fixture.farm.base);
+        assert_eq!(farm.state_mint, fixture.farm.state_mint);
+        assert_eq!(farm.crop_accounts, fixture.farm.crop_accounts);
+        assert_eq!(farm.authority, fixture.farm.authority);
+        assert!(decode_crop_account(&data).is_err());
+        assert!(decode_farm_account(&data[..4]).is_err());
+        assert!(decode_farm_account(&data[..100]).is_err());
+
+        let (crop, harvester) = &fixture.crops[1];
+        let decoded = decode_crop_account(&account_data(crop, "CropAccount")).unwrap();
+        assert_eq!(decoded.reward_mint, crop.reward_mint);
+        assert_eq!(decoded.reward_amount_per_day, crop.reward_amount_per_day);
+        assert_eq!(
+            decoded.accrued_reward_per_stake,
+            crop.accrued_reward_per_stake
+        );
+        assert_eq!(decoded.last_reward_timestamp, crop.last_reward_timestamp);
+        let decoded =
+            decode_harvester_account(&account_data(harvester, "HarvesterAccount")).unwrap();
+        assert_eq!(decoded.crop_account, harvester.crop_account);
+        assert_eq!(decoded.reward_debt, harvester.reward_debt);
+        assert_eq!(decoded.earned_rewards, harvester.earned_rewards);
+        let decoded =
+            decode_staker_account(&account_data(&fixture.staker, "StakerAccount")).unwrap();
+        assert_eq!(decoded.farm_account, fixture.staker.farm_account);
+        assert_eq!(decoded.authority, fixture.staker.authority);
+        assert_eq!(decoded.staked_amount, fixture.staker.staked_amount);
+    }
+
+    #[test]
+    fn test_estimate_rewards() {
+        let fixture = new_fixture(1);
+        assert_eq!(
+            fixture.vault.harvester_addresses(&fixture.farm).unwrap()[0],
+            fixture.vault.vault_harvester_account
+        );
+        // 1_000 seconds elapsed with 4_000_000 lp staked in the farm
+        let estimate = fixture
+            .vault
+            .estimate_rewards(
+                &fixture.farm,
+                &fixture.staker,
+                &fixture.crops,
+                4_000_000,
+                2_000,
+            )
+            .unwrap();
+        // crop 1 emits 10_000 / 86_400 per second, a quarter of which goes to the vault
+        // on top of the 2 rewards per stake already accrued
+        assert_eq!(
+            estimate,
+            AtrixRewardsEstimate {
+                staked_amount: 1_000_000,
+                pending_crop_1: 2_000_000 + 10_000 * 1_000 / 86_400 / 4,
+                pending_crop_2: 2_000_000 + 250 - 5 + 3,
+            }
+        );
+
+        // the second crop is ignored when the vault is not dual crop
+        let fixture = new_fixture(0);
+        let estimate = fixture
+            .vault
+            .estimate_rewards(
+                &fixture.farm,
+                &fixture.staker,
+                &fixture.crops,
+                4_000_000,
+                2_000,
+            )
+            .unwrap();
+        assert_eq!(estimate.pending_crop_1, 2_000_000 + 28);
+        assert_eq!(estimate.pending_crop_2, 0);
+
+        // rewards do not accrue when nothing is staked, or before the last update
+        for (total_staked, current_ts) in [(0, 2_000), (4_000_000, 500)] {
+            let estimate = fixture
+                .vault
+                .estimate_rewards(
+                    &fixture.farm,
+                    &fixture.staker,
+                    &fixture.crops,
+                    total_staked,
+                    current_ts,
+                )
+                .unwrap();
+            assert_eq!(estimate.pending_crop_1, 2_000_000);
+        }
+    }
+
+    #[test]
+    fn test_estimate_rewards_invalid_accounts() {
+        let fixture = new_fixture(1);
+        let estimate = |staker: &StakerAccount, crops: &[(CropAccount, HarvesterAccount)]| {
+            fixture
+                .vault
+                .estimate_rewards(&fixture.farm, staker, crops, 4_000_000, 2_000)
+        };
+        assert_eq!(
+            estimate(&fixture.staker, &fixture.crops[..1]),
+            Err(ProgramError::InvalidArgument)
+        );
+        let mut staker = fixture.staker.clone();
+        staker.authority = Pubkey::new_unique();
+        assert_eq!(
+            estimate(&staker, &fixture.crops),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut crops = fixture.crops.clone();
+        crops.swap(0, 1);
+        assert_eq!(
+            estimate(&fixture.staker, &crops),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut crops = fixture.crops.clone();
+        crops[1].1.authority = Pubkey::new_unique();
+        assert_eq!(
+            estimate(&fixture.staker, &crops),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut farm = fixture.farm.clone();
+        farm.crop_accounts[1] = None;
+        assert_eq!(
+            fixture.vault.crop_addresses(&farm),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
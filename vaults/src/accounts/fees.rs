@@ -0,0 +1,336 @@
+//! fee engine used to calculate the fees charged by v2 vaults on deposits, withdrawals
+//! and compound harvests, according to the vault's `FeesV1` configuration.
+//!
+//! fees are expressed as percentages which are divided by `fee_multiplier`. for multi deposit
+//! optimizers the sum of the controller and platform fees is divided by the multiplier, for
+//! other vault types the multiplier is applied to each fee separately. a zeroed multiplier
+//! is rejected as a misconfigured vault
+use super::{
+    atrix_vault::AtrixVaultV1,
+    lending_optimizer::LendingOptimizerV1,
+    multi_optimizer::MultiDepositOptimizerV1,
+    orca_vault::{OrcaDoubleDipVaultV1, OrcaVaultV1},
+    quarry_vault::QuarryVaultV1,
+    raydium_vault::RaydiumVaultV1,
+    vault_base::FeesV1,
+};
+use anchor_lang::solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+use tulipv2_sdk_common::math::error::MathError;
+
+/// fees are configured as whole percentages, prior to dividing by the fee multiplier
+pub const PERCENT_DENOMINATOR: u128 = 100;
+
+/// the type of vault charging fees, which determines how the fee multiplier is applied
+/// and how the `total_collected_a/b` values are interpreted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeVaultType {
+    MultiDepositOptimizer,
+    LendingOptimizer,
+    Raydium {
+        /// whether or not the vault is farming a dual reward pool
+        dual_rewards: bool,
+    },
+    Orca {
+        /// whether or not the vault is a double dip vault
+        double_dip: bool,
+    },
+    Quarry,
+    Atrix,
+}
+
+/// the fee charged on a deposit or withdrawal
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeCharge {
+    /// the amount taken as fees
+    pub fee: u64,
+    /// the amount remaining after fees, ie: "you will receive"
+    pub amount_after_fee: u64,
+    /// the token account receiving the fees
+    pub fee_recipient: Pubkey,
+}
+
+/// the fees charged against rewards harvested during a compound
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarvestFees {
+    pub controller_fee: u64,
+    pub platform_fee: u64,
+    /// the amount of rewards remaining to be compounded
+    pub amount_after_fees: u64,
+    /// the token account receiving the fees
+    pub fee_recipient: Pubkey,
+}
+
+impl HarvestFees {
+    pub fn total_fee(&self) -> u64 {
+        // both fees are carved out of the same harvested amount so this can't overflow
+        self.controller_fee + self.platform_fee
+    }
+}
+
+/// the lifetime fees collected by a vault
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CollectedFees {
+    /// fees collected from the vault's primary reward. for orca double dip vaults
+    /// this is the non double dip reward
+    pub reward_a: u64,
+    /// fees collected from raydium dual reward vault's rewardB, or the double dip
+    /// reward of orca double dip vaults. None for all other vaults
+    pub reward_b: Option<u64>,
+}
+
+impl FeeVaultType {
+    pub fn is_multi_deposit_optimizer(&self) -> bool {
+        matches!(self, FeeVaultType::MultiDepositOptimizer)
+    }
+    /// returns true if the vault tracks fees for two separate rewards
+    pub fn has_second_reward(&self) -> bool {
+        matches!(
+            self,
+            FeeVaultType::Raydium { dual_rewards: true } | FeeVaultType::Orca { double_dip: true }
+        )
+    }
+}
+
+impl FeesV1 {
+    /// returns the address of the associated token account owned by the
+    /// fee wallet that receives fees denominated in `mint`
+    pub fn fee_recipient(&self, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address(&self.fee_wallet, mint)
+    }
+    /// calculates the fee charged for depositing `amount` of the underlying asset
+    pub fn deposit_fee_charge(
+        &self,
+        underlying_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<FeeCharge, ProgramError> {
+        self.fee_charge(self.deposit_fee, underlying_mint, amount)
+    }
+    /// calculates the fee charged when withdrawing `amount` of the underlying asset
+    pub fn withdraw_fee_charge(
+        &self,
+        underlying_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<FeeCharge, ProgramError> {
+        self.fee_charge(self.withdraw_fee, underlying_mint, amount)
+    }
+    /// calculates the controller and platform fees charged against `harvested` rewards
+    /// of `reward_mint` during a compound
+    pub fn harvest_fees(
+        &self,
+        vault_type: FeeVaultType,
+        reward_mint: &Pubkey,
+        harvested: u64,
+    ) -> Result<HarvestFees, ProgramError> {
+        let (controller_fee, platform_fee) = if vault_type.is_multi_deposit_optimizer() {
+            let fee_sum = self
+                .controller_fee
+                .checked_add(self.platform_fee)
+                .ok_or(MathError::MathOverflow)?;
+            let total_fee = self.apply_fee(fee_sum, harvested)?;
+            let controller_fee = if fee_sum == 0 {
+                0
+            } else {
+                mul_div(total_fee, self.controller_fee, fee_sum as u128)?
+            };
+            (controller_fee, total_fee - controller_fee)
+        } else {
+            (
+                self.apply_fee(self.controller_fee, harvested)?,
+                self.apply_fee(self.platform_fee, harvested)?,
+            )
+        };
+        let amount_after_fees = harvested
+            .checked_sub(controller_fee)
+            .and_then(|amount| amount.checked_sub(platform_fee))
+            .ok_or(MathError::MathOverflow)?;
+        Ok(HarvestFees {
+            controller_fee,
+            platform_fee,
+            amount_after_fees,
+            fee_recipient: self.fee_recipient(reward_mint),
+        })
+    }
+    /// returns the lifetime fees collected by the vault, interpreting
+    /// `total_collected_a/b` according to the vault type
+    pub fn collected_fees(&self, vault_type: FeeVaultType) -> CollectedFees {
+        CollectedFees {
+            reward_a: self.total_collected_a,
+            reward_b: if vault_type.has_second_reward() {
+                Some(self.total_collected_b)
+            } else {
+                None
+            },
+        }
+    }
+    fn fee_charge(&self, fee: u64, mint: &Pubkey, amount: u64) -> Result<FeeCharge, ProgramError> {
+        let fee = self.apply_fee(fee, amount)?;
+        Ok(FeeCharge {
+            fee,
+            amount_after_fee: amount.checked_sub(fee).ok_or(MathError::MathOverflow)?,
+            fee_recipient: self.fee_recipient(mint),
+        })
+    }
+    /// returns the portion of `amount` charged by `fee`
+    fn apply_fee(&self, fee: u64, amount: u64) -> Result<u64, ProgramError> {
+        if self.fee_multiplier == 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let denominator = (self.fee_multiplier as u128)
+            .checked_mul(PERCENT_DENOMINATOR)
+            .ok_or(MathError::MathOverflow)?;
+        let fee = mul_div(amount, fee, denominator)?;
+        if fee > amount {
+            // a fee above 100% indicates a misconfigured vault
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(fee)
+    }
+}
+
+fn mul_div(amount: u64, numerator: u64, denominator: u128) -> Result<u64, ProgramError> {
+    let result = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(MathError::MathOverflow)?
+        .checked_div(denominator)
+        .ok_or(MathError::MathOverflow)?;
+    u64::try_from(result).map_err(|_| MathError::MathOverflow.into())
+}
+
+impl From<&MultiDepositOptimizerV1> for FeeVaultType {
+    fn from(_: &MultiDepositOptimizerV1) -> Self {
+        FeeVaultType::MultiDepositOptimizer
+    }
+}
+
+impl From<&LendingOptimizerV1> for FeeVaultType {
+    fn from(_: &LendingOptimizerV1) -> Self {
+        FeeVaultType::LendingOptimizer
+    }
+}
+
+impl From<&RaydiumVaultV1> for FeeVaultType {
+    fn from(vault: &RaydiumVaultV1) -> Self {
+        FeeVaultType::Raydium {
+            dual_rewards: vault.dual_rewards == 1,
+        }
+    }
+}
+
+impl From<&OrcaVaultV1> for FeeVaultType {
+    fn from(_: &OrcaVaultV1) -> Self {
+        FeeVaultType::Orca { double_dip: false }
+    }
+}
+
+impl From<&OrcaDoubleDipVaultV1> for FeeVaultType {
+    fn from(_: &OrcaDoubleDipVaultV1) -> Self {
+        FeeVaultType::Orca { double_dip: true }
+    }
+}
+
+impl From<&QuarryVaultV1> for FeeVaultType {
+    fn from(_: &QuarryVaultV1) -> Self {
+        FeeVaultType::Quarry
+    }
+}
+
+impl From<&AtrixVaultV1> for FeeVaultType {
+    fn from(_: &AtrixVaultV1) -> Self {
+        FeeVaultType::Atrix
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    fn test_fees(fee_multiplier: u64) -> FeesV1 {
+        FeesV1 {
+            fee_multiplier,
+            controller_fee: 100,
+            platform_fee: 200,
+            withdraw_fee: 50,
+            deposit_fee: 10,
+            fee_wallet: Pubkey::new_unique(),
+            total_collected_a: 1,
+            total_collected_b: 2,
+            ..Default::default()
+        }
+    }
+    #[test]
+    fn test_deposit_withdraw_fees() {
+        let mint = Pubkey::new_unique();
+        let fees = test_fees(100);
+        // 0.1%
+        let charge = fees.deposit_fee_charge(&mint, 1_000_000).unwrap();
+        assert_eq!(charge.fee, 1_000);
+        assert_eq!(charge.amount_after_fee, 999_000);
+        assert_eq!(
+            charge.fee_recipient,
+            get_associated_token_address(&fees.fee_wallet, &mint)
+        );
+        // 0.5%
+        let charge = fees.withdraw_fee_charge(&mint, 1_000_000).unwrap();
+        assert_eq!(charge.fee, 5_000);
+        assert_eq!(charge.amount_after_fee, 995_000);
+        // a multiplier of 1 treats fees as whole percentages
+        let charge = test_fees(1).withdraw_fee_charge(&mint, 1_000_000).unwrap();
+        assert_eq!(charge.fee, 500_000);
+        // 200% is invalid
+        assert_eq!(
+            test_fees(1)
+                .harvest_fees(FeeVaultType::Quarry, &mint, 1_000_000)
+                .unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+        // an unset multiplier is invalid
+        assert_eq!(
+            test_fees(0)
+                .deposit_fee_charge(&mint, 1_000_000)
+                .unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+    #[test]
+    fn test_harvest_fees() {
+        let mint = Pubkey::new_unique();
+        let fees = test_fees(100);
+        let harvest = fees
+            .harvest_fees(FeeVaultType::MultiDepositOptimizer, &mint, 999)
+            .unwrap();
+        // 3% of 999 is 29, split 1:2 between the controller and platform
+        assert_eq!(harvest.total_fee(), 29);
+        assert_eq!(harvest.controller_fee, 9);
+        assert_eq!(harvest.platform_fee, 20);
+        assert_eq!(harvest.amount_after_fees, 970);
+        // each fee is rounded down individually
+        let harvest = fees
+            .harvest_fees(FeeVaultType::Raydium { dual_rewards: true }, &mint, 999)
+            .unwrap();
+        assert_eq!(harvest.controller_fee, 9);
+        assert_eq!(harvest.platform_fee, 19);
+        assert_eq!(harvest.amount_after_fees, 971);
+    }
+    #[test]
+    fn test_collected_fees() {
+        let fees = test_fees(100);
+        assert_eq!(
+            fees.collected_fees(FeeVaultType::Orca { double_dip: true }),
+            CollectedFees {
+                reward_a: 1,
+                reward_b: Some(2)
+            }
+        );
+        assert_eq!(
+            fees.collected_fees(FeeVaultType::Raydium {
+                dual_rewards: false
+            })
+            .reward_b,
+            None
+        );
+        assert_eq!(
+            fees.collected_fees(FeeVaultType::LendingOptimizer).reward_b,
+            None
+        );
+    }
+}
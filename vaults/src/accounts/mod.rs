@@ -9,17 +9,25 @@
 
 use self::vault_base::VaultBaseV1;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
 
 use anchor_spl::token::Mint;
 
-use tulipv2_sdk_common::traits::vault::TokenizedShares;
+use tulipv2_sdk_common::{tag::VaultTag, traits::vault::TokenizedShares};
 use tulipv2_sdk_farms::Farm;
 
+pub mod accounting;
+pub mod atrix_farm;
 pub mod atrix_vault;
+pub mod fees;
 pub mod lending_optimizer;
 pub mod multi_optimizer;
+pub mod orca_routing;
 pub mod orca_vault;
+pub mod preflight;
+pub mod quarry_mine;
 pub mod quarry_vault;
+pub mod raydium_stake;
 pub mod raydium_vault;
 pub mod tracking;
 pub mod vault_base;
@@ -56,14 +64,18 @@ pub struct InitFeeArgsV1;
 /// with strings. there is no hard requirements for the format of this tag, however it is
 /// recommended that if the tag is 31 bytes or less, you use a newline delimiter (\n)
 /// so that UI, and other clients can render the data correctly
-pub fn derive_vault_address(farm: &tulipv2_sdk_farms::Farm, tag: [u8; 32]) -> (Pubkey, u8) {
+pub fn derive_vault_address(
+    farm: &tulipv2_sdk_farms::Farm,
+    tag: impl Into<VaultTag>,
+) -> (Pubkey, u8) {
     let parts: [u64; 2] = (*farm).into();
+    let tag: VaultTag = tag.into();
     Pubkey::find_program_address(
         &[
             b"v1",
             &parts[0].to_le_bytes()[..],
             &parts[1].to_le_bytes()[..],
-            &tag[..],
+            tag.as_ref(),
         ],
         &crate::ID,
     )
@@ -201,6 +213,14 @@ pub fn parse_formatted_name(formatted_name: &str) -> (String, String) {
     (farm_name, tag)
 }
 
+/// parses a formatted name as given by [vault_base::VaultBaseV1::formatted_name()]
+/// into the vault's farm and tag, returning None if the name is malformed, the farm
+/// is unknown, or the tag is invalid
+pub fn try_parse_formatted_name(formatted_name: &str) -> Option<(Farm, VaultTag)> {
+    let (farm_name, tag) = formatted_name.strip_suffix(')')?.split_once("-tag(")?;
+    Some((farm_name.parse().ok()?, tag.parse().ok()?))
+}
+
 use bytemuck::{cast_slice, from_bytes, try_cast_slice, Pod};
 pub fn load<T: Pod>(data: &[u8]) -> Result<&T> {
     let size = std::mem::size_of::<T>();
@@ -214,12 +234,39 @@ pub fn load<T: Pod>(data: &[u8]) -> Result<&T> {
     Ok(from_bytes(cast_slice::<u8, u8>(sliced_data)))
 }
 
+/// returns the anchor account discriminator for `account_name`
+pub fn account_discriminator(account_name: &str) -> [u8; 8] {
+    let mut discriminator = [0_u8; 8];
+    discriminator
+        .copy_from_slice(&hash(format!("account:{}", account_name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// reads the pubkey at `offset` of an account's data
+pub(crate) fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new(&data[offset..offset + 32])
+}
+
+/// reads the little endian u64 at `offset` of an account's data
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// reads the little endian u128 at `offset` of an account's data
+pub(crate) fn read_u128(data: &[u8], offset: usize) -> u128 {
+    let mut bytes = [0_u8; 16];
+    bytes.copy_from_slice(&data[offset..offset + 16]);
+    u128::from_le_bytes(bytes)
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
 
     use tulipv2_sdk_common::DEFAULT_KEY;
-    use tulipv2_sdk_farms::Farm;
+    use tulipv2_sdk_farms::{lending::Lending, orca::Orca, raydium::Raydium, Farm};
 
     use super::*;
     #[test]
@@ -396,4 +443,44 @@ mod test {
             assert_eq!(&got_tag, test.1);
         }
     }
+    #[test]
+    fn test_try_parse_formatted_name() {
+        let (farm, tag) = try_parse_formatted_name("LENDING-MULTI_DEPOSIT-tag(usdcv1)").unwrap();
+        assert_eq!(
+            farm,
+            Farm::Lending {
+                name: Lending::MULTI_DEPOSIT
+            }
+        );
+        assert_eq!(tag, VaultTag::new("usdcv1").unwrap());
+        let (farm, tag) = try_parse_formatted_name("RAYDIUM-RAY-USDC-tag()").unwrap();
+        assert_eq!(
+            farm,
+            Farm::Raydium {
+                name: Raydium::RAYUSDC
+            }
+        );
+        assert!(tag.is_empty());
+        // the tag may itself contain the delimiters
+        let (_, tag) = try_parse_formatted_name("ORCA-ORCA-SOL-tag(a-tag(b))").unwrap();
+        assert_eq!(tag.to_string(), "a-tag(b)");
+        let mut vault = VaultBaseV1::default();
+        vault.farm = Farm::Orca {
+            name: Orca::ORCASOL,
+        }
+        .into();
+        vault.tag = VaultTag::new("tülip").unwrap().into();
+        assert_eq!(
+            try_parse_formatted_name(&vault.formatted_name()),
+            Some((Farm::from(vault.farm), VaultTag::from(vault.tag)))
+        );
+        for invalid in [
+            "RAYDIUM-RAY-USDC",
+            "RAYDIUM-RAY-USDC-tag(",
+            "RAYDIUM-FOO-tag()",
+            "RAYDIUM-RAY-USDC-tag(aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa)",
+        ] {
+            assert!(try_parse_formatted_name(invalid).is_none());
+        }
+    }
 }
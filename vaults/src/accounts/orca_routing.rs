@@ -0,0 +1,437 @@
+//! routing of orca vault compounds across the swap markets configured by `OrcaVaultDataV1`.
+//!
+//! when compounding, the vault converts its reward tokens into the pool's token a and b, and
+//! adds liquidity to the pool. the router quotes every combination of swap markets, and every
+//! split of the rewards between the two tokens, returning the route yielding the most lp tokens
+use super::orca_vault::OrcaVaultDataV1;
+use anchor_lang::prelude::*;
+use tulipv2_sdk_common::math::{
+    common::{TryAdd, TryDiv, TryMul},
+    decimal::Decimal,
+    error::MathError,
+};
+use tulipv2_sdk_common::pricing::orca::{OrcaPool, OrcaQuote};
+
+/// the state of an orca swap account, along with the balances of its token accounts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrcaMarketState {
+    /// address of the swap account
+    pub address: Pubkey,
+    pub pool: OrcaPool,
+    /// balance of `pool.token_a`
+    pub balance_a: u64,
+    /// balance of `pool.token_b`
+    pub balance_b: u64,
+}
+
+/// a swap of reward tokens through one of the vault's swap markets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrcaRouteLeg {
+    /// address of the swap account used by the swap
+    pub market: Pubkey,
+    /// true if the reward token is the market's token a
+    pub a_to_b: bool,
+    pub quote: OrcaQuote,
+}
+
+/// the route yielding the most lp tokens when compounding a vault's rewards
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrcaCompoundRoute {
+    /// the swap from reward tokens into token a, or None if the reward token is token a
+    pub token_a_leg: Option<OrcaRouteLeg>,
+    /// the swap from reward tokens into token b, or None if the reward token is token b
+    pub token_b_leg: Option<OrcaRouteLeg>,
+    /// the amount of token a deposited into the pool
+    pub token_a_amount: u64,
+    /// the amount of token b deposited into the pool
+    pub token_b_amount: u64,
+    /// the expected amount of lp tokens received when adding liquidity
+    pub lp_amount: u64,
+    /// the price impact of the swaps, weighted by the amount of rewards swapped
+    /// through each leg. a value of 0.01 indicates 1% price impact
+    pub price_impact: Decimal,
+}
+
+impl OrcaCompoundRoute {
+    /// the minimum amount of lp tokens after applying `slippage_bps`
+    pub fn minimum_lp_amount(&self, slippage_bps: u64) -> std::result::Result<u64, ProgramError> {
+        tulipv2_sdk_common::pricing::minimum_amount_out(self.lp_amount, slippage_bps)
+    }
+}
+
+/// how rewards are converted into one of the pool's tokens
+#[derive(Clone, Copy)]
+enum LegOption<'a> {
+    /// the reward token is the pool's token
+    Keep,
+    /// swap through the given market
+    Swap(&'a OrcaMarketState, bool),
+}
+
+impl OrcaVaultDataV1 {
+    /// returns the route converting `reward_amount` reward tokens into the most lp tokens.
+    ///
+    /// * `pool` - the state of the vault's pool, `pool_swap_account`
+    /// * `lp_supply` - the supply of the pool's lp token, `swap_pool_mint`
+    /// * `markets` - the state of the vault's swap markets, each of which must be one of `swap_markets`
+    ///
+    /// swaps made through `pool` itself are applied to the pool's balances before adding
+    /// liquidity, however lp tokens minted to the pool owner as fees are ignored
+    pub fn route_compound(
+        &self,
+        pool: &OrcaMarketState,
+        lp_supply: u64,
+        markets: &[OrcaMarketState],
+        reward_amount: u64,
+    ) -> std::result::Result<OrcaCompoundRoute, ProgramError> {
+        if pool.address != self.pool_swap_account
+            || pool.pool.pool_mint != self.swap_pool_mint
+            || pool.pool.token_a_mint != self.token_a_mint
+            || pool.pool.token_b_mint != self.token_b_mint
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if markets.iter().any(|market| {
+            market.address == Pubkey::default() || !self.swap_markets.contains(&market.address)
+        }) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let a_options = self.leg_options(markets, self.token_a_mint);
+        let b_options = self.leg_options(markets, self.token_b_mint);
+        let mut best: Option<OrcaCompoundRoute> = None;
+        for a_option in a_options.iter() {
+            for b_option in b_options.iter() {
+                let route = route_split(pool, lp_supply, *a_option, *b_option, reward_amount)?;
+                match best {
+                    Some(best) if best.lp_amount >= route.lp_amount => (),
+                    _ => best = Some(route),
+                }
+            }
+        }
+        best.ok_or(ProgramError::InvalidArgument)
+    }
+    /// returns the ways rewards can be converted into `mint`
+    fn leg_options<'a>(&self, markets: &'a [OrcaMarketState], mint: Pubkey) -> Vec<LegOption<'a>> {
+        if mint == self.reward_token_mint {
+            return vec![LegOption::Keep];
+        }
+        markets
+            .iter()
+            .filter_map(|market| {
+                let (token_a_mint, token_b_mint) =
+                    (market.pool.token_a_mint, market.pool.token_b_mint);
+                if token_a_mint == self.reward_token_mint && token_b_mint == mint {
+                    Some(LegOption::Swap(market, true))
+                } else if token_b_mint == self.reward_token_mint && token_a_mint == mint {
+                    Some(LegOption::Swap(market, false))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// finds the split of `reward_amount` between the two legs yielding the most lp tokens.
+///
+/// the lp tokens obtainable from token a increase with the amount of rewards converted into
+/// token a, while the lp tokens obtainable from token b decrease, so the best split is found
+/// by binary searching for the point at which the two are balanced
+fn route_split(
+    pool: &OrcaMarketState,
+    lp_supply: u64,
+    a_option: LegOption,
+    b_option: LegOption,
+    reward_amount: u64,
+) -> std::result::Result<OrcaCompoundRoute, ProgramError> {
+    let (mut low, mut high) = (0_u64, reward_amount);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let (lp_from_a, lp_from_b) = simulate(
+            pool,
+            lp_supply,
+            a_option,
+            b_option,
+            mid,
+            reward_amount - mid,
+        )?
+        .1;
+        if lp_from_a >= lp_from_b {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    let mut best = simulate(
+        pool,
+        lp_supply,
+        a_option,
+        b_option,
+        low,
+        reward_amount - low,
+    )?
+    .0;
+    if low > 0 {
+        let other = simulate(
+            pool,
+            lp_supply,
+            a_option,
+            b_option,
+            low - 1,
+            reward_amount - low + 1,
+        )?
+        .0;
+        if other.lp_amount > best.lp_amount {
+            best = other;
+        }
+    }
+    Ok(best)
+}
+
+/// simulates converting `amount_to_a` rewards into token a, and `amount_to_b` rewards into
+/// token b, returning the resulting route along with the lp tokens obtainable from each token
+fn simulate(
+    pool: &OrcaMarketState,
+    lp_supply: u64,
+    a_option: LegOption,
+    b_option: LegOption,
+    amount_to_a: u64,
+    amount_to_b: u64,
+) -> std::result::Result<(OrcaCompoundRoute, (u64, u64)), ProgramError> {
+    let mut balance_a = pool.balance_a;
+    let mut balance_b = pool.balance_b;
+    let (token_a_leg, token_a_amount) =
+        convert(pool, &mut balance_a, &mut balance_b, a_option, amount_to_a)?;
+    let (token_b_leg, token_b_amount) =
+        convert(pool, &mut balance_a, &mut balance_b, b_option, amount_to_b)?;
+    let lp_from_a = lp_for_deposit(token_a_amount, balance_a, lp_supply)?;
+    let lp_from_b = lp_for_deposit(token_b_amount, balance_b, lp_supply)?;
+    let mut price_impact = Decimal::zero();
+    // both legs combined swap at most `amount_to_a + amount_to_b` rewards, so this can't overflow
+    let total_swapped = token_a_leg.map_or(0, |leg| leg.quote.amount_in)
+        + token_b_leg.map_or(0, |leg| leg.quote.amount_in);
+    if total_swapped > 0 {
+        for leg in [token_a_leg, token_b_leg].iter().flatten() {
+            price_impact = price_impact.try_add(
+                leg.quote
+                    .price_impact
+                    .try_mul(leg.quote.amount_in)?
+                    .try_div(total_swapped)?,
+            )?;
+        }
+    }
+    Ok((
+        OrcaCompoundRoute {
+            token_a_leg,
+            token_b_leg,
+            token_a_amount,
+            token_b_amount,
+            lp_amount: std::cmp::min(lp_from_a, lp_from_b),
+            price_impact,
+        },
+        (lp_from_a, lp_from_b),
+    ))
+}
+
+/// converts `amount` rewards using `option`, updating the balances of `pool` if the swap
+/// is made through the pool itself
+fn convert(
+    pool: &OrcaMarketState,
+    balance_a: &mut u64,
+    balance_b: &mut u64,
+    option: LegOption,
+    amount: u64,
+) -> std::result::Result<(Option<OrcaRouteLeg>, u64), ProgramError> {
+    let (market, a_to_b) = match option {
+        LegOption::Keep => return Ok((None, amount)),
+        LegOption::Swap(market, a_to_b) => (market, a_to_b),
+    };
+    let quote = if amount == 0 {
+        OrcaQuote::default()
+    } else if market.address == pool.address {
+        let quote = market.pool.quote(amount, *balance_a, *balance_b, a_to_b)?;
+        let (balance_in, balance_out) = if a_to_b {
+            (balance_a, balance_b)
+        } else {
+            (balance_b, balance_a)
+        };
+        *balance_in = balance_in
+            .checked_add(amount)
+            .ok_or(MathError::MathOverflow)?;
+        *balance_out = balance_out
+            .checked_sub(quote.amount_out)
+            .ok_or(MathError::MathOverflow)?;
+        quote
+    } else {
+        market
+            .pool
+            .quote(amount, market.balance_a, market.balance_b, a_to_b)?
+    };
+    let amount_out = quote.amount_out;
+    Ok((
+        Some(OrcaRouteLeg {
+            market: market.address,
+            a_to_b,
+            quote,
+        }),
+        amount_out,
+    ))
+}
+
+/// returns the lp tokens minted for depositing `amount` tokens into a pool holding `balance` tokens
+fn lp_for_deposit(
+    amount: u64,
+    balance: u64,
+    lp_supply: u64,
+) -> std::result::Result<u64, ProgramError> {
+    if balance == 0 {
+        return Err(MathError::MathOverflow.into());
+    }
+    let lp_amount = (amount as u128)
+        .checked_mul(lp_supply as u128)
+        .ok_or(MathError::MathOverflow)?
+        / balance as u128;
+    Ok(u64::try_from(lp_amount).map_err(|_| MathError::MathOverflow)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::orca_vault::OrcaVaultV1;
+    use tulipv2_sdk_common::pricing::orca::{OrcaCurve, OrcaFees};
+
+    fn fees() -> OrcaFees {
+        OrcaFees {
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            owner_trade_fee_numerator: 5,
+            owner_trade_fee_denominator: 10_000,
+            ..Default::default()
+        }
+    }
+
+    fn market(
+        token_a_mint: Pubkey,
+        token_b_mint: Pubkey,
+        balance_a: u64,
+        balance_b: u64,
+    ) -> OrcaMarketState {
+        OrcaMarketState {
+            address: Pubkey::new_unique(),
+            pool: OrcaPool {
+                token_a: Pubkey::new_unique(),
+                token_b: Pubkey::new_unique(),
+                pool_mint: Pubkey::new_unique(),
+                token_a_mint,
+                token_b_mint,
+                pool_fee_account: Pubkey::new_unique(),
+                fees: fees(),
+                curve: OrcaCurve::ConstantProduct,
+            },
+            balance_a,
+            balance_b,
+        }
+    }
+
+    fn vault_data(
+        pool: &OrcaMarketState,
+        reward_token_mint: Pubkey,
+        swap_markets: &[&OrcaMarketState],
+    ) -> OrcaVaultDataV1 {
+        let vault: OrcaVaultV1 = bytemuck::Zeroable::zeroed();
+        let mut data = vault.farm_data;
+        data.pool_swap_account = pool.address;
+        data.swap_pool_mint = pool.pool.pool_mint;
+        data.token_a_mint = pool.pool.token_a_mint;
+        data.token_b_mint = pool.pool.token_b_mint;
+        data.reward_token_mint = reward_token_mint;
+        for (idx, market) in swap_markets.iter().enumerate() {
+            data.swap_markets[idx] = market.address;
+        }
+        data
+    }
+
+    #[test]
+    fn test_route_compound_reward_is_token_a() {
+        // an ORCA-USDC vault, which can swap ORCA for USDC through its own pool,
+        // or a second pool with 10x the liquidity at the same price
+        let (orca, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pool = market(orca, usdc, 1_000_000_000, 2_000_000_000);
+        let mut deep = market(orca, usdc, 10_000_000_000, 20_000_000_000);
+        deep.pool.pool_mint = Pubkey::new_unique();
+        let data = vault_data(&pool, orca, &[&pool, &deep]);
+        let lp_supply = 1_000_000_000;
+        let reward_amount = 100_000_000;
+
+        let route = data
+            .route_compound(&pool, lp_supply, &[pool, deep], reward_amount)
+            .unwrap();
+        assert!(route.token_a_leg.is_none());
+        let leg = route.token_b_leg.unwrap();
+        assert_eq!(leg.market, deep.address);
+        assert!(leg.a_to_b);
+        assert_eq!(route.token_a_amount + leg.quote.amount_in, reward_amount);
+        assert_eq!(route.token_b_amount, leg.quote.amount_out);
+        assert_eq!(route.price_impact, leg.quote.price_impact);
+        // roughly half the rewards are swapped, and a 5% deposit mints roughly 5% of the supply
+        assert!(leg.quote.amount_in > 49_000_000 && leg.quote.amount_in < 51_000_000);
+        assert!(route.lp_amount > 49_000_000 && route.lp_amount < 50_000_000);
+        assert!(route.minimum_lp_amount(100).unwrap() < route.lp_amount);
+
+        // routing only through the vault's own pool yields fewer lp tokens
+        let shallow = data
+            .route_compound(&pool, lp_supply, &[pool], reward_amount)
+            .unwrap();
+        assert_eq!(shallow.token_b_leg.unwrap().market, pool.address);
+        assert!(shallow.lp_amount < route.lp_amount);
+        assert!(shallow.price_impact > route.price_impact);
+    }
+
+    #[test]
+    fn test_route_compound_two_legs() {
+        // a SOL-USDC vault earning ORCA, which must be swapped for both tokens
+        let (orca, sol, usdc) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let pool = market(sol, usdc, 10_000_000_000, 1_000_000_000_000);
+        // ORCA is worth $2 and SOL $100
+        let orca_sol = market(sol, orca, 1_000_000_000, 50_000_000_000);
+        let orca_usdc = market(orca, usdc, 50_000_000_000, 100_000_000_000);
+        let data = vault_data(&pool, orca, &[&orca_sol, &orca_usdc]);
+        let lp_supply = 100_000_000_000;
+
+        let route = data
+            .route_compound(&pool, lp_supply, &[orca_sol, orca_usdc], 1_000_000_000)
+            .unwrap();
+        let (a_leg, b_leg) = (route.token_a_leg.unwrap(), route.token_b_leg.unwrap());
+        assert_eq!(a_leg.market, orca_sol.address);
+        assert!(!a_leg.a_to_b);
+        assert_eq!(b_leg.market, orca_usdc.address);
+        assert!(b_leg.a_to_b);
+        assert_eq!(a_leg.quote.amount_in + b_leg.quote.amount_in, 1_000_000_000);
+        // $2000 of rewards mint roughly 0.1% of the $2m pool's lp supply
+        assert!(route.lp_amount > 98_000_000 && route.lp_amount < 100_000_000);
+        assert!(route.price_impact > Decimal::zero());
+
+        // without a market for SOL there is no route
+        assert_eq!(
+            data.route_compound(&pool, lp_supply, &[orca_usdc], 1_000_000_000)
+                .unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        // markets must be configured by the vault
+        let unknown = market(orca, sol, 1, 1);
+        assert_eq!(
+            data.route_compound(&pool, lp_supply, &[orca_sol, orca_usdc, unknown], 1)
+                .unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        assert_eq!(
+            data.route_compound(&orca_sol, lp_supply, &[orca_sol, orca_usdc], 1)
+                .unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! preflight checks used to determine whether a vault action will fail, before
+//! sending the transaction which performs the action
+use super::{tracking::LockStatus, vault_base::VaultBaseV1, Base};
+use tulipv2_sdk_common::traits::pausable::PausableAction;
+
+/// a reason for which a vault action will fail
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreflightFailure {
+    /// the vault has not been configured
+    NotConfigured,
+    DepositsPaused,
+    WithdrawsPaused,
+    CompoundPaused,
+    RebasePaused,
+    RebalancePaused,
+    /// the vault does not support compounding operations
+    CompoundUnsupported,
+    /// the deposit would exceed the vault's deposit cap
+    DepositCapExceeded {
+        cap: u64,
+        /// the amount by which the deposit exceeds the cap
+        exceeded_by: u64,
+    },
+    /// the deposit tracking account's shares are still locked
+    TrackingLocked {
+        unlocks_at: i64,
+        seconds_remaining: i64,
+    },
+    /// the action is not one that can be checked
+    UnknownAction,
+}
+
+/// returns the reasons `action` will fail against `vault`, an empty list indicates
+/// the action is expected to succeed.
+///
+/// `amount` is the amount of underlying being deposited, and is only checked
+/// against the deposit cap. `lock_status` is the status of the user's deposit
+/// tracking account, and is only checked when withdrawing
+pub fn preflight(
+    vault: &impl Base,
+    action: PausableAction,
+    amount: u64,
+    lock_status: Option<LockStatus>,
+) -> Vec<PreflightFailure> {
+    preflight_base(&vault.base(), action, amount, lock_status)
+}
+
+/// see [preflight]
+pub fn preflight_base(
+    base: &VaultBaseV1,
+    action: PausableAction,
+    amount: u64,
+    lock_status: Option<LockStatus>,
+) -> Vec<PreflightFailure> {
+    let mut failures = Vec::new();
+    if base.configured == 0 {
+        failures.push(PreflightFailure::NotConfigured);
+    }
+    let (deposit, withdraw, compound, rebase, rebalance) = match action {
+        PausableAction::Deposit => (true, false, false, false, false),
+        PausableAction::Withdrawal => (false, true, false, false, false),
+        PausableAction::DepositAndWithdrawal => (true, true, false, false, false),
+        PausableAction::Compound => (false, false, true, false, false),
+        PausableAction::Rebase => (false, false, false, true, false),
+        PausableAction::Rebalance => (false, false, false, false, true),
+        PausableAction::All => (true, true, true, true, true),
+        PausableAction::Unknown => {
+            failures.push(PreflightFailure::UnknownAction);
+            return failures;
+        }
+    };
+    if deposit {
+        if base.deposits_paused != 0 {
+            failures.push(PreflightFailure::DepositsPaused);
+        }
+        if base.total_deposited_balance_cap > 0 {
+            let exceeded_by = base
+                .total_deposited_balance
+                .saturating_add(amount)
+                .saturating_sub(base.total_deposited_balance_cap);
+            if exceeded_by > 0 {
+                failures.push(PreflightFailure::DepositCapExceeded {
+                    cap: base.total_deposited_balance_cap,
+                    exceeded_by,
+                });
+            }
+        }
+    }
+    if withdraw {
+        if base.withdraws_paused != 0 {
+            failures.push(PreflightFailure::WithdrawsPaused);
+        }
+        if let Some(lock_status) = lock_status {
+            if lock_status.locked {
+                failures.push(PreflightFailure::TrackingLocked {
+                    unlocks_at: lock_status.unlocks_at,
+                    seconds_remaining: lock_status.seconds_remaining,
+                });
+            }
+        }
+    }
+    if compound {
+        if base.compound_paused != 0 {
+            failures.push(PreflightFailure::CompoundPaused);
+        }
+        if base.supports_compound == 0 {
+            failures.push(PreflightFailure::CompoundUnsupported);
+        }
+    }
+    if rebase && base.rebase_paused != 0 {
+        failures.push(PreflightFailure::RebasePaused);
+    }
+    if rebalance && base.rebalance_paused != 0 {
+        failures.push(PreflightFailure::RebalancePaused);
+    }
+    failures
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::raydium_vault::RaydiumVaultV1;
+    use tulipv2_sdk_common::traits::pausable::Pausable;
+    #[test]
+    fn test_preflight() {
+        let mut vault = <RaydiumVaultV1 as bytemuck::Zeroable>::zeroed();
+        assert_eq!(
+            preflight(&vault, PausableAction::Unknown, 0, None),
+            vec![
+                PreflightFailure::NotConfigured,
+                PreflightFailure::UnknownAction
+            ]
+        );
+        vault.base.configured = 1;
+        vault.base.supports_compound = 1;
+        vault.base.total_deposited_balance = 900;
+        vault.base.total_deposited_balance_cap = 1_000;
+        assert!(preflight(&vault, PausableAction::All, 100, None).is_empty());
+        assert_eq!(
+            preflight(&vault, PausableAction::Deposit, 150, None),
+            vec![PreflightFailure::DepositCapExceeded {
+                cap: 1_000,
+                exceeded_by: 50
+            }]
+        );
+        vault.base.pause(PausableAction::Withdrawal);
+        // the deposit amount and lock status are ignored for compounds
+        assert!(preflight(&vault, PausableAction::Compound, 150, None).is_empty());
+        let lock_status = LockStatus {
+            locked: true,
+            unlocks_at: 10,
+            seconds_remaining: 5,
+        };
+        assert_eq!(
+            preflight(
+                &vault,
+                PausableAction::DepositAndWithdrawal,
+                0,
+                Some(lock_status)
+            ),
+            vec![
+                PreflightFailure::WithdrawsPaused,
+                PreflightFailure::TrackingLocked {
+                    unlocks_at: 10,
+                    seconds_remaining: 5
+                }
+            ]
+        );
+        vault.base.pause(PausableAction::All);
+        vault.base.supports_compound = 0;
+        assert_eq!(
+            preflight(&vault, PausableAction::Compound, 0, None),
+            vec![
+                PreflightFailure::CompoundPaused,
+                PreflightFailure::CompoundUnsupported
+            ]
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! decoding of the quarry mine program's `Quarry` and `Miner` accounts, and the
+//! rewards-per-token math used to estimate the pending rewards of quarry vaults.
+//!
+//! the sunny quarry program is a fork of quarry mine, and uses the same account layouts
+use super::quarry_vault::{QuarryVariant, QuarryVaultV1, SunnyConfigurationDataV1};
+use super::{account_discriminator, read_pubkey, read_u128, read_u64};
+use anchor_lang::prelude::*;
+use tulipv2_sdk_common::math::error::MathError;
+use tulipv2_sdk_common::math::uint::U256;
+
+/// number of seconds quarry uses for a year when calculating rewards
+pub const SECONDS_PER_YEAR: u128 = 86_400 * 365;
+/// precision multiplier used by quarry for rewards per token
+pub const PRECISION_MULTIPLIER: u128 = 1_000_000_000_000_000;
+/// claim fees are denominated in thousandths of a basis point
+pub const MILLIBPS_DENOMINATOR: u64 = 10_000_000;
+/// size of a quarry account, including the discriminator
+pub const QUARRY_ACCOUNT_SIZE: usize = 140;
+/// size of a miner account, including the discriminator
+pub const MINER_ACCOUNT_SIZE: usize = 145;
+
+/// a quarry mine `Quarry` account, which distributes rewards to miners
+/// staking the quarry's token
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quarry {
+    pub rewarder_key: Pubkey,
+    pub token_mint_key: Pubkey,
+    pub bump: u8,
+    pub index: u16,
+    pub token_mint_decimals: u8,
+    /// timestamp after which no more rewards are distributed
+    pub famine_ts: i64,
+    pub last_update_ts: i64,
+    pub rewards_per_token_stored: u128,
+    pub annual_rewards_rate: u64,
+    pub rewards_share: u64,
+    pub total_tokens_deposited: u64,
+    pub num_miners: u64,
+}
+
+/// a quarry mine `Miner` account, which tracks the stake and rewards of a
+/// single authority within a quarry
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Miner {
+    pub quarry_key: Pubkey,
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub token_vault_key: Pubkey,
+    pub rewards_earned: u64,
+    pub rewards_per_token_paid: u128,
+    pub balance: u64,
+    pub index: u64,
+}
+
+/// the estimated rewards of a quarry vault which have yet to be claimed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuarryRewardsEstimate {
+    /// rewards owed to the vault's miner after claim fees, denominated in `reward_token_mint`.
+    /// for saber vaults this is the amount of IOU tokens
+    pub pending: u64,
+    /// the amount of rewards `pending` can be converted into. saber IOUs are redeemed 1:1
+    /// for SBR, and all other variants receive the reward token directly
+    pub redeemable: u64,
+    /// for sunny vaults, the SBR IOU rewards owed by the underlying saber quarry to the
+    /// miner sunny created on behalf of the vault. always 0 for other variants
+    pub nested_pending: u64,
+}
+
+impl Quarry {
+    /// decodes a quarry account, validating the account discriminator
+    pub fn try_from_slice(data: &[u8]) -> std::result::Result<Self, ProgramError> {
+        check_account(data, "Quarry", QUARRY_ACCOUNT_SIZE)?;
+        Ok(Self {
+            rewarder_key: read_pubkey(data, 8),
+            token_mint_key: read_pubkey(data, 40),
+            bump: data[72],
+            index: u16::from_le_bytes([data[73], data[74]]),
+            token_mint_decimals: data[75],
+            famine_ts: read_u64(data, 76) as i64,
+            last_update_ts: read_u64(data, 84) as i64,
+            rewards_per_token_stored: read_u128(data, 92),
+            annual_rewards_rate: read_u64(data, 108),
+            rewards_share: read_u64(data, 116),
+            total_tokens_deposited: read_u64(data, 124),
+            num_miners: read_u64(data, 132),
+        })
+    }
+    /// returns the rewards per token as of `current_ts`, including rewards
+    /// accrued since the quarry was last updated
+    pub fn rewards_per_token(&self, current_ts: i64) -> std::result::Result<u128, ProgramError> {
+        if self.total_tokens_deposited == 0 || self.annual_rewards_rate == 0 {
+            return Ok(self.rewards_per_token_stored);
+        }
+        let last_time_reward_applicable = std::cmp::min(current_ts, self.famine_ts);
+        let time_worked = last_time_reward_applicable
+            .checked_sub(self.last_update_ts)
+            .ok_or(MathError::MathOverflow)?
+            .max(0) as u128;
+        let reward = U256::from(time_worked)
+            .checked_mul(U256::from(PRECISION_MULTIPLIER))
+            .ok_or(MathError::MathOverflow)?
+            .checked_mul(U256::from(self.annual_rewards_rate))
+            .ok_or(MathError::MathOverflow)?
+            / U256::from(SECONDS_PER_YEAR)
+            / U256::from(self.total_tokens_deposited);
+        let reward = u128::try_from(reward).map_err(|_| MathError::MathOverflow)?;
+        Ok(self
+            .rewards_per_token_stored
+            .checked_add(reward)
+            .ok_or(MathError::MathOverflow)?)
+    }
+}
+
+impl Miner {
+    /// decodes a miner account, validating the account discriminator
+    pub fn try_from_slice(data: &[u8]) -> std::result::Result<Self, ProgramError> {
+        check_account(data, "Miner", MINER_ACCOUNT_SIZE)?;
+        Ok(Self {
+            quarry_key: read_pubkey(data, 8),
+            authority: read_pubkey(data, 40),
+            bump: data[72],
+            token_vault_key: read_pubkey(data, 73),
+            rewards_earned: read_u64(data, 105),
+            rewards_per_token_paid: read_u128(data, 113),
+            balance: read_u64(data, 129),
+            index: read_u64(data, 137),
+        })
+    }
+    /// returns the total rewards earned by the miner as of `current_ts`, before claim fees
+    pub fn pending_rewards(
+        &self,
+        quarry: &Quarry,
+        current_ts: i64,
+    ) -> std::result::Result<u64, ProgramError> {
+        let net_new_rewards = quarry
+            .rewards_per_token(current_ts)?
+            .checked_sub(self.rewards_per_token_paid)
+            .ok_or(MathError::MathOverflow)?;
+        let earned = U256::from(self.balance)
+            .checked_mul(U256::from(net_new_rewards))
+            .ok_or(MathError::MathOverflow)?
+            / U256::from(PRECISION_MULTIPLIER);
+        let earned = u64::try_from(earned).map_err(|_| MathError::MathOverflow)?;
+        Ok(self
+            .rewards_earned
+            .checked_add(earned)
+            .ok_or(MathError::MathOverflow)?)
+    }
+}
+
+impl QuarryVaultV1 {
+    /// estimates the unclaimed rewards of a vanilla or saber vault, where `quarry` and `miner`
+    /// are the accounts at `self.quarry` and `self.miner`.
+    ///
+    /// `claim_fee_millibps` is the claim fee charged by the rewarder, ie: 100_000 for 1%
+    pub fn estimate_rewards(
+        &self,
+        quarry: &Quarry,
+        miner: &Miner,
+        current_ts: i64,
+        claim_fee_millibps: u64,
+    ) -> std::result::Result<QuarryRewardsEstimate, ProgramError> {
+        match self.variant {
+            QuarryVariant::Vanilla | QuarryVariant::Saber => (),
+            _ => return Err(ProgramError::InvalidArgument),
+        }
+        if miner.quarry_key != self.quarry || miner.token_vault_key != self.miner_token_account {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let pending = apply_claim_fee(
+            miner.pending_rewards(quarry, current_ts)?,
+            claim_fee_millibps,
+        )?;
+        Ok(QuarryRewardsEstimate {
+            pending,
+            // saber IOUs redeem 1:1 for SBR
+            redeemable: pending,
+            nested_pending: 0,
+        })
+    }
+    /// estimates the unclaimed rewards of a sunny vault. sunny runs a quarry on top of saber's quarry,
+    /// so rewards are owed by two quarries:
+    ///
+    /// * `sunny_quarry` / `vault_miner` - the accounts at `self.quarry` and `self.miner` which emit SUNNY
+    /// * `saber_quarry` / `sunny_miner` - the saber quarry, and the miner at `config.sunny_miner` which emits SBR IOUs
+    ///
+    /// each rewarder charges its own claim fee, `sunny_claim_fee_millibps` for the sunny
+    /// rewarder and `saber_claim_fee_millibps` for the saber rewarder
+    #[allow(clippy::too_many_arguments)]
+    pub fn estimate_sunny_rewards(
+        &self,
+        config: &SunnyConfigurationDataV1,
+        sunny_quarry: &Quarry,
+        vault_miner: &Miner,
+        saber_quarry: &Quarry,
+        sunny_miner: &Miner,
+        current_ts: i64,
+        sunny_claim_fee_millibps: u64,
+        saber_claim_fee_millibps: u64,
+    ) -> std::result::Result<QuarryRewardsEstimate, ProgramError> {
+        if self.variant != QuarryVariant::Sunny {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if vault_miner.quarry_key != self.quarry
+            || vault_miner.authority != self.base.pda
+            || vault_miner.token_vault_key != self.miner_token_account
+            || sunny_miner.authority != config.sunny_tvault
+            || sunny_miner.token_vault_key != config.sunny_miner_token_account
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let pending = apply_claim_fee(
+            vault_miner.pending_rewards(sunny_quarry, current_ts)?,
+            sunny_claim_fee_millibps,
+        )?;
+        let nested_pending = apply_claim_fee(
+            sunny_miner.pending_rewards(saber_quarry, current_ts)?,
+            saber_claim_fee_millibps,
+        )?;
+        Ok(QuarryRewardsEstimate {
+            pending,
+            redeemable: pending,
+            nested_pending,
+        })
+    }
+}
+
+fn apply_claim_fee(amount: u64, claim_fee_millibps: u64) -> std::result::Result<u64, ProgramError> {
+    let fee = (amount as u128)
+        .checked_mul(claim_fee_millibps as u128)
+        .ok_or(MathError::MathOverflow)?
+        / MILLIBPS_DENOMINATOR as u128;
+    Ok(amount
+        .checked_sub(u64::try_from(fee).map_err(|_| MathError::MathOverflow)?)
+        .ok_or(MathError::MathOverflow)?)
+}
+
+fn check_account(
+    data: &[u8],
+    account_name: &str,
+    size: usize,
+) -> std::result::Result<(), ProgramError> {
+    if data.len() < size {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    if data[0..8] != account_discriminator(account_name) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn quarry_data(quarry: &Quarry) -> Vec<u8> {
+        let mut data = Vec::with_capacity(QUARRY_ACCOUNT_SIZE);
+        data.extend_from_slice(&account_discriminator("Quarry"));
+        data.extend_from_slice(quarry.rewarder_key.as_ref());
+        data.extend_from_slice(quarry.token_mint_key.as_ref());
+        data.push(quarry.bump);
+        data.extend_from_slice(&quarry.index.to_le_bytes());
+        data.push(quarry.token_mint_decimals);
+        data.extend_from_slice(&quarry.famine_ts.to_le_bytes());
+        data.extend_from_slice(&quarry.last_update_ts.to_le_bytes());
+        data.extend_from_slice(&quarry.rewards_per_token_stored.to_le_bytes());
+        data.extend_from_slice(&quarry.annual_rewards_rate.to_le_bytes());
+        data.extend_from_slice(&quarry.rewards_share.to_le_bytes());
+        data.extend_from_slice(&quarry.total_tokens_deposited.to_le_bytes());
+        data.extend_from_slice(&quarry.num_miners.to_le_bytes());
+        data
+    }
+
+    fn miner_data(miner: &Miner) -> Vec<u8> {
+        let mut data = Vec::with_capacity(MINER_ACCOUNT_SIZE);
+        data.extend_from_slice(&account_discriminator("Miner"));
+        data.extend_from_slice(miner.quarry_key.as_ref());
+        data.extend_from_slice(miner.authority.as_ref());
+        data.push(miner.bump);
+        data.extend_from_slice(miner.token_vault_key.as_ref());
+        data.extend_from_slice(&miner.rewards_earned.to_le_bytes());
+        data.extend_from_slice(&miner.rewards_per_token_paid.to_le_bytes());
+        data.extend_from_slice(&miner.balance.to_le_bytes());
+        data.extend_from_slice(&miner.index.to_le_bytes());
+        data
+    }
+
+    fn quarry() -> Quarry {
+        Quarry {
+            rewarder_key: Pubkey::new_unique(),
+            token_mint_key: Pubkey::new_unique(),
+            bump: 254,
+            index: 3,
+            token_mint_decimals: 6,
+            famine_ts: i64::MAX,
+            last_update_ts: 1_000,
+            rewards_per_token_stored: 0,
+            // 1 token per second
+            annual_rewards_rate: SECONDS_PER_YEAR as u64 * 1_000_000,
+            rewards_share: 1,
+            total_tokens_deposited: 1_000_000_000,
+            num_miners: 2,
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let quarry = quarry();
+        let data = quarry_data(&quarry);
+        assert_eq!(data.len(), QUARRY_ACCOUNT_SIZE);
+        assert_eq!(Quarry::try_from_slice(&data).unwrap(), quarry);
+
+        let miner = Miner {
+            quarry_key: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            bump: 255,
+            token_vault_key: Pubkey::new_unique(),
+            rewards_earned: 42,
+            rewards_per_token_paid: 69,
+            balance: 420,
+            index: 1,
+        };
+        let data = miner_data(&miner);
+        assert_eq!(data.len(), MINER_ACCOUNT_SIZE);
+        assert_eq!(Miner::try_from_slice(&data).unwrap(), miner);
+        // a miner is not a quarry
+        assert!(Quarry::try_from_slice(&data).is_err());
+        assert!(Miner::try_from_slice(&data[0..100]).is_err());
+    }
+
+    #[test]
+    fn test_pending_rewards() {
+        let quarry = quarry();
+        let miner = Miner {
+            rewards_earned: 5,
+            // a quarter of the quarry
+            balance: 250_000_000,
+            ..Default::default()
+        };
+        // 100 seconds elapsed, 100 tokens emitted, a quarter goes to the miner
+        assert_eq!(miner.pending_rewards(&quarry, 1_100).unwrap(), 25_000_005);
+        // no rewards after the famine
+        let famine = Quarry {
+            famine_ts: 1_050,
+            ..quarry
+        };
+        assert_eq!(miner.pending_rewards(&famine, 1_100).unwrap(), 12_500_005);
+        // time before the last update earns nothing new
+        assert_eq!(miner.pending_rewards(&quarry, 500).unwrap(), 5);
+        assert_eq!(apply_claim_fee(25_000_000, 100_000).unwrap(), 24_750_000);
+    }
+
+    #[test]
+    fn test_estimate_rewards() {
+        let quarry = quarry();
+        let mut vault = <QuarryVaultV1 as bytemuck::Zeroable>::zeroed();
+        vault.quarry = Pubkey::new_unique();
+        vault.miner_token_account = Pubkey::new_unique();
+        vault.variant = QuarryVariant::Saber;
+        let miner = Miner {
+            quarry_key: vault.quarry,
+            token_vault_key: vault.miner_token_account,
+            balance: 250_000_000,
+            ..Default::default()
+        };
+        let estimate = vault.estimate_rewards(&quarry, &miner, 1_100, 0).unwrap();
+        assert_eq!(estimate.pending, 25_000_000);
+        assert_eq!(estimate.redeemable, 25_000_000);
+        assert_eq!(estimate.nested_pending, 0);
+
+        let wrong_miner = Miner {
+            quarry_key: Pubkey::new_unique(),
+            ..miner
+        };
+        assert!(vault
+            .estimate_rewards(&quarry, &wrong_miner, 1_100, 0)
+            .is_err());
+        vault.variant = QuarryVariant::Sunny;
+        assert!(vault.estimate_rewards(&quarry, &miner, 1_100, 0).is_err());
+    }
+
+    #[test]
+    fn test_estimate_sunny_rewards() {
+        let mut vault = <QuarryVaultV1 as bytemuck::Zeroable>::zeroed();
+        vault.base.pda = Pubkey::new_unique();
+        vault.quarry = Pubkey::new_unique();
+        vault.miner_token_account = Pubkey::new_unique();
+        vault.variant = QuarryVariant::Sunny;
+        let mut config: SunnyConfigurationDataV1 =
+            AnchorDeserialize::deserialize(&mut &[0_u8; 512][..]).unwrap();
+        config.sunny_tvault = Pubkey::new_unique();
+        config.sunny_miner_token_account = Pubkey::new_unique();
+
+        // 1 SUNNY per second, the vault holds a quarter of the sunny quarry
+        let sunny_quarry = quarry();
+        let vault_miner = Miner {
+            quarry_key: vault.quarry,
+            authority: vault.base.pda,
+            token_vault_key: vault.miner_token_account,
+            rewards_earned: 1_000,
+            balance: 250_000_000,
+            ..Default::default()
+        };
+        // 2 SBR IOUs per second, sunny holds half of the saber quarry, and has
+        // already been paid for the first 10 seconds
+        let saber_quarry = Quarry {
+            annual_rewards_rate: SECONDS_PER_YEAR as u64 * 2_000_000,
+            ..quarry()
+        };
+        let sunny_miner = Miner {
+            quarry_key: Pubkey::new_unique(),
+            authority: config.sunny_tvault,
+            token_vault_key: config.sunny_miner_token_account,
+            rewards_per_token_paid: PRECISION_MULTIPLIER / 50,
+            balance: 500_000_000,
+            ..Default::default()
+        };
+        let estimate = vault
+            .estimate_sunny_rewards(
+                &config,
+                &sunny_quarry,
+                &vault_miner,
+                &saber_quarry,
+                &sunny_miner,
+                1_100,
+                // 1% sunny claim fee, 0.5% saber claim fee
+                100_000,
+                50_000,
+            )
+            .unwrap();
+        // 25_001_000 earned, less 1%
+        assert_eq!(estimate.pending, 24_750_990);
+        assert_eq!(estimate.redeemable, 24_750_990);
+        // 200 SBR emitted, 180 after the first 10 seconds, half goes to sunny, less 0.5%
+        assert_eq!(estimate.nested_pending, 89_550_000);
+
+        // a miner belonging to another vault is rejected
+        let other_vault_miner = Miner {
+            authority: Pubkey::new_unique(),
+            ..vault_miner
+        };
+        assert!(vault
+            .estimate_sunny_rewards(
+                &config,
+                &sunny_quarry,
+                &other_vault_miner,
+                &saber_quarry,
+                &sunny_miner,
+                1_100,
+                0,
+                0,
+            )
+            .is_err());
+        let wrong_sunny_miner = Miner {
+            authority: Pubkey::new_unique(),
+            ..sunny_miner
+        };
+        assert!(vault
+            .estimate_sunny_rewards(
+                &config,
+                &sunny_quarry,
+                &vault_miner,
+                &saber_quarry,
+                &wrong_sunny_miner,
+                1_100,
+                0,
+                0,
+            )
+            .is_err());
+        vault.variant = QuarryVariant::Saber;
+        assert!(vault
+            .estimate_sunny_rewards(
+                &config,
+                &sunny_quarry,
+                &vault_miner,
+                &saber_quarry,
+                &sunny_miner,
+                1_100,
+                0,
+                0,
+            )
+            .is_err());
+    }
+}
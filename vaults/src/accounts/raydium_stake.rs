@@ -0,0 +1,475 @@
+//! decoding of the raydium staking program's pool and user stake info accounts, and the
+//! reward math used to estimate the pending rewards of raydium vaults.
+//!
+//! both the dual reward (v4) and latest (v5) staking programs share the same pool layout,
+//! however v5 user stake info accounts track reward debt using u128's, and v5 pools use
+//! a larger precision multiplier when tracking rewards per share
+use super::raydium_vault::RaydiumVaultV1;
+use super::{read_pubkey, read_u128, read_u64};
+use anchor_lang::prelude::*;
+use tulipv2_sdk_common::config::{RAYDIUM_STAKE_V4, RAYDIUM_STAKE_V5};
+use tulipv2_sdk_common::math::error::MathError;
+use tulipv2_sdk_common::math::uint::U256;
+
+/// size of a v4 or v5 staking pool account
+pub const STAKE_POOL_ACCOUNT_SIZE: usize = 224;
+/// size of a v4 user stake info account
+pub const STAKE_INFO_V4_ACCOUNT_SIZE: usize = 96;
+/// size of a v5 user stake info account, including trailing padding
+pub const STAKE_INFO_V5_ACCOUNT_SIZE: usize = 248;
+
+/// the version of the raydium staking program managing a farm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaydiumStakeVersion {
+    V4,
+    V5,
+}
+
+impl RaydiumStakeVersion {
+    /// returns the version of the given staking program, or None if the program
+    /// is not a dual reward staking program
+    pub fn from_program(program: &Pubkey) -> Option<Self> {
+        if program.eq(&RAYDIUM_STAKE_V4) {
+            Some(Self::V4)
+        } else if program.eq(&RAYDIUM_STAKE_V5) {
+            Some(Self::V5)
+        } else {
+            None
+        }
+    }
+    /// returns the multiplier used when tracking rewards per share
+    pub fn precision(&self) -> u128 {
+        match self {
+            Self::V4 => 1_000_000_000,
+            Self::V5 => 1_000_000_000_000_000,
+        }
+    }
+}
+
+/// a raydium staking pool, which distributes reward A and reward B to users
+/// staking lp tokens in `lp_vault`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RaydiumStakePool {
+    pub version: RaydiumStakeVersion,
+    pub state: u64,
+    pub nonce: u64,
+    pub lp_vault: Pubkey,
+    pub reward_vault_a: Pubkey,
+    pub total_reward_a: u64,
+    pub per_share_reward_a: u128,
+    pub per_slot_reward_a: u64,
+    pub option: u8,
+    pub reward_vault_b: Pubkey,
+    pub total_reward_b: u64,
+    pub per_share_reward_b: u128,
+    pub per_slot_reward_b: u64,
+    /// slot at which rewards per share were last updated
+    pub last_slot: u64,
+    pub owner: Pubkey,
+}
+
+/// a raydium user stake info account, which tracks the stake and reward debt of a
+/// single owner within a staking pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RaydiumStakeInfo {
+    pub version: RaydiumStakeVersion,
+    pub state: u64,
+    pub pool_id: Pubkey,
+    pub owner: Pubkey,
+    pub deposited: u64,
+    pub reward_debt_a: u128,
+    pub reward_debt_b: u128,
+}
+
+/// the estimated rewards of a raydium vault which have yet to be harvested
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RaydiumRewardsEstimate {
+    /// pending rewards denominated in the pool's reward A token
+    pub pending_a: u64,
+    /// pending rewards denominated in the pool's reward B token, always 0 for vaults
+    /// which do not emit dual rewards
+    pub pending_b: u64,
+}
+
+impl RaydiumStakePool {
+    /// decodes a staking pool account owned by the given version of the staking program
+    pub fn try_from_slice(
+        data: &[u8],
+        version: RaydiumStakeVersion,
+    ) -> std::result::Result<Self, ProgramError> {
+        if data.len() < STAKE_POOL_ACCOUNT_SIZE {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        Ok(Self {
+            version,
+            state: read_u64(data, 0),
+            nonce: read_u64(data, 8),
+            lp_vault: read_pubkey(data, 16),
+            reward_vault_a: read_pubkey(data, 48),
+            total_reward_a: read_u64(data, 80),
+            per_share_reward_a: read_u128(data, 88),
+            per_slot_reward_a: read_u64(data, 104),
+            option: data[112],
+            reward_vault_b: read_pubkey(data, 113),
+            // 7 bytes of padding
+            total_reward_b: read_u64(data, 152),
+            per_share_reward_b: read_u128(data, 160),
+            per_slot_reward_b: read_u64(data, 176),
+            last_slot: read_u64(data, 184),
+            owner: read_pubkey(data, 192),
+        })
+    }
+    /// returns the reward A and reward B per share as of `current_slot`, including rewards
+    /// accrued since the pool was last updated. `lp_vault_balance` is the amount of lp
+    /// tokens held by `lp_vault`
+    pub fn rewards_per_share(
+        &self,
+        lp_vault_balance: u64,
+        current_slot: u64,
+    ) -> std::result::Result<(u128, u128), ProgramError> {
+        if lp_vault_balance == 0 || current_slot <= self.last_slot {
+            return Ok((self.per_share_reward_a, self.per_share_reward_b));
+        }
+        let slots = current_slot - self.last_slot;
+        Ok((
+            self.accrue(
+                self.per_share_reward_a,
+                self.per_slot_reward_a,
+                slots,
+                lp_vault_balance,
+            )?,
+            self.accrue(
+                self.per_share_reward_b,
+                self.per_slot_reward_b,
+                slots,
+                lp_vault_balance,
+            )?,
+        ))
+    }
+    fn accrue(
+        &self,
+        per_share_reward: u128,
+        per_slot_reward: u64,
+        slots: u64,
+        lp_vault_balance: u64,
+    ) -> std::result::Result<u128, ProgramError> {
+        let reward = U256::from(per_slot_reward)
+            .checked_mul(U256::from(slots))
+            .ok_or(MathError::MathOverflow)?
+            .checked_mul(U256::from(self.version.precision()))
+            .ok_or(MathError::MathOverflow)?
+            / U256::from(lp_vault_balance);
+        let reward = u128::try_from(reward).map_err(|_| MathError::MathOverflow)?;
+        Ok(per_share_reward
+            .checked_add(reward)
+            .ok_or(MathError::MathOverflow)?)
+    }
+}
+
+impl RaydiumStakeInfo {
+    /// decodes a user stake info account owned by the given version of the staking program
+    pub fn try_from_slice(
+        data: &[u8],
+        version: RaydiumStakeVersion,
+    ) -> std::result::Result<Self, ProgramError> {
+        let (reward_debt_a, reward_debt_b) = match version {
+            RaydiumStakeVersion::V4 if data.len() >= STAKE_INFO_V4_ACCOUNT_SIZE => {
+                (read_u64(data, 80) as u128, read_u64(data, 88) as u128)
+            }
+            RaydiumStakeVersion::V5 if data.len() >= STAKE_INFO_V5_ACCOUNT_SIZE => {
+                (read_u128(data, 80), read_u128(data, 96))
+            }
+            _ => return Err(ProgramError::AccountDataTooSmall),
+        };
+        Ok(Self {
+            version,
+            state: read_u64(data, 0),
+            pool_id: read_pubkey(data, 8),
+            owner: read_pubkey(data, 40),
+            deposited: read_u64(data, 72),
+            reward_debt_a,
+            reward_debt_b,
+        })
+    }
+    /// returns the reward A and reward B owed to the stake info's owner as of `current_slot`
+    pub fn pending_rewards(
+        &self,
+        pool: &RaydiumStakePool,
+        lp_vault_balance: u64,
+        current_slot: u64,
+    ) -> std::result::Result<(u64, u64), ProgramError> {
+        if self.version != pool.version {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (per_share_reward_a, per_share_reward_b) =
+            pool.rewards_per_share(lp_vault_balance, current_slot)?;
+        Ok((
+            self.pending(per_share_reward_a, self.reward_debt_a)?,
+            self.pending(per_share_reward_b, self.reward_debt_b)?,
+        ))
+    }
+    fn pending(
+        &self,
+        per_share_reward: u128,
+        reward_debt: u128,
+    ) -> std::result::Result<u64, ProgramError> {
+        let accumulated = U256::from(self.deposited)
+            .checked_mul(U256::from(per_share_reward))
+            .ok_or(MathError::MathOverflow)?
+            / U256::from(self.version.precision());
+        let pending = accumulated
+            .checked_sub(U256::from(reward_debt))
+            .ok_or(MathError::MathOverflow)?;
+        Ok(u64::try_from(pending).map_err(|_| MathError::MathOverflow)?)
+    }
+}
+
+impl RaydiumVaultV1 {
+    /// returns the version of the staking program used by the vault, or None if the
+    /// vault does not use a dual reward staking program
+    pub fn stake_version(&self) -> Option<RaydiumStakeVersion> {
+        RaydiumStakeVersion::from_program(&self.raydium_stake_program)
+    }
+    /// returns the address of the stake info account used by the vault, which for v5
+    /// pools is the associated stake info account
+    pub fn stake_info_address(&self) -> Option<Pubkey> {
+        match self.stake_version()? {
+            RaydiumStakeVersion::V4 => Some(self.vault_stake_info_account),
+            RaydiumStakeVersion::V5 => Some(self.associated_stake_info_address),
+        }
+    }
+    /// estimates the unharvested rewards of the vault as of `current_slot`, where `pool` is the
+    /// account at `self.raydium_pool_id`, `stake_info` is the account at `self.stake_info_address()`
+    /// and `lp_vault_balance` is the amount of lp tokens held by `pool.lp_vault`
+    pub fn estimate_rewards(
+        &self,
+        pool: &RaydiumStakePool,
+        stake_info: &RaydiumStakeInfo,
+        lp_vault_balance: u64,
+        current_slot: u64,
+    ) -> std::result::Result<RaydiumRewardsEstimate, ProgramError> {
+        if self.stake_version() != Some(pool.version) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if stake_info.pool_id != self.raydium_pool_id
+            || pool.reward_vault_a != self.raydium_pool_reward_a_token_account
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (pending_a, pending_b) =
+            stake_info.pending_rewards(pool, lp_vault_balance, current_slot)?;
+        Ok(RaydiumRewardsEstimate {
+            pending_a,
+            pending_b: if self.dual_rewards == 0 { 0 } else { pending_b },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pool_data(pool: &RaydiumStakePool) -> Vec<u8> {
+        let mut data = Vec::with_capacity(STAKE_POOL_ACCOUNT_SIZE);
+        data.extend_from_slice(&pool.state.to_le_bytes());
+        data.extend_from_slice(&pool.nonce.to_le_bytes());
+        data.extend_from_slice(pool.lp_vault.as_ref());
+        data.extend_from_slice(pool.reward_vault_a.as_ref());
+        data.extend_from_slice(&pool.total_reward_a.to_le_bytes());
+        data.extend_from_slice(&pool.per_share_reward_a.to_le_bytes());
+        data.extend_from_slice(&pool.per_slot_reward_a.to_le_bytes());
+        data.push(pool.option);
+        data.extend_from_slice(pool.reward_vault_b.as_ref());
+        data.extend_from_slice(&[0_u8; 7]);
+        data.extend_from_slice(&pool.total_reward_b.to_le_bytes());
+        data.extend_from_slice(&pool.per_share_reward_b.to_le_bytes());
+        data.extend_from_slice(&pool.per_slot_reward_b.to_le_bytes());
+        data.extend_from_slice(&pool.last_slot.to_le_bytes());
+        data.extend_from_slice(pool.owner.as_ref());
+        data
+    }
+
+    fn stake_info_data(stake_info: &RaydiumStakeInfo) -> Vec<u8> {
+        let mut data = Vec::with_capacity(STAKE_INFO_V5_ACCOUNT_SIZE);
+        data.extend_from_slice(&stake_info.state.to_le_bytes());
+        data.extend_from_slice(stake_info.pool_id.as_ref());
+        data.extend_from_slice(stake_info.owner.as_ref());
+        data.extend_from_slice(&stake_info.deposited.to_le_bytes());
+        match stake_info.version {
+            RaydiumStakeVersion::V4 => {
+                data.extend_from_slice(&(stake_info.reward_debt_a as u64).to_le_bytes());
+                data.extend_from_slice(&(stake_info.reward_debt_b as u64).to_le_bytes());
+            }
+            RaydiumStakeVersion::V5 => {
+                data.extend_from_slice(&stake_info.reward_debt_a.to_le_bytes());
+                data.extend_from_slice(&stake_info.reward_debt_b.to_le_bytes());
+                data.resize(STAKE_INFO_V5_ACCOUNT_SIZE, 0);
+            }
+        }
+        data
+    }
+
+    fn pool(version: RaydiumStakeVersion) -> RaydiumStakePool {
+        RaydiumStakePool {
+            version,
+            state: 1,
+            nonce: 254,
+            lp_vault: Pubkey::new_unique(),
+            reward_vault_a: Pubkey::new_unique(),
+            total_reward_a: 1_000_000,
+            per_share_reward_a: 2 * version.precision(),
+            per_slot_reward_a: 100,
+            option: 1,
+            reward_vault_b: Pubkey::new_unique(),
+            total_reward_b: 2_000_000,
+            per_share_reward_b: version.precision() / 2,
+            per_slot_reward_b: 30,
+            last_slot: 1_000,
+            owner: Pubkey::new_unique(),
+        }
+    }
+
+    fn vault(pool_id: Pubkey, pool: &RaydiumStakePool, program: Pubkey) -> RaydiumVaultV1 {
+        let mut vault: RaydiumVaultV1 = bytemuck::Zeroable::zeroed();
+        vault.raydium_pool_id = pool_id;
+        vault.raydium_pool_reward_a_token_account = pool.reward_vault_a;
+        vault.raydium_stake_program = program;
+        vault.dual_rewards = 1;
+        vault.vault_stake_info_account = Pubkey::new_unique();
+        vault.associated_stake_info_address = Pubkey::new_unique();
+        vault
+    }
+
+    #[test]
+    fn test_decode_stake_accounts() {
+        for version in [RaydiumStakeVersion::V4, RaydiumStakeVersion::V5] {
+            let pool = pool(version);
+            let data = pool_data(&pool);
+            assert_eq!(data.len(), STAKE_POOL_ACCOUNT_SIZE);
+            assert_eq!(
+                RaydiumStakePool::try_from_slice(&data, version).unwrap(),
+                pool
+            );
+            assert_eq!(
+                RaydiumStakePool::try_from_slice(&data[..data.len() - 1], version).unwrap_err(),
+                ProgramError::AccountDataTooSmall
+            );
+
+            let stake_info = RaydiumStakeInfo {
+                version,
+                state: 1,
+                pool_id: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                deposited: 500,
+                reward_debt_a: 900,
+                reward_debt_b: 200,
+            };
+            let data = stake_info_data(&stake_info);
+            assert_eq!(
+                RaydiumStakeInfo::try_from_slice(&data, version).unwrap(),
+                stake_info
+            );
+            assert!(RaydiumStakeInfo::try_from_slice(&data[..data.len() - 1], version).is_err());
+        }
+        // v4 stake info accounts are too small to decode as v5
+        let data = vec![0_u8; STAKE_INFO_V4_ACCOUNT_SIZE];
+        assert!(RaydiumStakeInfo::try_from_slice(&data, RaydiumStakeVersion::V4).is_ok());
+        assert!(RaydiumStakeInfo::try_from_slice(&data, RaydiumStakeVersion::V5).is_err());
+    }
+
+    #[test]
+    fn test_estimate_rewards() {
+        for (version, program) in [
+            (RaydiumStakeVersion::V4, RAYDIUM_STAKE_V4),
+            (RaydiumStakeVersion::V5, RAYDIUM_STAKE_V5),
+        ] {
+            let pool = pool(version);
+            let pool_id = Pubkey::new_unique();
+            let mut vault = vault(pool_id, &pool, program);
+            assert_eq!(vault.stake_version(), Some(version));
+            let stake_info = RaydiumStakeInfo {
+                version,
+                state: 1,
+                pool_id,
+                owner: Pubkey::new_unique(),
+                deposited: 500,
+                // debt as of a per share reward of 1 and 0.25
+                reward_debt_a: 500,
+                reward_debt_b: 125,
+            };
+
+            // no slots have passed, so only the rewards accrued up to the last update are pending
+            assert_eq!(
+                vault
+                    .estimate_rewards(&pool, &stake_info, 1_000, pool.last_slot)
+                    .unwrap(),
+                RaydiumRewardsEstimate {
+                    pending_a: 500,
+                    pending_b: 125,
+                }
+            );
+            // 10 slots emit 1000 reward A and 300 reward B, of which the vault owns half
+            assert_eq!(
+                vault
+                    .estimate_rewards(&pool, &stake_info, 1_000, pool.last_slot + 10)
+                    .unwrap(),
+                RaydiumRewardsEstimate {
+                    pending_a: 1_000,
+                    pending_b: 275,
+                }
+            );
+            // no lp tokens are staked, so rewards per share do not change
+            assert_eq!(
+                stake_info
+                    .pending_rewards(&pool, 0, pool.last_slot + 10)
+                    .unwrap(),
+                (500, 125)
+            );
+
+            vault.dual_rewards = 0;
+            assert_eq!(
+                vault
+                    .estimate_rewards(&pool, &stake_info, 1_000, pool.last_slot + 10)
+                    .unwrap()
+                    .pending_b,
+                0
+            );
+
+            let other_pool = RaydiumStakeInfo {
+                pool_id: Pubkey::new_unique(),
+                ..stake_info
+            };
+            assert_eq!(
+                vault
+                    .estimate_rewards(&pool, &other_pool, 1_000, pool.last_slot)
+                    .unwrap_err(),
+                ProgramError::InvalidAccountData
+            );
+        }
+
+        let pool = pool(RaydiumStakeVersion::V4);
+        let mut vault = vault(Pubkey::new_unique(), &pool, RAYDIUM_STAKE_V5);
+        assert_eq!(
+            vault.stake_info_address(),
+            Some(vault.associated_stake_info_address)
+        );
+        let stake_info = RaydiumStakeInfo {
+            version: RaydiumStakeVersion::V5,
+            state: 1,
+            pool_id: vault.raydium_pool_id,
+            owner: Pubkey::new_unique(),
+            deposited: 0,
+            reward_debt_a: 0,
+            reward_debt_b: 0,
+        };
+        assert_eq!(
+            vault
+                .estimate_rewards(&pool, &stake_info, 1, 1)
+                .unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        vault.raydium_stake_program = Pubkey::new_unique();
+        assert_eq!(vault.stake_version(), None);
+        assert_eq!(vault.stake_info_address(), None);
+    }
+}
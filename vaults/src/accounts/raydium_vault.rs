@@ -2,7 +2,10 @@
 //! capable of supporting any v4 and onwards Raydium AMM farm
 use super::{vault_base::VaultBaseV1, InitVaultArgsV1};
 use anchor_lang::prelude::*;
+use tulipv2_sdk_common::math::decimal::Decimal;
+use tulipv2_sdk_common::math::error::MathError;
 use tulipv2_sdk_common::msg_panic;
+use tulipv2_sdk_common::pricing::raydium::RaydiumPoolReserves;
 use tulipv2_sdk_common::traits::vault::TokenizedShares;
 use tulipv2_sdk_farms::Farm;
 pub const VAULT_ACCOUNT_SIZE: usize = 1712;
@@ -70,6 +73,31 @@ pub struct RaydiumVaultV1 {
     pub buffer: [u8; 407],
 }
 
+impl RaydiumVaultV1 {
+    /// returns the amount of lp tokens that `shares` are redeemable for
+    pub fn shares_to_lp(&self, shares: u64) -> std::result::Result<u64, ProgramError> {
+        if self.base.total_shares == 0 {
+            return Err(MathError::MathOverflow.into());
+        }
+        let lp_amount = (shares as u128)
+            .checked_mul(self.base.total_deposited_balance as u128)
+            .ok_or(MathError::MathOverflow)?
+            .checked_div(self.base.total_shares as u128)
+            .ok_or(MathError::MathOverflow)?;
+        Ok(u64::try_from(lp_amount).map_err(|_| MathError::MathOverflow)?)
+    }
+    /// returns the value of `shares` at the given lp price, which is usually
+    /// obtained from `RaydiumPoolReserves::fair_lp_price`
+    pub fn shares_value(
+        &self,
+        shares: u64,
+        reserves: &RaydiumPoolReserves,
+        lp_price: Decimal,
+    ) -> std::result::Result<Decimal, ProgramError> {
+        reserves.lp_value(self.shares_to_lp(shares)?, lp_price)
+    }
+}
+
 impl super::Base for RaydiumVaultV1 {
     fn base(&self) -> VaultBaseV1 {
         self.base
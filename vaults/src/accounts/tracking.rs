@@ -2,6 +2,8 @@
 //! providing additional ephemeral account types used during withdrawal processes.
 use anchor_lang::prelude::*;
 
+use super::vault_base::Network;
+
 #[cfg(not(target_arch = "bpf"))]
 use tulip_derivative::*;
 use tulipv2_sdk_common::msg_panic;
@@ -86,6 +88,34 @@ pub struct EphemeralTrackingV1 {
     pub buffer: [u8; 254],
 }
 
+/// the withdrawal lock status of a deposit tracking account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockStatus {
+    /// whether or not shares are currently locked in the tracking account
+    pub locked: bool,
+    /// the first unix timestamp at which the shares are unlocked
+    pub unlocks_at: i64,
+    /// seconds until the shares are unlocked, 0 if already unlocked
+    pub seconds_remaining: i64,
+}
+
+impl DepositTrackingV1 {
+    /// returns the lock status of the tracking account at `unix_timestamp`, using the
+    /// lockup duration of `network`. unlike `TokenizedShares::is_locked` this doesn't
+    /// access the `Clock` sysvar, so may be used off-chain
+    pub fn lock_status(&self, network: Network, unix_timestamp: i64) -> LockStatus {
+        let unlocks_at = self
+            .last_deposit_time
+            .saturating_add(network.required_lock_duration())
+            .saturating_add(1);
+        LockStatus {
+            locked: network.is_locked(self.last_deposit_time, unix_timestamp),
+            unlocks_at,
+            seconds_remaining: unlocks_at.saturating_sub(unix_timestamp).max(0),
+        }
+    }
+}
+
 impl TokenizedSharesHolder for DepositTrackingV1 {
     fn shares_to_give(&self, vault: &impl TokenizedShares, amount: u64) -> u64 {
         if vault.total_deposited_tokens() == 0 {
@@ -141,3 +171,33 @@ impl TokenizedSharesHolder for DepositTrackingV1 {
         msg_panic!("noop");
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_lock_status() {
+        let mut tracking = <DepositTrackingV1 as bytemuck::Zeroable>::zeroed();
+        tracking.last_deposit_time = 1_000;
+        let status = tracking.lock_status(Network::Mainnet, 1_900);
+        assert_eq!(
+            status,
+            LockStatus {
+                locked: true,
+                unlocks_at: 1_901,
+                seconds_remaining: 1,
+            }
+        );
+        let status = tracking.lock_status(Network::Mainnet, 1_901);
+        assert!(!status.locked);
+        assert_eq!(status.seconds_remaining, 0);
+        let status = tracking.lock_status(Network::Staging, 1_030);
+        assert!(status.locked);
+        assert_eq!(status.seconds_remaining, 31);
+        assert!(!tracking.lock_status(Network::Localnet, 1_030).locked);
+        assert_eq!(
+            Network::default().required_lock_duration(),
+            crate::accounts::vault_base::REQUIRED_LOCK_DURATION_SECONDS
+        );
+    }
+}
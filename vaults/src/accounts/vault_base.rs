@@ -11,11 +11,11 @@ use tulipv2_sdk_common::math::uint::U192;
 use tulipv2_sdk_common::msg_panic;
 use tulipv2_sdk_common::{
     math,
+    tag::VaultTag,
     traits::{
         pausable::{Pausable, PausableAction},
         vault::TokenizedShares,
     },
-    vaults::tag_to_str,
 };
 use tulipv2_sdk_farms::Farm;
 
@@ -23,13 +23,19 @@ use tulipv2_sdk_farms::Farm;
 pub const VAULT_BASE_SIZE: usize = 560;
 #[cfg(not(feature = "staging"))]
 /// hard coded lockup time of 25 minutes
-pub const REQUIRED_LOCK_DURATION_SECONDS: i64 = 900;
+pub const REQUIRED_LOCK_DURATION_SECONDS: i64 = MAINNET_LOCK_DURATION_SECONDS;
 #[cfg(feature = "localnet")]
 /// hard coded lockup time of 14 seconds for localnet
-pub const REQUIRED_LOCK_DURATION_SECONDS: i64 = 14;
+pub const REQUIRED_LOCK_DURATION_SECONDS: i64 = LOCALNET_LOCK_DURATION_SECONDS;
 #[cfg(all(feature = "staging", not(feature = "localnet")))]
 /// hard coded lockup time of 60 seconds
-pub const REQUIRED_LOCK_DURATION_SECONDS: i64 = 60;
+pub const REQUIRED_LOCK_DURATION_SECONDS: i64 = STAGING_LOCK_DURATION_SECONDS;
+/// lockup time used by mainnet vaults
+pub const MAINNET_LOCK_DURATION_SECONDS: i64 = 900;
+/// lockup time used by staging vaults
+pub const STAGING_LOCK_DURATION_SECONDS: i64 = 60;
+/// lockup time used by localnet vaults
+pub const LOCALNET_LOCK_DURATION_SECONDS: i64 = 14;
 /// seconds per year defined as a constant decimal
 pub const SECONDS_PER_YEAR: Decimal = Decimal(U192([14_679_384_701_502_750_720, 1_710_705, 0]));
 /// the number `100` defined as a constant decimal
@@ -175,13 +181,56 @@ pub struct VaultBaseV1 {
 
 pub struct NewVaultArgsV1;
 
+/// the cluster a set of vaults is deployed to, allowing the deposit lockup
+/// duration to be selected at runtime instead of through cargo features
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Staging,
+    Localnet,
+}
+
+impl Network {
+    /// returns the minimum amount of time deposits are locked for
+    pub fn required_lock_duration(&self) -> i64 {
+        match self {
+            Network::Mainnet => MAINNET_LOCK_DURATION_SECONDS,
+            Network::Staging => STAGING_LOCK_DURATION_SECONDS,
+            Network::Localnet => LOCALNET_LOCK_DURATION_SECONDS,
+        }
+    }
+    /// returns true if a deposit made at `last_deposit_time` is still locked at `unix_timestamp`.
+    /// matches `TokenizedShares::is_locked`, without requiring access to the `Clock` sysvar
+    pub fn is_locked(&self, last_deposit_time: i64, unix_timestamp: i64) -> bool {
+        last_deposit_time.saturating_add(self.required_lock_duration()) >= unix_timestamp
+    }
+}
+
+/// defaults to the network selected by the enabled cargo features,
+/// matching `REQUIRED_LOCK_DURATION_SECONDS`
+impl Default for Network {
+    fn default() -> Self {
+        if cfg!(feature = "localnet") {
+            Network::Localnet
+        } else if cfg!(feature = "staging") {
+            Network::Staging
+        } else {
+            Network::Mainnet
+        }
+    }
+}
+
 impl VaultBaseV1 {
+    /// returns the tag used to derive the vault's address
+    pub fn vault_tag(&self) -> VaultTag {
+        VaultTag::from(self.tag)
+    }
     /// returns a formatted name of farm_name-tag({})
     /// for LENDING-USDC with a tag of solend it would be
     /// LENDING-USDC-tag(solend)
     pub fn formatted_name(&self) -> String {
         let farm = Farm::from(self.farm);
-        arrform!(512, "{}-tag({})", farm.name(), tag_to_str(&self.tag))
+        arrform!(512, "{}-tag({})", farm.name(), self.vault_tag())
             .as_str()
             .to_owned()
     }
@@ -1,6 +1,7 @@
 use crate::accounts::{
+    atrix_farm::{farm_address, FarmAccount},
     derive_compound_queue_address, derive_pda_address, derive_shares_mint_address,
-    derive_withdraw_queue_address,
+    derive_vault_address, derive_withdraw_queue_address,
 };
 use anchor_lang::solana_program::pubkey::Pubkey;
 use so_defi_atrix::addresses as atrix_addresses;
@@ -13,6 +14,8 @@ use tulipv2_sdk_common::config::deposit_tracking::traits::{
 use tulipv2_sdk_common::config::deposit_tracking::withdraw::WithdrawDepositTrackingAddresses;
 
 use super::VaultBaseConfig;
+use tulipv2_sdk_common::tag::VaultTag;
+use tulipv2_sdk_farms::{atrix::Atrix, Farm};
 
 pub struct AtrixVaultConfig {
     pub vault: Pubkey,
@@ -60,6 +63,27 @@ impl AtrixVaultConfig {
             staker_account,
         }
     }
+    /// derives the config of the untagged vault farming `name`, using the farm's state mint
+    /// as the underlying mint, and the farm's first crop as the harvested crop.
+    ///
+    /// returns None if the farm has no crops
+    pub fn from_farm_account(name: Atrix, farm: &FarmAccount) -> Option<Self> {
+        Self::from_farm_account_with_tag(name, farm, VaultTag::default())
+    }
+    /// same as `from_farm_account`, but derives the address of the vault using `tag`
+    pub fn from_farm_account_with_tag(
+        name: Atrix,
+        farm: &FarmAccount,
+        tag: impl Into<VaultTag>,
+    ) -> Option<Self> {
+        let vault = derive_vault_address(&Farm::Atrix { name }, tag).0;
+        Some(Self::new(
+            vault,
+            farm.state_mint,
+            Some(farm_address(farm)),
+            Some(farm.crop_accounts[0]?),
+        ))
+    }
     pub fn register_deposit_tracking(&self, authority: Pubkey) -> impl RegisterDepositTracking {
         RegisterDepositTrackingAddresses::new(
             authority,
@@ -110,4 +134,49 @@ impl VaultBaseConfig for AtrixVaultConfig {
     fn shares_mint(&self) -> Pubkey {
         self.shares_mint
     }
+    fn underlying_mint(&self) -> Pubkey {
+        self.underlying_mint
+    }
+    fn supports_farm(&self, farm_type: Farm) -> bool {
+        matches!(farm_type, Farm::Atrix { .. })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_atrix_config_from_farm_account() {
+        use so_defi_atrix::addresses::{
+            find_crop_address, find_farm_address, find_harvester_address, find_staker_address,
+        };
+        let mut farm = FarmAccount::default();
+        farm.base = Pubkey::new_unique();
+        farm.state_mint = Pubkey::new_unique();
+        assert!(AtrixVaultConfig::from_farm_account(Atrix::GMTUSDC, &farm).is_none());
+        let farm_key = find_farm_address(farm.base).0;
+        let crop_key = find_crop_address(farm_key, Pubkey::new_unique()).0;
+        farm.crop_accounts[0] = Some(crop_key);
+        let config = AtrixVaultConfig::from_farm_account(Atrix::GMTUSDC, &farm).unwrap();
+        let farm_type = Farm::Atrix {
+            name: Atrix::GMTUSDC,
+        };
+        assert_eq!(
+            config.vault,
+            derive_vault_address(&farm_type, VaultTag::default()).0
+        );
+        assert_eq!(config.underlying_mint, farm.state_mint);
+        assert_eq!(
+            config.staker_account,
+            Some(find_staker_address(farm_key, config.pda).0)
+        );
+        assert_eq!(
+            config.harvester_account,
+            Some(find_harvester_address(crop_key, config.pda).0)
+        );
+        let tagged =
+            AtrixVaultConfig::from_farm_account_with_tag(Atrix::GMTUSDC, &farm, [1_u8; 32])
+                .unwrap();
+        assert_ne!(tagged.vault, config.vault);
+    }
 }
@@ -3,7 +3,17 @@ pub mod deposit_tracking;
 pub mod orca;
 pub mod quarry;
 pub mod raydium;
-use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::solana_program::{instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::instruction::create_associated_token_account;
+use tulipv2_sdk_common::config::deposit_tracking::{
+    issue_shares::DepositAddresses,
+    register::RegisterDepositTrackingAddresses,
+    traits::{IssueShares, RegisterDepositTracking, WithdrawDepositTracking},
+    withdraw::WithdrawDepositTrackingAddresses,
+};
+use tulipv2_sdk_farms::Farm;
+
+use self::deposit_tracking::DepositTrackingConfig;
 
 /// base configuration accounts used across all vault types
 pub trait VaultBaseConfig {
@@ -13,4 +23,163 @@ pub trait VaultBaseConfig {
     fn compound_queue(&self) -> Pubkey;
     fn deposit_queue(&self) -> Pubkey;
     fn shares_mint(&self) -> Pubkey;
+    fn underlying_mint(&self) -> Pubkey;
+    /// returns true if `farm_type` belongs to the same platform as the vault
+    fn supports_farm(&self, farm_type: Farm) -> bool;
+    /// returns the instructions used to deposit `amount` of `depositing_mint` into the vault, and
+    /// to remove `shares` from the deposit tracking account once they unlock.
+    ///
+    /// returns an error if `depositing_mint` is not the vault's underlying mint, if `farm_type`
+    /// is not supported by the vault, or if any of the instructions could not be created
+    fn deposit_sequence(
+        &self,
+        authority: Pubkey,
+        depositing_mint: Pubkey,
+        farm_type: Farm,
+        amount: u64,
+        shares: u64,
+    ) -> Result<DepositSequence, DepositSequenceError> {
+        let underlying_mint = self.underlying_mint();
+        if depositing_mint != underlying_mint {
+            return Err(DepositSequenceError::MintMismatch {
+                expected: underlying_mint,
+                found: depositing_mint,
+            });
+        }
+        if !self.supports_farm(farm_type) {
+            return Err(DepositSequenceError::FarmMismatch(farm_type));
+        }
+        let tracking =
+            DepositTrackingConfig::new(authority, self.vault(), self.shares_mint(), false);
+        let register = RegisterDepositTrackingAddresses::new(
+            authority,
+            self.vault(),
+            self.shares_mint(),
+            underlying_mint,
+        );
+        let issue_shares = DepositAddresses::new(
+            authority,
+            self.vault(),
+            self.vault_pda(),
+            self.shares_mint(),
+            underlying_mint,
+        );
+        let withdraw =
+            WithdrawDepositTrackingAddresses::new(authority, self.vault(), self.shares_mint());
+        Ok(DepositSequence {
+            create_hold_account: create_associated_token_account(
+                &authority,
+                &tracking.tracking_pda,
+                &self.shares_mint(),
+            ),
+            register_deposit_tracking: register.instruction(farm_type).ok_or(
+                DepositSequenceError::InstructionUnavailable("register_deposit_tracking_account"),
+            )?,
+            issue_shares: issue_shares
+                .instruction(farm_type, amount)
+                .ok_or(DepositSequenceError::InstructionUnavailable("issue_shares"))?,
+            withdraw_deposit_tracking: withdraw.instruction(shares, farm_type).ok_or(
+                DepositSequenceError::InstructionUnavailable("withdraw_deposit_tracking"),
+            )?,
+            tracking,
+        })
+    }
+}
+
+/// bundles together the instructions used to deposit into a vault, and to later
+/// remove the issued shares from the user's deposit tracking account
+pub struct DepositSequence {
+    /// the deposit tracking accounts used by the deposit
+    pub tracking: DepositTrackingConfig,
+    /// creates the deposit tracking hold account, only required
+    /// if the deposit tracking account has not yet been registered
+    pub create_hold_account: Instruction,
+    /// registers the deposit tracking account, only required
+    /// if the deposit tracking account has not yet been registered
+    pub register_deposit_tracking: Instruction,
+    /// deposits the underlying asset, locking the issued shares into the deposit tracking account
+    pub issue_shares: Instruction,
+    /// removes shares from the deposit tracking account into the authority's shares ATA.
+    /// this will fail until the deposit tracking lockup period has elapsed
+    pub withdraw_deposit_tracking: Instruction,
+}
+
+impl DepositSequence {
+    /// returns the instructions used for a first time deposit, which register the
+    /// deposit tracking account before issuing shares
+    pub fn first_deposit_instructions(&self) -> Vec<Instruction> {
+        vec![
+            self.create_hold_account.clone(),
+            self.register_deposit_tracking.clone(),
+            self.issue_shares.clone(),
+        ]
+    }
+}
+
+/// the reason a deposit sequence could not be created
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepositSequenceError {
+    /// the depositing mint is not the vault's underlying mint
+    MintMismatch { expected: Pubkey, found: Pubkey },
+    /// the farm type belongs to a different platform than the vault
+    FarmMismatch(Farm),
+    /// the named instruction could not be created, either because its sighash
+    /// is unknown, or because the farm type failed to serialize
+    InstructionUnavailable(&'static str),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tulipv2_sdk_farms::atrix::Atrix;
+    #[test]
+    fn test_new_deposit_sequence() {
+        let vault = Pubkey::new_unique();
+        let underlying_mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let config = atrix::AtrixVaultConfig::new(vault, underlying_mint, None, None);
+        let farm = Farm::Atrix {
+            name: Atrix::SOLUSDC,
+        };
+        let wrong_mint = Pubkey::new_unique();
+        assert_eq!(
+            config
+                .deposit_sequence(authority, wrong_mint, farm, 100, 100)
+                .err(),
+            Some(DepositSequenceError::MintMismatch {
+                expected: underlying_mint,
+                found: wrong_mint,
+            })
+        );
+        let raydium_farm = Farm::Raydium {
+            name: tulipv2_sdk_farms::raydium::Raydium::RAYUSDC,
+        };
+        assert_eq!(
+            config
+                .deposit_sequence(authority, underlying_mint, raydium_farm, 100, 100)
+                .err(),
+            Some(DepositSequenceError::FarmMismatch(raydium_farm))
+        );
+        let sequence = config
+            .deposit_sequence(authority, underlying_mint, farm, 100, 90)
+            .unwrap();
+        assert_eq!(sequence.first_deposit_instructions().len(), 3);
+        assert_eq!(sequence.issue_shares.accounts[0].pubkey, authority);
+        assert_eq!(
+            sequence.issue_shares.accounts[2].pubkey,
+            sequence.tracking.tracking_account
+        );
+        assert_eq!(
+            sequence.issue_shares.accounts[7].pubkey,
+            sequence.tracking.tracking_hold
+        );
+        assert_eq!(
+            sequence.withdraw_deposit_tracking.accounts[5].pubkey,
+            sequence.tracking.tracking_hold
+        );
+        assert_eq!(
+            sequence.register_deposit_tracking.accounts[3].pubkey,
+            sequence.tracking.tracking_queue
+        );
+    }
 }
@@ -16,6 +16,7 @@ use tulipv2_sdk_common::config::deposit_tracking::traits::{
 use tulipv2_sdk_common::config::deposit_tracking::withdraw::WithdrawDepositTrackingAddresses;
 
 use super::VaultBaseConfig;
+use tulipv2_sdk_farms::Farm;
 
 #[derive(Debug)]
 pub struct OrcaVaultConfig {
@@ -181,4 +182,10 @@ impl VaultBaseConfig for OrcaVaultConfig {
     fn shares_mint(&self) -> Pubkey {
         self.shares_mint
     }
+    fn underlying_mint(&self) -> Pubkey {
+        self.underlying_mint
+    }
+    fn supports_farm(&self, farm_type: Farm) -> bool {
+        matches!(farm_type, Farm::Orca { .. })
+    }
 }
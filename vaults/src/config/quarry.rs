@@ -8,6 +8,15 @@ use crate::accounts::{
 };
 
 use anchor_lang::solana_program::pubkey::Pubkey;
+use tulipv2_sdk_common::config::deposit_tracking::issue_shares::{
+    DepositAddresses, DepositAddressesPermissioned,
+};
+use tulipv2_sdk_common::config::deposit_tracking::register::RegisterDepositTrackingAddresses;
+use tulipv2_sdk_common::config::deposit_tracking::traits::{
+    IssueShares, RegisterDepositTracking, WithdrawDepositTracking,
+};
+use tulipv2_sdk_common::config::deposit_tracking::withdraw::WithdrawDepositTrackingAddresses;
+use tulipv2_sdk_farms::Farm;
 
 use super::VaultBaseConfig;
 
@@ -142,6 +151,35 @@ impl QuarryVaultConfig {
             miner_token_account: quarry_miner_token_account,
         }
     }
+    pub fn register_deposit_tracking(&self, authority: Pubkey) -> impl RegisterDepositTracking {
+        RegisterDepositTrackingAddresses::new(
+            authority,
+            self.vault,
+            self.shares_mint,
+            self.underlying_mint,
+        )
+    }
+    pub fn issue_shares(&self, authority: Pubkey) -> impl IssueShares {
+        DepositAddresses::new(
+            authority,
+            self.vault,
+            self.pda,
+            self.shares_mint,
+            self.underlying_mint,
+        )
+    }
+    pub fn permissioned_issue_shares(&self, authority: Pubkey) -> impl IssueShares {
+        DepositAddressesPermissioned::new(
+            authority,
+            self.vault,
+            self.pda,
+            self.shares_mint,
+            self.underlying_mint,
+        )
+    }
+    pub fn withdraw_deposit_tracking(&self, authority: Pubkey) -> impl WithdrawDepositTracking {
+        WithdrawDepositTrackingAddresses::new(authority, self.vault, self.shares_mint)
+    }
 }
 
 impl VaultBaseConfig for QuarryVaultConfig {
@@ -163,4 +201,10 @@ impl VaultBaseConfig for QuarryVaultConfig {
     fn shares_mint(&self) -> Pubkey {
         self.shares_mint
     }
+    fn underlying_mint(&self) -> Pubkey {
+        self.underlying_mint
+    }
+    fn supports_farm(&self, farm_type: Farm) -> bool {
+        matches!(farm_type, Farm::Quarry { .. })
+    }
 }
@@ -14,6 +14,7 @@ use tulipv2_sdk_common::config::deposit_tracking::traits::{
 use tulipv2_sdk_common::config::deposit_tracking::withdraw::WithdrawDepositTrackingAddresses;
 
 use super::VaultBaseConfig;
+use tulipv2_sdk_farms::Farm;
 
 pub struct RaydiumVaultConfig {
     pub vault: Pubkey,
@@ -159,4 +160,10 @@ impl VaultBaseConfig for RaydiumVaultConfig {
     fn shares_mint(&self) -> Pubkey {
         self.shares_mint
     }
+    fn underlying_mint(&self) -> Pubkey {
+        self.underlying_mint
+    }
+    fn supports_farm(&self, farm_type: Farm) -> bool {
+        matches!(farm_type, Farm::Raydium { .. })
+    }
 }
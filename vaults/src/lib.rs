@@ -8,7 +8,7 @@ use anchor_lang::{
     solana_program::{self, pubkey::Pubkey},
 };
 use static_pubkey::static_pubkey;
-use tulipv2_sdk_common::{config::strategy::StrategyVaults, tag::tag_to_str};
+use tulipv2_sdk_common::{config::strategy::StrategyVaults, tag::VaultTag};
 use tulipv2_sdk_farms::{lending::Lending, Farm};
 
 pub const ID: Pubkey = static_pubkey!("TLPv2tuSVvn3fSk8RgW3yPddkp5oFivzZV3rA9hQxtX");
@@ -25,9 +25,8 @@ pub fn into_strategy_vault<'info>(account: &AccountInfo<'info>) -> StrategyVault
         match farm {
             Farm::Lending { name } => match name {
                 Lending::MULTI_DEPOSIT => {
-                    let mut tag = tag_to_str(&vault.base.tag);
-                    tag.make_ascii_lowercase();
-                    match tag.as_str() {
+                    let tag = VaultTag::from(vault.base.tag).to_ascii_lowercase();
+                    match tag.as_str().unwrap_or_default() {
                         "usdcv1" => StrategyVaults::USDCv1,
                         "usdtv1" => StrategyVaults::USDTv1,
                         "solv1" => StrategyVaults::SOLv1,
@@ -45,65 +44,65 @@ pub fn into_strategy_vault<'info>(account: &AccountInfo<'info>) -> StrategyVault
 #[cfg(test)]
 mod test {
     use anchor_lang::{AccountDeserialize, solana_program::program_pack::Pack};
+    use tulipv2_sdk_common::fixtures::{AccountProvider, JsonFileAccountProvider};
     use tulipv2_sdk_common::traits::vault::TokenizedShares;
     use super::*;
-    use anchor_lang::solana_program::{self, pubkey::Pubkey, account_info::IntoAccountInfo};
-    use solana_client::rpc_client::RpcClient;
     use static_pubkey::static_pubkey;
+
+    fn strategy_vault(vault_key: Pubkey) -> StrategyVaults {
+        let mut account = JsonFileAccountProvider::fixtures()
+            .get_account(&vault_key)
+            .expect("missing fixture, record it with scripts/record_fixtures.sh");
+        into_strategy_vault(&account.account_info(&vault_key))
+    }
     #[test]
     fn test_into_strategy_vault_usdcv1() {
         let vault_key = static_pubkey!("3wPiV9inTGexMZjp6x5Amqwp2sRNtpSheG8Hbv2rgq8W");
-        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
-        let account = rpc.get_account(&vault_key).unwrap();
-
-        let mut acct_tup = (vault_key, account);
-        let mut acct = acct_tup.into_account_info();
-
-        let strat_vault = into_strategy_vault(&acct);
-        assert!(strat_vault.eq(&StrategyVaults::USDCv1));
+        assert!(strategy_vault(vault_key).eq(&StrategyVaults::USDCv1));
     }
     #[test]
+    #[ignore = "requires the usdtv1 vault fixture, record it with scripts/record_fixtures.sh"]
     fn test_into_strategy_vault_usdtv1() {
         let vault_key = static_pubkey!("BBRkN5paHbHLku4KrZMN8Mc5U3Ygasd4v2FtxdwG7F8F");
-        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
-        let account = rpc.get_account(&vault_key).unwrap();
-
-        let mut acct_tup = (vault_key, account);
-        let mut acct = acct_tup.into_account_info();
-
-        let strat_vault = into_strategy_vault(&acct);
-        assert!(strat_vault.eq(&StrategyVaults::USDTv1));
+        assert!(strategy_vault(vault_key).eq(&StrategyVaults::USDTv1));
     }
     #[test]
+    #[ignore = "requires the rayv1 vault fixture, record it with scripts/record_fixtures.sh"]
     fn test_into_strategy_vault_rayv1() {
         let vault_key = static_pubkey!("EH1iQnhDqQpHsVJWLw8oC1ehDqVaPGh7JH6ctG4dAQ2d");
-        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
-        let account = rpc.get_account(&vault_key).unwrap();
-
-        let mut acct_tup = (vault_key, account);
-        let mut acct = acct_tup.into_account_info();
-
-        let strat_vault = into_strategy_vault(&acct);
-        assert!(strat_vault.eq(&StrategyVaults::RAYv1));
+        assert!(strategy_vault(vault_key).eq(&StrategyVaults::RAYv1));
     }
     #[test]
+    #[ignore = "requires the solv1 vault fixture, record it with scripts/record_fixtures.sh"]
     fn test_into_strategy_vault_solv1() {
         let vault_key = static_pubkey!("2WNw7tW2G54UCXN726S5tR9XutSEDeMf7xamidQtWszK");
-        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
-        let account = rpc.get_account(&vault_key).unwrap();
-
-        let mut acct_tup = (vault_key, account);
-        let mut acct = acct_tup.into_account_info();
-
-        let strat_vault = into_strategy_vault(&acct);
-        assert!(strat_vault.eq(&StrategyVaults::SOLv1));
+        assert!(strategy_vault(vault_key).eq(&StrategyVaults::SOLv1));
+    }
+    /// same as `strategy_vault`, but builds the vault account in memory, covering
+    /// vaults which are not yet recorded to the fixtures
+    fn in_memory_strategy_vault(tag: &str) -> StrategyVaults {
+        use anchor_lang::Discriminator;
+        let mut vault = <MultiDepositOptimizerV1 as bytemuck::Zeroable>::zeroed();
+        vault.base.farm = Farm::Lending { name: Lending::MULTI_DEPOSIT }.into();
+        vault.base.tag = VaultTag::new(tag).unwrap().into();
+        let mut data = MultiDepositOptimizerV1::discriminator().to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&vault));
+        let key = Pubkey::new_unique();
+        let mut account = tulipv2_sdk_common::fixtures::FixtureAccount::new(0, &data, ID);
+        into_strategy_vault(&account.account_info(&key))
     }
     #[test]
-    fn test_rayv1_exchange_rate() {
-        let vault_key = static_pubkey!("EH1iQnhDqQpHsVJWLw8oC1ehDqVaPGh7JH6ctG4dAQ2d");
-        let rpc = RpcClient::new("https://ssc-dao.genesysgo.net".to_string());
-        let mut strat_vault = MultiDepositOptimizerV1::try_deserialize_unchecked(&mut &rpc.get_account(&vault_key).unwrap().data[..]).unwrap();
-        let share_mint = spl_token::state::Mint::unpack_unchecked(&mut &rpc.get_account(&strat_vault.base.shares_mint).unwrap().data[..]).unwrap();
+    fn test_into_strategy_vault_in_memory() {
+        assert!(in_memory_strategy_vault("usdcv1").eq(&StrategyVaults::USDCv1));
+        assert!(in_memory_strategy_vault("usdtv1").eq(&StrategyVaults::USDTv1));
+        assert!(in_memory_strategy_vault("rayv1").eq(&StrategyVaults::RAYv1));
+        assert!(in_memory_strategy_vault("solv1").eq(&StrategyVaults::SOLv1));
+    }
+    fn test_exchange_rate(vault_key: Pubkey) {
+        let fixtures = JsonFileAccountProvider::fixtures();
+        let vault_account = fixtures.get_account(&vault_key).unwrap();
+        let mut strat_vault = MultiDepositOptimizerV1::try_deserialize_unchecked(&mut vault_account.data()).unwrap();
+        let share_mint = spl_token::state::Mint::unpack_unchecked(fixtures.get_account(&strat_vault.base.shares_mint).unwrap().data()).unwrap();
 
         // this will update the vault state synchronizing shares issued tracked 
         // by the vault with the actual supply of the mint itself.
@@ -115,8 +114,18 @@ mod test {
         // the multi deposit vault state, this may be useful off-chain
         let exch_rate = strat_vault.base.exchange_rate(&share_mint);
         println!("exchange rate {}", exch_rate);
+        assert_eq!(strat_vault.base.total_shares, share_mint.supply);
         // doesn't synchronize the vault state intended for off-chain usage
-        let exch_rate = strat_vault.base.cached_exchange_rate(&share_mint);
-        println!("exchange rate {}", exch_rate);
+        let cached_exch_rate = strat_vault.base.cached_exchange_rate(&share_mint);
+        assert_eq!(cached_exch_rate, exch_rate);
+    }
+    #[test]
+    #[ignore = "requires the rayv1 vault and shares mint fixtures, record them with scripts/record_fixtures.sh"]
+    fn test_rayv1_exchange_rate() {
+        test_exchange_rate(static_pubkey!("EH1iQnhDqQpHsVJWLw8oC1ehDqVaPGh7JH6ctG4dAQ2d"));
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_usdcv1_exchange_rate() {
+        test_exchange_rate(static_pubkey!("3wPiV9inTGexMZjp6x5Amqwp2sRNtpSheG8Hbv2rgq8W"));
+    }
+}